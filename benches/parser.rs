@@ -0,0 +1,186 @@
+//! Parser benchmarks over realistic traffic and hostile payloads.
+//!
+//! Throughput is reported in packets/s. Before each benchmark, the heap allocations
+//! made to parse one packet are counted and printed, as `<benchmark>: <n> allocations
+//! per packet`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::measurement::WallTime;
+use criterion::{
+    black_box, criterion_group, criterion_main, BenchmarkGroup, Criterion, Throughput,
+};
+use detect_dns_packet::{DnsHeader, DnsPacket, DnsPacketRef, DnsQuery};
+
+use common::{RESPONSE, RTCP};
+
+mod common;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+// The SSL payload of the lib.rs tests.
+const SSL: &str = "8746a7014094af07a47e9b7f";
+
+fn push_u16(message: &mut Vec<u8>, value: u16) {
+    message.extend_from_slice(&value.to_be_bytes());
+}
+
+/// Appends a record owned by the question name (a pointer to offset 12).
+fn push_record(message: &mut Vec<u8>, rtype: u16, rdata: &[u8]) {
+    push_u16(message, 0xC00C);
+    push_u16(message, rtype);
+    push_u16(message, 1);
+    message.extend_from_slice(&3600u32.to_be_bytes());
+    push_u16(message, rdata.len() as u16);
+    message.extend_from_slice(rdata);
+}
+
+/// A signed DNSKEY response of about 3 KB, as returned with EDNS and the DO bit: four
+/// 2048-bit keys, each with its RRSIG, and an OPT record.
+fn dnssec_response() -> Vec<u8> {
+    let mut message = vec![0x12, 0x34, 0x81, 0x80, 0, 1, 0, 8, 0, 0, 0, 1];
+    message.extend_from_slice(b"\x07example\x03com\x00");
+    push_u16(&mut message, 48); // DNSKEY
+    push_u16(&mut message, 1);
+    for key in 0..4u8 {
+        let mut dnskey = vec![0x01, 0x01, 3, 8];
+        dnskey.extend((0..260).map(|i| (i as u8) ^ key));
+        push_record(&mut message, 48, &dnskey);
+
+        let mut rrsig = vec![0, 48, 8, 2, 0, 0, 0x0E, 0x10];
+        rrsig.extend_from_slice(&[0x66, 0x00, 0x00, 0x00, 0x65, 0x00, 0x00, 0x00]);
+        rrsig.extend_from_slice(&[0x4F, 0x66]);
+        rrsig.extend_from_slice(b"\x07example\x03com\x00");
+        rrsig.extend((0..256).map(|i| (i as u8).wrapping_mul(key + 1)));
+        push_record(&mut message, 46, &rrsig);
+    }
+    message.extend_from_slice(&[0, 0, 41, 0x04, 0xD0, 0, 0, 0x80, 0, 0, 0]);
+    message
+}
+
+/// A response whose 200 answers are owned by the end of a chain of 1000 compression
+/// pointers, each pointing to the previous one: every owner name costs 1000 jumps.
+fn compression_chain() -> Vec<u8> {
+    const LINKS: usize = 1000;
+    const ANSWERS: u16 = 200;
+
+    let mut message = vec![0xAB, 0xCD, 0x81, 0x80, 0, 1];
+    push_u16(&mut message, ANSWERS + 1);
+    message.extend_from_slice(&[0, 0, 0, 0]);
+    message.extend_from_slice(b"\x01a\x00\x00\x01\x00\x01");
+
+    // A NULL record whose data is the chain, the first link pointing to the question.
+    let chain_start = message.len() + 12;
+    let mut chain = Vec::with_capacity(LINKS * 2);
+    chain.extend_from_slice(&0xC00Cu16.to_be_bytes());
+    for link in 1..LINKS {
+        let target = chain_start + (link - 1) * 2;
+        chain.extend_from_slice(&(0xC000 | target as u16).to_be_bytes());
+    }
+    push_record(&mut message, 10, &chain);
+
+    let last_link = 0xC000 | (chain_start + (LINKS - 1) * 2) as u16;
+    for _ in 0..ANSWERS {
+        push_u16(&mut message, last_link);
+        push_u16(&mut message, 1);
+        push_u16(&mut message, 1);
+        message.extend_from_slice(&3600u32.to_be_bytes());
+        push_u16(&mut message, 4);
+        message.extend_from_slice(&[192, 0, 2, 1]);
+    }
+    message
+}
+
+/// The payloads, with whether they are valid DNS.
+fn payloads() -> Vec<(&'static str, Vec<u8>, bool)> {
+    vec![
+        ("response", hex::decode(RESPONSE).unwrap(), true),
+        ("dnssec", dnssec_response(), true),
+        ("compression_chain", compression_chain(), true),
+        ("rtcp", hex::decode(RTCP).unwrap(), false),
+        ("ssl", hex::decode(SSL).unwrap(), false),
+    ]
+}
+
+fn group<'a>(c: &'a mut Criterion, name: &str) -> BenchmarkGroup<'a, WallTime> {
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Elements(1));
+    group
+}
+
+/// Benchmarks `parse` on one packet, after printing the allocations it makes.
+fn bench<R>(group: &mut BenchmarkGroup<WallTime>, name: &str, id: &str, parse: impl Fn() -> R) {
+    let start = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(parse());
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - start;
+    println!("{name}/{id}: {allocations} allocations per packet");
+
+    group.bench_function(id, |b| b.iter(&parse));
+}
+
+fn bench_sections(c: &mut Criterion) {
+    let response = hex::decode(RESPONSE).unwrap();
+
+    let mut header = group(c, "header");
+    bench(&mut header, "header", "DnsHeader", || {
+        DnsHeader::try_from(black_box(&response[..12])).is_ok()
+    });
+    header.finish();
+
+    let mut queries = group(c, "queries");
+    bench(&mut queries, "queries", "DnsQuery", || {
+        let mut offset = 12;
+        DnsQuery::from_bytes(black_box(&response), &mut offset).is_ok()
+    });
+    queries.finish();
+}
+
+fn bench_messages(c: &mut Criterion) {
+    for (name, payload, is_dns) in payloads() {
+        assert_eq!(
+            DnsPacket::try_from(payload.as_slice()).is_ok(),
+            is_dns,
+            "{name}"
+        );
+        let name = format!("message/{name}");
+        let mut message = group(c, &name);
+        bench(&mut message, &name, "DnsPacket", || {
+            DnsPacket::try_from(black_box(payload.as_slice())).is_ok()
+        });
+        bench(
+            &mut message,
+            &name,
+            "DnsPacketRef",
+            || match DnsPacketRef::try_from(black_box(payload.as_slice())) {
+                Ok(packet) => packet.records().count(),
+                Err(_) => 0,
+            },
+        );
+        message.finish();
+    }
+}
+
+criterion_group!(benches, bench_sections, bench_messages);
+criterion_main!(benches);
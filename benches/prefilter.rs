@@ -0,0 +1,25 @@
+//! Compares `is_probably_dns` with a full parse, on a DNS response and on non-DNS noise.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use detect_dns_packet::{is_probably_dns, DnsPacket};
+
+use common::{RESPONSE, RTCP};
+
+mod common;
+
+fn bench_prefilter(c: &mut Criterion) {
+    for (name, payload) in [("response", RESPONSE), ("rtcp", RTCP)] {
+        let payload = hex::decode(payload).unwrap();
+        let mut group = c.benchmark_group(name);
+        group.bench_function("is_probably_dns", |b| {
+            b.iter(|| is_probably_dns(black_box(&payload)))
+        });
+        group.bench_function("try_from", |b| {
+            b.iter(|| DnsPacket::try_from(black_box(payload.as_slice())).is_ok())
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_prefilter);
+criterion_main!(benches);
@@ -0,0 +1,759 @@
+//! C-DNS blocks: the deduplicated tables and the query/response items referring to them.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use super::cbor::Cbor;
+use super::errors::CdnsError;
+use super::{QueryResponse, Transport, MAJOR_FORMAT_VERSION, MINOR_FORMAT_VERSION};
+use crate::dns_records::rdata::RData;
+use crate::dns_records::{parse_name, write_name};
+use crate::utils::dns_class::DnsClass;
+use crate::utils::dns_types::{known_types, DnsType, DnsTypes};
+use crate::{DnsHeader, DnsPacket, DnsQueries, DnsQuery, ResourceRecord};
+
+/// Times are written in microseconds.
+const TICKS_PER_SECOND: u64 = 1_000_000;
+
+// Query/response signature flags.
+const QUERY_PRESENT: u64 = 0x01;
+const RESPONSE_PRESENT: u64 = 0x02;
+const QUERY_HAS_OPT: u64 = 0x04;
+const RESPONSE_HAS_OPT: u64 = 0x08;
+const QUERY_HAS_NO_QUESTION: u64 = 0x10;
+const RESPONSE_HAS_NO_QUESTION: u64 = 0x20;
+
+// Storage hints: every query/response field written, except the hop limit and the
+// response processing data; every signature field except the query type.
+const QUERY_RESPONSE_HINTS: u64 = 0x3FBDF;
+const QUERY_RESPONSE_SIGNATURE_HINTS: u64 = 0x1FFF7;
+const RR_HINTS: u64 = 0x03;
+
+/// The file preamble, describing a single set of block parameters.
+pub(crate) fn preamble(max_block_items: u64) -> Cbor {
+    let storage_hints = Cbor::int_map([
+        (0, Some(Cbor::Unsigned(QUERY_RESPONSE_HINTS))),
+        (1, Some(Cbor::Unsigned(QUERY_RESPONSE_SIGNATURE_HINTS))),
+        (2, Some(Cbor::Unsigned(RR_HINTS))),
+        (3, Some(Cbor::Unsigned(0))),
+    ]);
+    let opcodes = [0, 1, 2, 4, 5].map(Cbor::Unsigned).to_vec();
+    let rr_types = known_types()
+        .map(|rtype| Cbor::Unsigned(rtype.0 as u64))
+        .collect();
+    let storage_parameters = Cbor::int_map([
+        (0, Some(Cbor::Unsigned(TICKS_PER_SECOND))),
+        (1, Some(Cbor::Unsigned(max_block_items))),
+        (2, Some(storage_hints)),
+        (3, Some(Cbor::Array(opcodes))),
+        (4, Some(Cbor::Array(rr_types))),
+    ]);
+    let block_parameters = Cbor::int_map([(0, Some(storage_parameters))]);
+    Cbor::int_map([
+        (0, Some(Cbor::Unsigned(MAJOR_FORMAT_VERSION))),
+        (1, Some(Cbor::Unsigned(MINOR_FORMAT_VERSION))),
+        (3, Some(Cbor::Array(vec![block_parameters]))),
+    ])
+}
+
+/// Checks the file preamble and returns the ticks per second of each block parameters.
+pub(crate) fn parse_preamble(preamble: &Cbor) -> Result<Vec<u64>, CdnsError> {
+    let major = required(preamble, 0, "major-format-version")?.as_u64()?;
+    let minor = required(preamble, 1, "minor-format-version")?.as_u64()?;
+    if major != MAJOR_FORMAT_VERSION {
+        return Err(CdnsError::UnsupportedVersion { major, minor });
+    }
+    required(preamble, 3, "block-parameters")?
+        .as_array()?
+        .iter()
+        .map(|parameters| {
+            let storage = required(parameters, 0, "storage-parameters")?;
+            match required(storage, 0, "ticks-per-second")?.as_u64()? {
+                0 => Err(CdnsError::InvalidField("ticks-per-second")),
+                ticks_per_second => Ok(ticks_per_second),
+            }
+        })
+        .collect()
+}
+
+fn required<'a>(map: &'a Cbor, key: u64, name: &'static str) -> Result<&'a Cbor, CdnsError> {
+    map.get(key)?.ok_or(CdnsError::MissingField(name))
+}
+
+fn optional_u64(map: &Cbor, key: u64) -> Result<Option<u64>, CdnsError> {
+    map.get(key)?.map(Cbor::as_u64).transpose()
+}
+
+fn narrow<T: TryFrom<u64>>(value: u64, name: &'static str) -> Result<T, CdnsError> {
+    T::try_from(value).map_err(|_| CdnsError::InvalidField(name))
+}
+
+/// A block table, in which each distinct value is stored once.
+struct Table<T> {
+    items: Vec<T>,
+    indices: HashMap<T, u64>,
+}
+
+impl<T> Default for Table<T> {
+    fn default() -> Self {
+        Table {
+            items: Vec::new(),
+            indices: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Clone + Eq + Hash> Table<T> {
+    /// Returns the (zero-based) index of `item`, adding it if needed.
+    fn insert(&mut self, item: T) -> u64 {
+        if let Some(&index) = self.indices.get(&item) {
+            return index;
+        }
+        let index = self.items.len() as u64;
+        self.indices.insert(item.clone(), index);
+        self.items.push(item);
+        index
+    }
+
+    /// Encodes the table, `None` when it is empty since tables are optional.
+    fn encode(&self, encode_item: impl Fn(&T) -> Cbor) -> Option<Cbor> {
+        (!self.items.is_empty()).then(|| Cbor::Array(self.items.iter().map(encode_item).collect()))
+    }
+}
+
+fn indices(list: &[u64]) -> Cbor {
+    Cbor::Array(list.iter().map(|&index| Cbor::Unsigned(index)).collect())
+}
+
+/// The fields shared by many query/response items.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Signature {
+    server_address: u64,
+    server_port: u16,
+    transport_flags: u8,
+    flags: u64,
+    opcode: u8,
+    dns_flags: u64,
+    query_rcode: Option<u8>,
+    classtype: Option<u64>,
+    query_counts: Option<[u16; 4]>,
+    edns_version: Option<u8>,
+    udp_size: Option<u16>,
+    opt_rdata: Option<u64>,
+    response_rcode: Option<u8>,
+}
+
+impl Signature {
+    fn encode(&self) -> Cbor {
+        let unsigned = |value: Option<u64>| value.map(Cbor::Unsigned);
+        let counts = self
+            .query_counts
+            .map(|counts| counts.map(|count| count as u64));
+        Cbor::int_map([
+            (0, unsigned(Some(self.server_address))),
+            (1, unsigned(Some(self.server_port as u64))),
+            (2, unsigned(Some(self.transport_flags as u64))),
+            (4, unsigned(Some(self.flags))),
+            (5, unsigned(Some(self.opcode as u64))),
+            (6, unsigned(Some(self.dns_flags))),
+            (7, unsigned(self.query_rcode.map(u64::from))),
+            (8, unsigned(self.classtype)),
+            (9, unsigned(counts.map(|counts| counts[0]))),
+            (10, unsigned(counts.map(|counts| counts[1]))),
+            (11, unsigned(counts.map(|counts| counts[2]))),
+            (12, unsigned(counts.map(|counts| counts[3]))),
+            (13, unsigned(self.edns_version.map(u64::from))),
+            (14, unsigned(self.udp_size.map(u64::from))),
+            (15, unsigned(self.opt_rdata)),
+            (16, unsigned(self.response_rcode.map(u64::from))),
+        ])
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Rr {
+    name: u64,
+    classtype: u64,
+    ttl: u32,
+    rdata: u64,
+}
+
+struct Item {
+    time: Duration,
+    client_address: u64,
+    client_port: u16,
+    transaction_id: u16,
+    signature: u64,
+    query_name: Option<u64>,
+    query_size: Option<u32>,
+    response_size: Option<u32>,
+    response_delay: Option<Duration>,
+    query_extended: Option<Cbor>,
+    response_extended: Option<Cbor>,
+}
+
+/// Accumulates query/response items and their tables until the block is written.
+#[derive(Default)]
+pub(crate) struct BlockBuilder {
+    addresses: Table<Vec<u8>>,
+    classtypes: Table<(DnsType, DnsClass)>,
+    names_rdata: Table<Vec<u8>>,
+    signatures: Table<Signature>,
+    question_lists: Table<Vec<u64>>,
+    questions: Table<(u64, u64)>,
+    rr_lists: Table<Vec<u64>>,
+    rrs: Table<Rr>,
+    items: Vec<Item>,
+}
+
+fn address_bytes(address: IpAddr) -> Vec<u8> {
+    match address {
+        IpAddr::V4(address) => address.octets().to_vec(),
+        IpAddr::V6(address) => address.octets().to_vec(),
+    }
+}
+
+fn opt_record(packet: &DnsPacket) -> Option<&ResourceRecord> {
+    packet
+        .additionals
+        .iter()
+        .flatten()
+        .find(|record| record.rtype == DnsTypes::OPT)
+}
+
+fn dns_flags(packet: &DnsPacket) -> u64 {
+    // AA, TC, RD, RA, Z, AD and CD, from the header bit 10 down to bit 4.
+    ((packet.header.flags >> 4) & 0x7F) as u64
+}
+
+impl BlockBuilder {
+    pub(crate) fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn name(&mut self, name: &str) -> u64 {
+        let mut wire = Vec::new();
+        write_name(name, &mut wire);
+        self.names_rdata.insert(wire)
+    }
+
+    fn question(&mut self, query: &DnsQuery) -> (u64, u64) {
+        (
+            self.name(&query.name),
+            self.classtypes.insert((query.qtype, query.qclass)),
+        )
+    }
+
+    fn rr_list(&mut self, records: &Option<Vec<ResourceRecord>>) -> Option<u64> {
+        let list = records
+            .iter()
+            .flatten()
+            .map(|record| {
+                let mut rdata = Vec::new();
+                record.rdata.to_wire(&mut rdata);
+                let rr = Rr {
+                    name: self.name(&record.name),
+                    classtype: self.classtypes.insert((record.rtype, record.rclass)),
+                    ttl: record.ttl,
+                    rdata: self.names_rdata.insert(rdata),
+                };
+                self.rrs.insert(rr)
+            })
+            .collect::<Vec<_>>();
+        records.as_ref().map(|_| self.rr_lists.insert(list))
+    }
+
+    /// The sections not covered by the signature: the questions after the first one and
+    /// the three record sections.
+    fn extended(&mut self, packet: &DnsPacket) -> Option<Cbor> {
+        let questions = packet.queries.queries.iter().skip(1).collect::<Vec<_>>();
+        let question_list = (!questions.is_empty()).then(|| {
+            let list = questions
+                .into_iter()
+                .map(|query| {
+                    let question = self.question(query);
+                    self.questions.insert(question)
+                })
+                .collect();
+            self.question_lists.insert(list)
+        });
+        let extended = Cbor::int_map([
+            (0, question_list.map(Cbor::Unsigned)),
+            (1, self.rr_list(&packet.answers).map(Cbor::Unsigned)),
+            (2, self.rr_list(&packet.authorities).map(Cbor::Unsigned)),
+            (3, self.rr_list(&packet.additionals).map(Cbor::Unsigned)),
+        ]);
+        match &extended {
+            Cbor::Map(entries) if entries.is_empty() => None,
+            _ => Some(extended),
+        }
+    }
+
+    pub(crate) fn push(&mut self, item: &QueryResponse) {
+        let query = item.query.as_ref();
+        let response = item.response.as_ref();
+        let first = query.or(response);
+        let first_question = first.and_then(|packet| packet.queries.queries.first());
+        let (query_name, classtype) = match first_question {
+            Some(question) => {
+                let (name, classtype) = self.question(question);
+                (Some(name), Some(classtype))
+            }
+            None => (None, None),
+        };
+
+        let mut flags = 0;
+        let mut dns_flags_bits = 0;
+        let mut signature = Signature {
+            server_address: self.addresses.insert(address_bytes(item.server.ip())),
+            server_port: item.server.port(),
+            transport_flags: (item.server.is_ipv6() as u8) | (item.transport.0 & 0x0F) << 1,
+            flags: 0,
+            opcode: first.map_or(0, |packet| ((packet.header.flags >> 11) & 0x0F) as u8),
+            dns_flags: 0,
+            query_rcode: None,
+            classtype,
+            query_counts: None,
+            edns_version: None,
+            udp_size: None,
+            opt_rdata: None,
+            response_rcode: None,
+        };
+        if let Some(query) = query {
+            flags |= QUERY_PRESENT;
+            dns_flags_bits |= dns_flags(query);
+            if query.queries.queries.is_empty() {
+                flags |= QUERY_HAS_NO_QUESTION;
+            }
+            if let Some(opt) = opt_record(query) {
+                flags |= QUERY_HAS_OPT;
+                if opt.ttl & 0x8000 != 0 {
+                    dns_flags_bits |= 0x80; // query-do
+                }
+                let mut rdata = Vec::new();
+                opt.rdata.to_wire(&mut rdata);
+                signature.edns_version = Some((opt.ttl >> 16) as u8);
+                signature.udp_size = Some(opt.rclass.0);
+                signature.opt_rdata = Some(self.names_rdata.insert(rdata));
+            }
+            signature.query_rcode = Some((query.header.flags & 0x0F) as u8);
+            signature.query_counts = Some(query.header.counts);
+        }
+        if let Some(response) = response {
+            flags |= RESPONSE_PRESENT;
+            dns_flags_bits |= dns_flags(response) << 8;
+            if response.queries.queries.is_empty() {
+                flags |= RESPONSE_HAS_NO_QUESTION;
+            }
+            if opt_record(response).is_some() {
+                flags |= RESPONSE_HAS_OPT;
+            }
+            signature.response_rcode = Some((response.header.flags & 0x0F) as u8);
+        }
+        signature.flags = flags;
+        signature.dns_flags = dns_flags_bits;
+
+        let entry = Item {
+            time: item.time,
+            client_address: self.addresses.insert(address_bytes(item.client.ip())),
+            client_port: item.client.port(),
+            transaction_id: first.map_or(0, |packet| packet.header.transaction_id),
+            signature: self.signatures.insert(signature),
+            query_name,
+            query_size: item.query_size,
+            response_size: item.response_size,
+            response_delay: item.response_delay,
+            query_extended: query.and_then(|packet| self.extended(packet)),
+            response_extended: response.and_then(|packet| self.extended(packet)),
+        };
+        self.items.push(entry);
+    }
+
+    /// Encodes the block, with times relative to its earliest item.
+    pub(crate) fn build(self) -> Cbor {
+        let earliest = self
+            .items
+            .iter()
+            .map(|item| item.time)
+            .min()
+            .unwrap_or_default();
+        let ticks = |duration: Duration| Cbor::Unsigned(duration.as_micros() as u64);
+        let block_preamble = Cbor::int_map([(
+            0,
+            Some(Cbor::Array(vec![
+                Cbor::Unsigned(earliest.as_secs()),
+                Cbor::Unsigned(earliest.subsec_micros() as u64),
+            ])),
+        )]);
+        let tables = Cbor::int_map([
+            (
+                0,
+                self.addresses
+                    .encode(|address| Cbor::Bytes(address.clone())),
+            ),
+            (
+                1,
+                self.classtypes.encode(|(rtype, rclass)| {
+                    Cbor::int_map([
+                        (0, Some(Cbor::Unsigned(rtype.0 as u64))),
+                        (1, Some(Cbor::Unsigned(rclass.0 as u64))),
+                    ])
+                }),
+            ),
+            (
+                2,
+                self.names_rdata.encode(|bytes| Cbor::Bytes(bytes.clone())),
+            ),
+            (3, self.signatures.encode(Signature::encode)),
+            (4, self.question_lists.encode(|list| indices(list))),
+            (
+                5,
+                self.questions.encode(|&(name, classtype)| {
+                    Cbor::int_map([
+                        (0, Some(Cbor::Unsigned(name))),
+                        (1, Some(Cbor::Unsigned(classtype))),
+                    ])
+                }),
+            ),
+            (6, self.rr_lists.encode(|list| indices(list))),
+            (
+                7,
+                self.rrs.encode(|rr| {
+                    Cbor::int_map([
+                        (0, Some(Cbor::Unsigned(rr.name))),
+                        (1, Some(Cbor::Unsigned(rr.classtype))),
+                        (2, Some(Cbor::Unsigned(rr.ttl as u64))),
+                        (3, Some(Cbor::Unsigned(rr.rdata))),
+                    ])
+                }),
+            ),
+        ]);
+        let items = self
+            .items
+            .into_iter()
+            .map(|item| {
+                let size = |size: Option<u32>| size.map(|size| Cbor::Unsigned(size as u64));
+                Cbor::int_map([
+                    (0, Some(ticks(item.time - earliest))),
+                    (1, Some(Cbor::Unsigned(item.client_address))),
+                    (2, Some(Cbor::Unsigned(item.client_port as u64))),
+                    (3, Some(Cbor::Unsigned(item.transaction_id as u64))),
+                    (4, Some(Cbor::Unsigned(item.signature))),
+                    (6, item.response_delay.map(ticks)),
+                    (7, item.query_name.map(Cbor::Unsigned)),
+                    (8, size(item.query_size)),
+                    (9, size(item.response_size)),
+                    (11, item.query_extended),
+                    (12, item.response_extended),
+                ])
+            })
+            .collect();
+        Cbor::int_map([
+            (0, Some(block_preamble)),
+            (2, Some(tables)),
+            (3, Some(Cbor::Array(items))),
+        ])
+    }
+}
+
+/// A decoded block, ready to rebuild its query/response items.
+pub(crate) struct Block<'a> {
+    earliest: Duration,
+    ticks_per_second: u64,
+    addresses: Vec<IpAddr>,
+    classtypes: Vec<(DnsType, DnsClass)>,
+    names_rdata: Vec<&'a [u8]>,
+    signatures: &'a [Cbor],
+    question_lists: &'a [Cbor],
+    questions: &'a [Cbor],
+    rr_lists: &'a [Cbor],
+    rrs: &'a [Cbor],
+    items: &'a [Cbor],
+}
+
+fn lookup<'a, T>(table: &'a [T], name: &'static str, index: u64) -> Result<&'a T, CdnsError> {
+    usize::try_from(index)
+        .ok()
+        .and_then(|i| table.get(i))
+        .ok_or(CdnsError::InvalidIndex { table: name, index })
+}
+
+fn table(tables: &Cbor, key: u64) -> Result<&[Cbor], CdnsError> {
+    match tables.get(key)? {
+        Some(table) => table.as_array(),
+        None => Ok(&[]),
+    }
+}
+
+impl<'a> Block<'a> {
+    pub(crate) fn decode(block: &'a Cbor, ticks_per_second: &[u64]) -> Result<Self, CdnsError> {
+        let preamble = required(block, 0, "block-preamble")?;
+        let parameters = optional_u64(preamble, 1)?.unwrap_or(0);
+        let ticks_per_second = *lookup(ticks_per_second, "block-parameters", parameters)?;
+        let earliest = match required(preamble, 0, "earliest-time")?.as_array()? {
+            [seconds, ticks] => Duration::from_secs(seconds.as_u64()?)
+                .checked_add(ticks_to_duration(ticks.as_u64()?, ticks_per_second))
+                .ok_or(CdnsError::InvalidField("earliest-time"))?,
+            _ => return Err(CdnsError::InvalidField("earliest-time")),
+        };
+
+        let tables = required(block, 2, "block-tables")?;
+        let addresses = table(tables, 0)?
+            .iter()
+            .map(|address| {
+                let bytes = address.as_bytes()?;
+                if let Ok(octets) = <[u8; 4]>::try_from(bytes) {
+                    Ok(IpAddr::V4(Ipv4Addr::from(octets)))
+                } else if let Ok(octets) = <[u8; 16]>::try_from(bytes) {
+                    Ok(IpAddr::V6(Ipv6Addr::from(octets)))
+                } else {
+                    Err(CdnsError::InvalidField("ip-address"))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        let classtypes = table(tables, 1)?
+            .iter()
+            .map(|classtype| {
+                let rtype = required(classtype, 0, "type")?.as_u64()?;
+                let rclass = required(classtype, 1, "class")?.as_u64()?;
+                Ok((
+                    DnsType(narrow(rtype, "type")?),
+                    DnsClass(narrow(rclass, "class")?),
+                ))
+            })
+            .collect::<Result<_, CdnsError>>()?;
+        let names_rdata = table(tables, 2)?
+            .iter()
+            .map(Cbor::as_bytes)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Block {
+            earliest,
+            ticks_per_second,
+            addresses,
+            classtypes,
+            names_rdata,
+            signatures: table(tables, 3)?,
+            question_lists: table(tables, 4)?,
+            questions: table(tables, 5)?,
+            rr_lists: table(tables, 6)?,
+            rrs: table(tables, 7)?,
+            items: required(block, 3, "query-responses")?.as_array()?,
+        })
+    }
+
+    pub(crate) fn items(&self) -> Result<Vec<QueryResponse>, CdnsError> {
+        self.items.iter().map(|item| self.item(item)).collect()
+    }
+
+    fn address(&self, index: u64) -> Result<IpAddr, CdnsError> {
+        lookup(&self.addresses, "ip-address", index).copied()
+    }
+
+    fn name(&self, index: u64) -> Result<String, CdnsError> {
+        let bytes = lookup(&self.names_rdata, "name-rdata", index)?;
+        Ok(parse_name(bytes, 0)?.0)
+    }
+
+    fn question(&self, name: u64, classtype: u64) -> Result<DnsQuery, CdnsError> {
+        let (qtype, qclass) = *lookup(&self.classtypes, "classtype", classtype)?;
+        Ok(DnsQuery {
+            name: self.name(name)?,
+            qtype,
+            qclass,
+        })
+    }
+
+    fn questions(&self, list: u64) -> Result<Vec<DnsQuery>, CdnsError> {
+        lookup(self.question_lists, "qlist", list)?
+            .as_array()?
+            .iter()
+            .map(|index| {
+                let question = lookup(self.questions, "qrr", index.as_u64()?)?;
+                self.question(
+                    required(question, 0, "name-index")?.as_u64()?,
+                    required(question, 1, "classtype-index")?.as_u64()?,
+                )
+            })
+            .collect()
+    }
+
+    fn records(&self, list: u64) -> Result<Vec<ResourceRecord>, CdnsError> {
+        lookup(self.rr_lists, "rrlist", list)?
+            .as_array()?
+            .iter()
+            .map(|index| {
+                let rr = lookup(self.rrs, "rr", index.as_u64()?)?;
+                let (rtype, rclass) = *lookup(
+                    &self.classtypes,
+                    "classtype",
+                    required(rr, 1, "classtype-index")?.as_u64()?,
+                )?;
+                let ttl = optional_u64(rr, 2)?.unwrap_or(0);
+                let rdata = match optional_u64(rr, 3)? {
+                    Some(index) => *lookup(&self.names_rdata, "name-rdata", index)?,
+                    None => &[],
+                };
+                Ok(ResourceRecord {
+                    name: self.name(required(rr, 0, "name-index")?.as_u64()?)?,
+                    rtype,
+                    rclass,
+                    ttl: narrow(ttl, "ttl")?,
+                    data_length: narrow(rdata.len() as u64, "rdata")?,
+                    rdata: RData::from_bytes(rdata, 0, rtype)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Rebuilds a message from its first question and the extended sections.
+    fn packet(
+        &self,
+        transaction_id: u16,
+        flags: u16,
+        first_question: Option<&DnsQuery>,
+        extended: Option<&Cbor>,
+        counts: Option<[u16; 4]>,
+    ) -> Result<DnsPacket, CdnsError> {
+        let mut queries = first_question.into_iter().cloned().collect::<Vec<_>>();
+        let mut sections: [Option<Vec<ResourceRecord>>; 3] = [None, None, None];
+        if let Some(extended) = extended {
+            if let Some(list) = optional_u64(extended, 0)? {
+                queries.extend(self.questions(list)?);
+            }
+            for (key, section) in (1..).zip(sections.iter_mut()) {
+                if let Some(list) = optional_u64(extended, key)? {
+                    *section = Some(self.records(list)?).filter(|records| !records.is_empty());
+                }
+            }
+        }
+        let count = |len: usize| narrow(len as u64, "section length");
+        let counts = match counts {
+            Some(counts) => counts,
+            None => [
+                count(queries.len())?,
+                count(sections[0].as_ref().map_or(0, Vec::len))?,
+                count(sections[1].as_ref().map_or(0, Vec::len))?,
+                count(sections[2].as_ref().map_or(0, Vec::len))?,
+            ],
+        };
+        let [answers, authorities, additionals] = sections;
+        Ok(DnsPacket {
+            header: DnsHeader {
+                transaction_id,
+                flags,
+                counts,
+            },
+            queries: DnsQueries { queries },
+            answers,
+            authorities,
+            additionals,
+            warnings: Vec::new(),
+        })
+    }
+
+    fn item(&self, item: &Cbor) -> Result<QueryResponse, CdnsError> {
+        let signature = lookup(
+            self.signatures,
+            "query-response-signature",
+            required(item, 4, "qr-signature-index")?.as_u64()?,
+        )?;
+        let sig_u64 = |key, default| optional_u64(signature, key).map(|v| v.unwrap_or(default));
+        let flags = sig_u64(4, 0)?;
+        let transport_flags = sig_u64(2, 0)?;
+        let opcode = narrow::<u16>(sig_u64(5, 0)?, "query-opcode")? & 0x0F;
+        let dns_flags = sig_u64(6, 0)?;
+        let transaction_id = narrow(optional_u64(item, 3)?.unwrap_or(0), "transaction-id")?;
+
+        let first_question = match (optional_u64(item, 7)?, optional_u64(signature, 8)?) {
+            (Some(name), Some(classtype)) => Some(self.question(name, classtype)?),
+            _ => None,
+        };
+
+        let query = if flags & QUERY_PRESENT != 0 {
+            let rcode = narrow::<u16>(sig_u64(7, 0)?, "query-rcode")? & 0x0F;
+            let header_flags = opcode << 11 | ((dns_flags & 0x7F) as u16) << 4 | rcode;
+            let counts = match (9..=12)
+                .map(|key| optional_u64(signature, key))
+                .collect::<Result<Vec<_>, _>>()?[..]
+            {
+                [Some(qd), Some(an), Some(ns), Some(ar)] => Some([
+                    narrow(qd, "query-qdcount")?,
+                    narrow(an, "query-ancount")?,
+                    narrow(ns, "query-nscount")?,
+                    narrow(ar, "query-arcount")?,
+                ]),
+                _ => None,
+            };
+            let question = first_question
+                .as_ref()
+                .filter(|_| flags & QUERY_HAS_NO_QUESTION == 0);
+            Some(self.packet(
+                transaction_id,
+                header_flags,
+                question,
+                item.get(11)?,
+                counts,
+            )?)
+        } else {
+            None
+        };
+
+        let response = if flags & RESPONSE_PRESENT != 0 {
+            let rcode = narrow::<u16>(sig_u64(16, 0)?, "response-rcode")? & 0x0F;
+            let header_flags =
+                0x8000 | opcode << 11 | (((dns_flags >> 8) & 0x7F) as u16) << 4 | rcode;
+            let question = first_question
+                .as_ref()
+                .filter(|_| flags & RESPONSE_HAS_NO_QUESTION == 0);
+            Some(self.packet(transaction_id, header_flags, question, item.get(12)?, None)?)
+        } else {
+            None
+        };
+
+        let client = SocketAddr::new(
+            self.address(required(item, 1, "client-address-index")?.as_u64()?)?,
+            narrow(optional_u64(item, 2)?.unwrap_or(0), "client-port")?,
+        );
+        let server = SocketAddr::new(
+            self.address(required(signature, 0, "server-address-index")?.as_u64()?)?,
+            narrow(sig_u64(1, 0)?, "server-port")?,
+        );
+        let time_offset = optional_u64(item, 0)?.unwrap_or(0);
+        let time = self
+            .earliest
+            .checked_add(ticks_to_duration(time_offset, self.ticks_per_second))
+            .ok_or(CdnsError::InvalidField("time-offset"))?;
+        let response_delay = match item.get(6)? {
+            Some(delay) => Some(ticks_to_duration(
+                u64::try_from(delay.as_i64()?)
+                    .map_err(|_| CdnsError::InvalidField("response-delay"))?,
+                self.ticks_per_second,
+            )),
+            None => None,
+        };
+        let size = |key| -> Result<Option<u32>, CdnsError> {
+            optional_u64(item, key)?
+                .map(|size| narrow(size, "message size"))
+                .transpose()
+        };
+        Ok(QueryResponse {
+            time,
+            client,
+            server,
+            transport: Transport(((transport_flags >> 1) & 0x0F) as u8),
+            query,
+            query_size: size(8)?,
+            response,
+            response_size: size(9)?,
+            response_delay,
+        })
+    }
+}
+
+fn ticks_to_duration(ticks: u64, ticks_per_second: u64) -> Duration {
+    let nanos = (ticks % ticks_per_second) as u128 * 1_000_000_000 / ticks_per_second as u128;
+    Duration::new(ticks / ticks_per_second, nanos as u32)
+}
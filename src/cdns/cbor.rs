@@ -0,0 +1,305 @@
+//! A minimal CBOR (RFC 8949) value model, covering the subset used by C-DNS.
+
+use super::errors::CdnsError;
+
+/// Maximum nesting depth accepted by the decoder, C-DNS files only use a handful of levels.
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Cbor {
+    Unsigned(u64),
+    Negative(u64), // Encodes -1 - n
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Cbor>),
+    Map(Vec<(Cbor, Cbor)>),
+    Simple(u8), // false, true, null, undefined...
+}
+
+impl Cbor {
+    /// Builds a map with integer keys, skipping the absent values.
+    pub(crate) fn int_map<const N: usize>(entries: [(u64, Option<Cbor>); N]) -> Cbor {
+        Cbor::Map(
+            entries
+                .into_iter()
+                .filter_map(|(key, value)| value.map(|value| (Cbor::Unsigned(key), value)))
+                .collect(),
+        )
+    }
+
+    pub(crate) fn as_u64(&self) -> Result<u64, CdnsError> {
+        match self {
+            Cbor::Unsigned(value) => Ok(*value),
+            _ => Err(CdnsError::InvalidCbor("expected an unsigned integer")),
+        }
+    }
+
+    /// Reads an unsigned or negative integer.
+    pub(crate) fn as_i64(&self) -> Result<i64, CdnsError> {
+        match self {
+            Cbor::Unsigned(value) => i64::try_from(*value).ok(),
+            Cbor::Negative(value) => i64::try_from(*value).ok().map(|value| -1 - value),
+            _ => None,
+        }
+        .ok_or(CdnsError::InvalidCbor("expected an integer"))
+    }
+
+    pub(crate) fn as_bytes(&self) -> Result<&[u8], CdnsError> {
+        match self {
+            Cbor::Bytes(bytes) => Ok(bytes),
+            _ => Err(CdnsError::InvalidCbor("expected a byte string")),
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Result<&[Cbor], CdnsError> {
+        match self {
+            Cbor::Array(items) => Ok(items),
+            _ => Err(CdnsError::InvalidCbor("expected an array")),
+        }
+    }
+
+    /// Looks up an integer key in a map; `Ok(None)` if the key is absent.
+    pub(crate) fn get(&self, key: u64) -> Result<Option<&Cbor>, CdnsError> {
+        match self {
+            Cbor::Map(entries) => Ok(entries
+                .iter()
+                .find(|(k, _)| *k == Cbor::Unsigned(key))
+                .map(|(_, value)| value)),
+            _ => Err(CdnsError::InvalidCbor("expected a map")),
+        }
+    }
+
+    pub(crate) fn encode(&self, buf: &mut Vec<u8>) {
+        match self {
+            Cbor::Unsigned(value) => write_head(buf, 0, *value),
+            Cbor::Negative(value) => write_head(buf, 1, *value),
+            Cbor::Bytes(bytes) => {
+                write_head(buf, 2, bytes.len() as u64);
+                buf.extend_from_slice(bytes);
+            }
+            Cbor::Text(text) => {
+                write_head(buf, 3, text.len() as u64);
+                buf.extend_from_slice(text.as_bytes());
+            }
+            Cbor::Array(items) => {
+                write_head(buf, 4, items.len() as u64);
+                for item in items {
+                    item.encode(buf);
+                }
+            }
+            Cbor::Map(entries) => {
+                write_head(buf, 5, entries.len() as u64);
+                for (key, value) in entries {
+                    key.encode(buf);
+                    value.encode(buf);
+                }
+            }
+            Cbor::Simple(value) => write_head(buf, 7, *value as u64),
+        }
+    }
+
+    /// Decodes one item starting at `offset`, which is advanced past it.
+    pub(crate) fn decode(bytes: &[u8], offset: &mut usize) -> Result<Cbor, CdnsError> {
+        decode_item(bytes, offset, 0)
+    }
+}
+
+fn write_head(buf: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    match value {
+        0..=23 => buf.push(major | value as u8),
+        24..=0xFF => buf.extend_from_slice(&[major | 24, value as u8]),
+        0x100..=0xFFFF => {
+            buf.push(major | 25);
+            buf.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        0x1_0000..=0xFFFF_FFFF => {
+            buf.push(major | 26);
+            buf.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        _ => {
+            buf.push(major | 27);
+            buf.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+}
+
+/// The start of an indefinite-length array, closed by `BREAK`.
+pub(crate) const INDEFINITE_ARRAY: u8 = 0x9F;
+pub(crate) const BREAK: u8 = 0xFF;
+
+fn read_bytes<'a>(bytes: &'a [u8], offset: &mut usize, len: usize) -> Result<&'a [u8], CdnsError> {
+    if len > bytes.len() - *offset {
+        return Err(CdnsError::InvalidCbor("truncated item"));
+    }
+    *offset += len;
+    Ok(&bytes[*offset - len..*offset])
+}
+
+/// Reads an item head: its major type and argument, `None` meaning indefinite length.
+fn read_head(bytes: &[u8], offset: &mut usize) -> Result<(u8, Option<u64>), CdnsError> {
+    let initial = read_bytes(bytes, offset, 1)?[0];
+    let major = initial >> 5;
+    let argument = match initial & 0x1F {
+        value @ 0..=23 => Some(value as u64),
+        24 => Some(read_bytes(bytes, offset, 1)?[0] as u64),
+        25 => Some(u16::from_be_bytes(read_bytes(bytes, offset, 2)?.try_into().unwrap()) as u64),
+        26 => Some(u32::from_be_bytes(read_bytes(bytes, offset, 4)?.try_into().unwrap()) as u64),
+        27 => Some(u64::from_be_bytes(
+            read_bytes(bytes, offset, 8)?.try_into().unwrap(),
+        )),
+        31 if (2..=5).contains(&major) => None,
+        _ => return Err(CdnsError::InvalidCbor("invalid additional information")),
+    };
+    Ok((major, argument))
+}
+
+fn is_break(bytes: &[u8], offset: &mut usize) -> bool {
+    if bytes.get(*offset) == Some(&BREAK) {
+        *offset += 1;
+        return true;
+    }
+    false
+}
+
+fn decode_item(bytes: &[u8], offset: &mut usize, depth: usize) -> Result<Cbor, CdnsError> {
+    if depth > MAX_DEPTH {
+        return Err(CdnsError::InvalidCbor("nesting too deep"));
+    }
+    let (major, argument) = read_head(bytes, offset)?;
+    let item = match (major, argument) {
+        (0, Some(value)) => Cbor::Unsigned(value),
+        (1, Some(value)) => Cbor::Negative(value),
+        (2 | 3, Some(len)) => {
+            let len = usize::try_from(len).map_err(|_| CdnsError::InvalidCbor("truncated item"))?;
+            let data = read_bytes(bytes, offset, len)?.to_vec();
+            if major == 2 {
+                Cbor::Bytes(data)
+            } else {
+                Cbor::Text(
+                    String::from_utf8(data).map_err(|_| CdnsError::InvalidCbor("invalid text"))?,
+                )
+            }
+        }
+        (2 | 3, None) => {
+            // Indefinite-length strings are a concatenation of definite-length chunks.
+            let mut data = Vec::new();
+            while !is_break(bytes, offset) {
+                match decode_item(bytes, offset, depth + 1)? {
+                    Cbor::Bytes(chunk) if major == 2 => data.extend(chunk),
+                    Cbor::Text(chunk) if major == 3 => data.extend(chunk.into_bytes()),
+                    _ => return Err(CdnsError::InvalidCbor("invalid string chunk")),
+                }
+            }
+            if major == 2 {
+                Cbor::Bytes(data)
+            } else {
+                Cbor::Text(
+                    String::from_utf8(data).map_err(|_| CdnsError::InvalidCbor("invalid text"))?,
+                )
+            }
+        }
+        (4, len) => {
+            let mut items = Vec::new();
+            match len {
+                Some(len) => {
+                    for _ in 0..len {
+                        items.push(decode_item(bytes, offset, depth + 1)?);
+                    }
+                }
+                None => {
+                    while !is_break(bytes, offset) {
+                        items.push(decode_item(bytes, offset, depth + 1)?);
+                    }
+                }
+            }
+            Cbor::Array(items)
+        }
+        (5, len) => {
+            let mut entries = Vec::new();
+            let mut remaining = len;
+            loop {
+                match remaining {
+                    Some(0) => break,
+                    Some(ref mut n) => *n -= 1,
+                    None if is_break(bytes, offset) => break,
+                    None => {}
+                }
+                let key = decode_item(bytes, offset, depth + 1)?;
+                let value = decode_item(bytes, offset, depth + 1)?;
+                entries.push((key, value));
+            }
+            Cbor::Map(entries)
+        }
+        // Tags only annotate the following item, which is kept as is.
+        (6, Some(_)) => decode_item(bytes, offset, depth + 1)?,
+        (7, Some(value)) => Cbor::Simple(
+            u8::try_from(value).map_err(|_| CdnsError::InvalidCbor("floats are not supported"))?,
+        ),
+        _ => return Err(CdnsError::InvalidCbor("invalid item")),
+    };
+    Ok(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cbor_round_trip() {
+        let value = Cbor::Array(vec![
+            Cbor::Text("C-DNS".into()),
+            Cbor::int_map([
+                (0, Some(Cbor::Unsigned(1))),
+                (1, None),
+                (3, Some(Cbor::Negative(9))),
+            ]),
+            Cbor::Bytes(vec![0; 300]),
+            Cbor::Unsigned(70_000),
+            Cbor::Unsigned(u64::MAX),
+        ]);
+        let mut buf = Vec::new();
+        value.encode(&mut buf);
+        assert_eq!(&buf[..7], b"\x85\x65C-DNS");
+
+        let mut offset = 0;
+        let decoded = Cbor::decode(&buf, &mut offset).unwrap();
+        assert_eq!(decoded, value);
+        assert_eq!(offset, buf.len());
+        assert_eq!(
+            decoded.as_array().unwrap()[1]
+                .get(3)
+                .unwrap()
+                .unwrap()
+                .as_i64()
+                .unwrap(),
+            -10
+        );
+    }
+
+    #[test]
+    fn test_cbor_indefinite_lengths() {
+        // [_ 1, {_ 0: h'01'}, (_ "a", "b")]
+        let bytes = [
+            0x9F, 0x01, 0xBF, 0x00, 0x41, 0x01, 0xFF, 0x7F, 0x61, b'a', 0x61, b'b', 0xFF, 0xFF,
+        ];
+        let mut offset = 0;
+        let decoded = Cbor::decode(&bytes, &mut offset).unwrap();
+        assert_eq!(
+            decoded,
+            Cbor::Array(vec![
+                Cbor::Unsigned(1),
+                Cbor::Map(vec![(Cbor::Unsigned(0), Cbor::Bytes(vec![1]))]),
+                Cbor::Text("ab".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_cbor_truncated() {
+        let mut offset = 0;
+        assert!(Cbor::decode(&[0x82, 0x01], &mut offset).is_err());
+        let mut offset = 0;
+        assert!(Cbor::decode(&[0x5A, 0xFF, 0xFF, 0xFF, 0xFF], &mut offset).is_err());
+    }
+}
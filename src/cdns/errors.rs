@@ -0,0 +1,23 @@
+use thiserror::Error;
+
+use crate::dns_records::errors::DnsRecordParseError;
+
+#[derive(Debug, Error)]
+pub enum CdnsError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid CBOR encoding: {0}")]
+    InvalidCbor(&'static str),
+    #[error("Not a C-DNS file")]
+    NotCdns,
+    #[error("Unsupported C-DNS format version {major}.{minor}")]
+    UnsupportedVersion { major: u64, minor: u64 },
+    #[error("Missing C-DNS field: {0}")]
+    MissingField(&'static str),
+    #[error("Invalid C-DNS field: {0}")]
+    InvalidField(&'static str),
+    #[error("Invalid index {index} into C-DNS table {table}")]
+    InvalidIndex { table: &'static str, index: u64 },
+    #[error("Invalid record stored in C-DNS block: {0}")]
+    RecordError(#[from] DnsRecordParseError),
+}
@@ -0,0 +1,405 @@
+//! Compacted DNS (C-DNS, RFC 8618) storage of DNS traffic.
+//!
+//! C-DNS stores query/response pairs in CBOR blocks in which names, addresses, classtypes
+//! and records are deduplicated into tables, which makes it far smaller than a pcap of the
+//! same traffic. `CdnsWriter` builds such a file from `QueryResponse` items and `CdnsReader`
+//! reads them back, reconstructing the query and the response as `DnsPacket`s.
+//!
+//! Times are stored with microsecond resolution. Names inside record data are stored
+//! uncompressed, so reconstructed records report the uncompressed `data_length`.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use crate::DnsPacket;
+use block::{Block, BlockBuilder};
+use cbor::{Cbor, BREAK, INDEFINITE_ARRAY};
+pub use errors::CdnsError;
+
+mod block;
+mod cbor;
+mod errors;
+
+/// The file type identifier that starts every C-DNS file.
+const FILE_TYPE_ID: &str = "C-DNS";
+const MAJOR_FORMAT_VERSION: u64 = 1;
+const MINOR_FORMAT_VERSION: u64 = 0;
+
+/// Number of query/response items per block when not configured otherwise.
+pub const DEFAULT_MAX_BLOCK_ITEMS: usize = 10_000;
+
+/// The transport of a query/response pair, as encoded in C-DNS transport flags.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Transport(pub u8);
+
+#[allow(non_snake_case)]
+#[allow(non_upper_case_globals)]
+pub mod Transports {
+    use super::Transport;
+
+    pub const UDP: Transport = Transport(0);
+    pub const TCP: Transport = Transport(1);
+    pub const TLS: Transport = Transport(2);
+    pub const DTLS: Transport = Transport(3);
+    pub const HTTPS: Transport = Transport(4);
+    pub const NON_STANDARD: Transport = Transport(15);
+}
+
+impl fmt::Display for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Transports::UDP => "UDP",                   // 0
+                Transports::TCP => "TCP",                   // 1
+                Transports::TLS => "TLS",                   // 2
+                Transports::DTLS => "DTLS",                 // 3
+                Transports::HTTPS => "HTTPS",               // 4
+                Transports::NON_STANDARD => "non-standard", // 15
+                _ => "unknown",
+            }
+        )
+    }
+}
+
+/// A DNS transaction: a query and/or its response, with the transport metadata.
+///
+/// `time` is the time the query was seen (or the response, when there is no query) as a
+/// duration since the Unix epoch, and `response_delay` the time between both.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryResponse {
+    pub time: Duration,
+    pub client: SocketAddr,
+    pub server: SocketAddr,
+    pub transport: Transport,
+    pub query: Option<DnsPacket>,
+    pub query_size: Option<u32>,
+    pub response: Option<DnsPacket>,
+    pub response_size: Option<u32>,
+    pub response_delay: Option<Duration>,
+}
+
+/// Writes query/response items as a C-DNS file, one block every `max_block_items` items.
+pub struct CdnsWriter<W: Write> {
+    writer: W,
+    max_block_items: usize,
+    block: BlockBuilder,
+}
+
+impl<W: Write> CdnsWriter<W> {
+    pub fn new(writer: W) -> Result<Self, CdnsError> {
+        Self::with_max_block_items(writer, DEFAULT_MAX_BLOCK_ITEMS)
+    }
+
+    pub fn with_max_block_items(mut writer: W, max_block_items: usize) -> Result<Self, CdnsError> {
+        let max_block_items = max_block_items.max(1);
+        let mut buf = Vec::new();
+        // The file is [file-type-id, file-preamble, file-blocks], with the blocks streamed
+        // in an indefinite-length array.
+        buf.push(0x83);
+        Cbor::Text(FILE_TYPE_ID.to_string()).encode(&mut buf);
+        block::preamble(max_block_items as u64).encode(&mut buf);
+        buf.push(INDEFINITE_ARRAY);
+        writer.write_all(&buf)?;
+        Ok(CdnsWriter {
+            writer,
+            max_block_items,
+            block: BlockBuilder::default(),
+        })
+    }
+
+    pub fn write(&mut self, item: &QueryResponse) -> Result<(), CdnsError> {
+        self.block.push(item);
+        if self.block.len() >= self.max_block_items {
+            self.write_block()?;
+        }
+        Ok(())
+    }
+
+    fn write_block(&mut self) -> Result<(), CdnsError> {
+        let block = std::mem::take(&mut self.block);
+        let mut buf = Vec::new();
+        block.build().encode(&mut buf);
+        self.writer.write_all(&buf)?;
+        Ok(())
+    }
+
+    /// Writes the pending block, ends the file and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, CdnsError> {
+        if self.block.len() > 0 {
+            self.write_block()?;
+        }
+        self.writer.write_all(&[BREAK])?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Iterates over the query/response items of a C-DNS file.
+pub struct CdnsReader {
+    ticks_per_second: Vec<u64>,
+    blocks: std::vec::IntoIter<Cbor>,
+    items: VecDeque<QueryResponse>,
+}
+
+impl CdnsReader {
+    /// Reads the whole file and checks its preamble; blocks are decoded while iterating.
+    pub fn new<R: Read>(mut reader: R) -> Result<Self, CdnsError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let mut offset = 0;
+        let file = Cbor::decode(&bytes, &mut offset)?;
+        let [Cbor::Text(file_type), preamble, Cbor::Array(blocks)] = file.as_array()? else {
+            return Err(CdnsError::NotCdns);
+        };
+        if file_type != FILE_TYPE_ID {
+            return Err(CdnsError::NotCdns);
+        }
+        Ok(CdnsReader {
+            ticks_per_second: block::parse_preamble(preamble)?,
+            blocks: blocks.clone().into_iter(),
+            items: VecDeque::new(),
+        })
+    }
+}
+
+impl Iterator for CdnsReader {
+    type Item = Result<QueryResponse, CdnsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.items.is_empty() {
+            let block = self.blocks.next()?;
+            match Block::decode(&block, &self.ticks_per_second).and_then(|block| block.items()) {
+                Ok(items) => self.items.extend(items),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        self.items.pop_front().map(Ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::pcap::read_udp_datagrams;
+
+    /// Pairs the DNS datagrams of a capture into query/response items.
+    fn query_responses(path: &str) -> Vec<QueryResponse> {
+        let mut items: Vec<QueryResponse> = Vec::new();
+        for datagram in read_udp_datagrams(path) {
+            let is_query = datagram.destination.port() == 53;
+            if !is_query && datagram.source.port() != 53 {
+                continue;
+            }
+            let packet = DnsPacket::try_from(datagram.payload.as_slice()).unwrap();
+            let size = Some(datagram.payload.len() as u32);
+            if is_query {
+                items.push(QueryResponse {
+                    time: datagram.time,
+                    client: datagram.source,
+                    server: datagram.destination,
+                    transport: Transports::UDP,
+                    query: Some(packet),
+                    query_size: size,
+                    response: None,
+                    response_size: None,
+                    response_delay: None,
+                });
+                continue;
+            }
+            let pending = items.iter_mut().find(|item| {
+                item.response.is_none()
+                    && item.client == datagram.destination
+                    && item.query.as_ref().unwrap().header.transaction_id
+                        == packet.header.transaction_id
+            });
+            match pending {
+                Some(item) => {
+                    item.response_delay = Some(datagram.time - item.time);
+                    item.response = Some(packet);
+                    item.response_size = size;
+                }
+                None => items.push(QueryResponse {
+                    time: datagram.time,
+                    client: datagram.destination,
+                    server: datagram.source,
+                    transport: Transports::UDP,
+                    query: None,
+                    query_size: None,
+                    response: Some(packet),
+                    response_size: size,
+                    response_delay: None,
+                }),
+            }
+        }
+        items
+    }
+
+    /// Sets the record lengths to those of the uncompressed data, as stored in C-DNS.
+    fn uncompressed(mut packet: DnsPacket) -> DnsPacket {
+        for section in [
+            &mut packet.answers,
+            &mut packet.authorities,
+            &mut packet.additionals,
+        ] {
+            for record in section.iter_mut().flatten() {
+                let mut wire = Vec::new();
+                record.rdata.to_wire(&mut wire);
+                record.data_length = wire.len() as u16;
+            }
+        }
+        packet
+    }
+
+    fn round_trip(items: &[QueryResponse], max_block_items: usize) -> Vec<u8> {
+        let mut writer = CdnsWriter::with_max_block_items(Vec::new(), max_block_items).unwrap();
+        for item in items {
+            writer.write(item).unwrap();
+        }
+        let bytes = writer.finish().unwrap();
+
+        let decoded = CdnsReader::new(bytes.as_slice())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let expected = items
+            .iter()
+            .cloned()
+            .map(|mut item| {
+                item.query = item.query.map(uncompressed);
+                item.response = item.response.map(uncompressed);
+                item
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(decoded, expected);
+        bytes
+    }
+
+    #[test]
+    fn test_cdns_round_trip_dns_cap() {
+        let items = query_responses("pcap_exemples/dns.cap");
+        assert_eq!(items.len(), 19);
+        assert!(items.iter().all(|item| item.response.is_some()));
+        let bytes = round_trip(&items, DEFAULT_MAX_BLOCK_ITEMS);
+        assert_eq!(&bytes[..7], b"\x83\x65C-DNS");
+
+        // Several blocks, with the last one partially filled.
+        round_trip(&items, 7);
+    }
+
+    #[test]
+    fn test_cdns_round_trip_other_captures() {
+        for path in [
+            "pcap_exemples/NTP_sync.pcap",
+            "pcap_exemples/dns-remoteshell.pcap",
+        ] {
+            let items = query_responses(path);
+            assert!(!items.is_empty());
+            round_trip(&items, DEFAULT_MAX_BLOCK_ITEMS);
+        }
+    }
+
+    #[test]
+    fn test_cdns_query_without_response() {
+        let query =
+            hex::decode("abcd01000001000000000000076578616d706c6503636f6d0000010001").unwrap();
+        let item = QueryResponse {
+            time: Duration::new(1_700_000_000, 123_456_000),
+            client: "[2001:db8::10]:53000".parse().unwrap(),
+            server: "[2001:db8::53]:853".parse().unwrap(),
+            transport: Transports::TLS,
+            query: Some(DnsPacket::try_from(query.as_slice()).unwrap()),
+            query_size: None,
+            response: None,
+            response_size: None,
+            response_delay: None,
+        };
+        round_trip(&[item], DEFAULT_MAX_BLOCK_ITEMS);
+    }
+
+    /// Returns the value of `key` in a CBOR map.
+    fn entry(map: &mut Cbor, key: u64) -> &mut Cbor {
+        let Cbor::Map(entries) = map else {
+            panic!("not a map")
+        };
+        let (_, value) = entries
+            .iter_mut()
+            .find(|(k, _)| *k == Cbor::Unsigned(key))
+            .unwrap();
+        value
+    }
+
+    #[test]
+    fn test_cdns_time_overflow() {
+        let query =
+            hex::decode("abcd01000001000000000000076578616d706c6503636f6d0000010001").unwrap();
+        let item = QueryResponse {
+            time: Duration::ZERO,
+            client: "192.0.2.1:53000".parse().unwrap(),
+            server: "192.0.2.53:53".parse().unwrap(),
+            transport: Transports::UDP,
+            query: Some(DnsPacket::try_from(query.as_slice()).unwrap()),
+            query_size: None,
+            response: None,
+            response_size: None,
+            response_delay: None,
+        };
+        let mut writer = CdnsWriter::new(Vec::new()).unwrap();
+        writer.write(&item).unwrap();
+        let bytes = writer.finish().unwrap();
+        let file = Cbor::decode(&bytes, &mut 0).unwrap();
+
+        // Rewrites the earliest time, in microsecond ticks, and the time offset of the item.
+        let read = |earliest: u64, offset: u64| {
+            let mut file = file.clone();
+            let Cbor::Array(parts) = &mut file else {
+                panic!("not an array")
+            };
+            let Cbor::Array(blocks) = &mut parts[2] else {
+                panic!("not an array")
+            };
+            let block = &mut blocks[0];
+            *entry(entry(block, 0), 0) =
+                Cbor::Array(vec![Cbor::Unsigned(u64::MAX), Cbor::Unsigned(earliest)]);
+            let Cbor::Array(items) = entry(block, 3) else {
+                panic!("not an array")
+            };
+            *entry(&mut items[0], 0) = Cbor::Unsigned(offset);
+            let mut buf = Vec::new();
+            file.encode(&mut buf);
+            CdnsReader::new(buf.as_slice()).unwrap().next().unwrap()
+        };
+        assert!(read(999_999, 0).is_ok());
+        assert!(matches!(
+            read(999_999, 2_000_000),
+            Err(CdnsError::InvalidField("time-offset"))
+        ));
+        assert!(matches!(
+            read(2_000_000, 0),
+            Err(CdnsError::InvalidField("earliest-time"))
+        ));
+    }
+
+    #[test]
+    fn test_cdns_not_cdns() {
+        let mut buf = Vec::new();
+        Cbor::Array(vec![
+            Cbor::Text("pcap".into()),
+            Cbor::Map(vec![]),
+            Cbor::Array(vec![]),
+        ])
+        .encode(&mut buf);
+        assert!(matches!(
+            CdnsReader::new(buf.as_slice()),
+            Err(CdnsError::NotCdns)
+        ));
+        assert!(matches!(
+            CdnsReader::new(&[0x83][..]),
+            Err(CdnsError::InvalidCbor(_))
+        ));
+    }
+}
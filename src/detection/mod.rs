@@ -0,0 +1,341 @@
+//! Scoring how likely a payload is to be a DNS message.
+//!
+//! `DnsPacket::try_from` stops at the first rule a message breaks, which is too strict to
+//! classify traffic seen on arbitrary ports: real resolvers emit slightly odd messages,
+//! and a non-DNS payload can survive the header checks by chance. `detect` instead runs a
+//! set of independent heuristics over the message and sums their evidence into a score.
+
+use core::fmt;
+
+use crate::dns_header::dns_flags::verify_dns_flags;
+use crate::dns_records::errors::DnsRecordParseError;
+use crate::dns_records::{parse_name, read_u16, write_name};
+use crate::prelude::*;
+use crate::utils::{dns_class::DnsClass, dns_types::DnsType, dns_types::DnsTypes};
+use crate::validation::ParseOptions;
+use crate::DnsPacket;
+
+/// Score from which `Detection::is_dns` considers a payload to be DNS.
+pub const DNS_THRESHOLD: u8 = 60;
+
+/// More questions than this is implausible for a DNS message.
+const MAX_PLAUSIBLE_QUESTIONS: u16 = 8;
+
+/// Smallest encoded question (root name, type, class) and record (root name, type,
+/// class, TTL, empty data).
+const MIN_QUESTION_SIZE: usize = 5;
+const MIN_RECORD_SIZE: usize = 11;
+
+/// A check run by `detect`; variants come in pairs of evidence for and against DNS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Heuristic {
+    /// Fewer than 12 bytes, not even a header.
+    TooShort,
+    /// The flags are consistent (known opcode and rcode, rules of RFC 1035 and 6895).
+    ValidFlags,
+    InconsistentFlags,
+    /// The question count is small and all the declared sections fit in the payload.
+    PlausibleCounts,
+    ImplausibleCounts,
+    /// Every question and owner name is a valid, printable domain name.
+    WellFormedNames,
+    MalformedNames,
+    /// The declared sections end exactly at the end of the payload.
+    FullyConsumed,
+    /// Only zero bytes follow the message, as left by padding link layers or tools.
+    ZeroPadding,
+    TrailingData,
+    /// The payload ends before the declared sections.
+    Truncated,
+    /// Every type and class has a mnemonic.
+    KnownTypes,
+    UnknownTypes,
+}
+
+impl Heuristic {
+    /// The evidence brought by the heuristic: positive for DNS, negative against.
+    pub fn weight(&self) -> i32 {
+        match self {
+            Heuristic::TooShort => -100,
+            Heuristic::ValidFlags => 20,
+            Heuristic::InconsistentFlags => -15,
+            Heuristic::PlausibleCounts => 20,
+            Heuristic::ImplausibleCounts => -30,
+            Heuristic::WellFormedNames => 20,
+            Heuristic::MalformedNames => -30,
+            Heuristic::FullyConsumed => 25,
+            Heuristic::ZeroPadding => 15,
+            Heuristic::TrailingData => -40,
+            Heuristic::Truncated => -25,
+            Heuristic::KnownTypes => 15,
+            Heuristic::UnknownTypes => -10,
+        }
+    }
+}
+
+impl fmt::Display for Heuristic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let weight = self.weight();
+        write!(
+            f,
+            "{:?} ({}{})",
+            self,
+            if weight > 0 { "+" } else { "" },
+            weight
+        )
+    }
+}
+
+/// The result of `detect`.
+#[derive(Debug)]
+pub struct Detection {
+    /// From 0 (certainly not DNS) to 100 (every heuristic agrees it is DNS).
+    pub score: u8,
+    /// The heuristics that fired, in the order they ran.
+    pub heuristics: Vec<Heuristic>,
+    /// The parsed packet, when the payload also passes the strict parser; with
+    /// `Heuristic::ZeroPadding`, the message before the padding.
+    pub packet: Option<DnsPacket>,
+}
+
+impl Detection {
+    fn new(heuristics: Vec<Heuristic>, packet: Option<DnsPacket>) -> Self {
+        let total: i32 = heuristics.iter().map(Heuristic::weight).sum();
+        Detection {
+            score: total.clamp(0, 100) as u8,
+            heuristics,
+            packet,
+        }
+    }
+
+    /// Whether the score reaches `DNS_THRESHOLD`.
+    pub fn is_dns(&self) -> bool {
+        self.score >= DNS_THRESHOLD
+    }
+
+    pub fn has(&self, heuristic: Heuristic) -> bool {
+        self.heuristics.contains(&heuristic)
+    }
+}
+
+/// Scores how likely `bytes` is to be a DNS message, without failing on the first anomaly.
+pub fn detect(bytes: &[u8]) -> Detection {
+    if bytes.len() < 12 {
+        return Detection::new(vec![Heuristic::TooShort], None);
+    }
+    let mut heuristics = Vec::new();
+
+    let flags = u16::from_be_bytes([bytes[2], bytes[3]]);
+    heuristics.push(match verify_dns_flags(flags) {
+        Ok(_) => Heuristic::ValidFlags,
+        Err(_) => Heuristic::InconsistentFlags,
+    });
+
+    let counts = [4, 6, 8, 10].map(|i| u16::from_be_bytes([bytes[i], bytes[i + 1]]));
+    heuristics.push(if plausible_counts(counts, bytes.len()) {
+        Heuristic::PlausibleCounts
+    } else {
+        Heuristic::ImplausibleCounts
+    });
+
+    let walk = walk_sections(bytes, counts);
+    heuristics.push(if walk.malformed_names {
+        Heuristic::MalformedNames
+    } else {
+        Heuristic::WellFormedNames
+    });
+    match walk.end {
+        Some(end) if end == bytes.len() => heuristics.push(Heuristic::FullyConsumed),
+        Some(end) if bytes[end..].iter().all(|&byte| byte == 0) => {
+            heuristics.push(Heuristic::ZeroPadding)
+        }
+        Some(_) => heuristics.push(Heuristic::TrailingData),
+        None if walk.malformed_names => {}
+        None => heuristics.push(Heuristic::Truncated),
+    }
+    heuristics.push(if walk.unknown_types {
+        Heuristic::UnknownTypes
+    } else {
+        Heuristic::KnownTypes
+    });
+
+    let packet = if heuristics.contains(&Heuristic::ZeroPadding) {
+        DnsPacket::parse_prefix(bytes, &ParseOptions::default())
+            .ok()
+            .map(|(packet, _)| packet)
+    } else {
+        DnsPacket::try_from(bytes).ok()
+    };
+    Detection::new(heuristics, packet)
+}
+
+fn plausible_counts(counts: [u16; 4], len: usize) -> bool {
+    let records = counts[1..]
+        .iter()
+        .map(|&count| count as usize)
+        .sum::<usize>();
+    let minimum_size = 12 + counts[0] as usize * MIN_QUESTION_SIZE + records * MIN_RECORD_SIZE;
+    counts[0] <= MAX_PLAUSIBLE_QUESTIONS && minimum_size <= len
+}
+
+struct Walk {
+    malformed_names: bool,
+    unknown_types: bool,
+    /// Where the last section ends, `None` if the walk stopped early.
+    end: Option<usize>,
+}
+
+/// Walks over the questions and records without decoding the record data.
+fn walk_sections(bytes: &[u8], counts: [u16; 4]) -> Walk {
+    let mut walk = Walk {
+        malformed_names: false,
+        unknown_types: false,
+        end: None,
+    };
+    let mut offset = 12;
+    let records = counts[1..]
+        .iter()
+        .map(|&count| count as usize)
+        .sum::<usize>();
+    for index in 0..counts[0] as usize + records {
+        match walk_entry(bytes, &mut offset, index >= counts[0] as usize) {
+            Ok(known) => walk.unknown_types |= !known,
+            Err(WalkError::Truncated) => return walk,
+            Err(WalkError::MalformedName) => {
+                walk.malformed_names = true;
+                return walk;
+            }
+        }
+    }
+    walk.end = Some(offset);
+    walk
+}
+
+enum WalkError {
+    Truncated,
+    MalformedName,
+}
+
+impl From<DnsRecordParseError> for WalkError {
+    fn from(error: DnsRecordParseError) -> Self {
+        match error {
+            DnsRecordParseError::InsufficientData { .. } => WalkError::Truncated,
+            _ => WalkError::MalformedName,
+        }
+    }
+}
+
+/// Skips a question or a record, returning whether its type and class are known.
+fn walk_entry(bytes: &[u8], offset: &mut usize, is_record: bool) -> Result<bool, WalkError> {
+    let (name, end) = parse_name(bytes, *offset)?;
+    *offset = end;
+    if !well_formed(&name) {
+        return Err(WalkError::MalformedName);
+    }
+    let rtype = DnsType(read_u16(bytes, offset)?);
+    let rclass = DnsClass(read_u16(bytes, offset)?);
+    if is_record {
+        *offset += 4; // TTL
+        let length = read_u16(bytes, offset)? as usize;
+        *offset += length;
+        if *offset > bytes.len() {
+            return Err(WalkError::Truncated);
+        }
+    }
+    // The class of an OPT record is the requestor's UDP payload size.
+    let known_class = rtype == DnsTypes::OPT || rclass.mnemonic().is_some();
+    Ok(rtype.mnemonic().is_some() && known_class)
+}
+
+fn well_formed(name: &str) -> bool {
+    let mut wire = Vec::new();
+    write_name(name, &mut wire);
+    wire.len() <= 255 && name.chars().all(|c| c.is_ascii_graphic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::pcap::read_udp_datagrams;
+
+    const QUERY: &str = "abcd01000001000000000000076578616d706c6503636f6d0000010001";
+    const RESPONSE: &str = "abcd81800001000100000000076578616d706c6503636f6d0000010001c00c000100010000012c00045db8d822";
+
+    #[test]
+    fn test_detect_dns() {
+        for message in [QUERY, RESPONSE] {
+            let detection = detect(&hex::decode(message).unwrap());
+            assert_eq!(detection.score, 100);
+            assert!(detection.is_dns());
+            assert!(detection.packet.is_some());
+        }
+    }
+
+    #[test]
+    fn test_detect_odd_but_real_dns() {
+        // NXDOMAIN from a recursive resolver, with AA=0.
+        let mut bytes = hex::decode(QUERY).unwrap();
+        bytes[2..4].copy_from_slice(&[0x81, 0x83]);
+        let detection = detect(&bytes);
+        assert!(detection.has(Heuristic::InconsistentFlags));
+        assert!(detection.is_dns());
+        assert!(detection.packet.is_none());
+    }
+
+    #[test]
+    fn test_detect_not_dns() {
+        let detection = detect(b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n");
+        assert!(detection.has(Heuristic::ImplausibleCounts));
+        assert!(!detection.is_dns());
+
+        let detection = detect(&[0x17, 0x03, 0x03, 0x00, 0x20]);
+        assert_eq!(detection.heuristics, [Heuristic::TooShort]);
+        assert_eq!(detection.score, 0);
+    }
+
+    #[test]
+    fn test_detect_trailing_and_truncated() {
+        let mut bytes = hex::decode(RESPONSE).unwrap();
+        bytes.extend_from_slice(b"garbage");
+        let detection = detect(&bytes);
+        assert!(detection.has(Heuristic::TrailingData));
+        assert!(!detection.is_dns());
+        assert!(detection.packet.is_none());
+
+        let mut bytes = hex::decode(RESPONSE).unwrap();
+        bytes.extend_from_slice(&[0; 6]);
+        let detection = detect(&bytes);
+        assert!(detection.has(Heuristic::ZeroPadding));
+        assert!(detection.is_dns());
+        let packet = detection.packet.unwrap();
+        assert_eq!(
+            packet,
+            DnsPacket::try_from(&bytes[..bytes.len() - 6]).unwrap()
+        );
+
+        let bytes = hex::decode(RESPONSE).unwrap();
+        let detection = detect(&bytes[..bytes.len() - 2]);
+        assert!(detection.has(Heuristic::Truncated));
+        assert!(!detection.is_dns());
+    }
+
+    #[test]
+    fn test_detect_captures() {
+        for path in ["pcap_exemples/dns.cap", "pcap_exemples/NTP_sync.pcap"] {
+            for datagram in read_udp_datagrams(path) {
+                let is_dns = datagram.source.port() == 53 || datagram.destination.port() == 53;
+                assert_eq!(detect(&datagram.payload).is_dns(), is_dns, "{}", path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_detect_unknown_type() {
+        let mut bytes = hex::decode(QUERY).unwrap();
+        let qtype = bytes.len() - 4;
+        bytes[qtype..qtype + 2].copy_from_slice(&[0xff, 0x00]);
+        let detection = detect(&bytes);
+        assert!(detection.has(Heuristic::UnknownTypes));
+        assert!(detection.is_dns());
+    }
+}
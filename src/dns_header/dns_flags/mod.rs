@@ -1,5 +1,7 @@
 use errors::DnsFlagsError;
 
+use crate::validation::Rule;
+
 pub(crate) mod errors;
 
 /// Verifies the consistency of DNS packet flags.
@@ -35,19 +37,46 @@ pub(crate) mod errors;
 /// # Returns
 ///
 /// * `Result<u16, String>` - Ok(flags) if the flags are consistent, Err(message) otherwise.
+#[cfg(any(feature = "alloc", test))]
 pub fn verify_dns_flags(flags: u16) -> Result<u16, DnsFlagsError> {
+    match flag_violations(flags).next() {
+        Some((_, error)) => Err(error),
+        None => Ok(flags),
+    }
+}
+
+/// Lists every rule broken by `flags`, in the order `verify_dns_flags` checks them.
+pub(crate) fn flag_violations(flags: u16) -> impl Iterator<Item = (Rule, DnsFlagsError)> {
     let (qr, opcode, aa, tc, _rd, ra, z, rcode) = extract_dns_flags(flags);
+    let invalid_z = |_| DnsFlagsError::InvalidZField(z);
 
-    verify_z_field(z)?;
-    verify_opcode(opcode)?;
-    verify_rcode(rcode)?;
-    verify_ra_in_query(qr, ra)?;
+    let checks = [
+        (
+            Rule::ReservedZBit,
+            verify_z_field(z & 0b100).map_err(invalid_z),
+        ),
+        (Rule::AdCdBits, verify_z_field(z & 0b011).map_err(invalid_z)),
+        (Rule::UnknownOpcode, verify_opcode(opcode)),
+        (Rule::UnknownRcode, verify_rcode(rcode)),
+        (Rule::RaInQuery, verify_ra_in_query(qr, ra)),
+    ];
+    // The response rules only apply to responses.
+    let response_checks = verify_response_flags(opcode, aa, tc, rcode)
+        .map(|(rule, result)| (rule, if qr == 1 { result } else { Ok(()) }));
 
-    if qr == 1 {
-        verify_response_flags(opcode, aa, tc, rcode)?;
-    }
+    checks
+        .into_iter()
+        .chain(response_checks)
+        .filter_map(|(rule, result)| result.err().map(|error| (rule, error)))
+}
 
-    Ok(flags)
+/// Checks only the fields no DNS message sets: the reserved Z bit and the unassigned
+/// opcodes. The flag combinations some resolvers emit anyway, such as AD in a response or
+/// NXDOMAIN without AA, are not checked.
+pub(crate) fn verify_reserved_flags(flags: u16) -> Result<(), DnsFlagsError> {
+    let (_, opcode, _, _, _, _, z, _) = extract_dns_flags(flags);
+    verify_z_field(z & 0b100).map_err(|_| DnsFlagsError::InvalidZField(z))?;
+    verify_opcode(opcode)
 }
 
 /// Extracts DNS flags into their respective components.
@@ -87,7 +116,6 @@ fn extract_dns_flags(flags: u16) -> (u16, u16, u16, u16, u16, u16, u16, u16) {
 /// # Returns
 ///
 /// * `Result<(), String>` - Ok(()) if the Z field is valid, Err(message) otherwise.
-#[allow(dead_code)]
 fn verify_z_field(z: u16) -> Result<(), DnsFlagsError> {
     if z != 0 {
         return Err(DnsFlagsError::InvalidZField(z));
@@ -170,25 +198,35 @@ fn verify_ra_in_query(qr: u16, ra: u16) -> Result<(), DnsFlagsError> {
 ///
 /// # Returns
 ///
-/// * `Result<(), String>` - Ok(()) if the response flags are valid, Err(message) otherwise.
-fn verify_response_flags(opcode: u16, aa: u16, tc: u16, rcode: u16) -> Result<(), DnsFlagsError> {
-    if opcode == 2 && (aa != 0 || tc != 0) {
-        return Err(DnsFlagsError::AaTcInStatusResponse(aa, tc));
-    }
-
-    if rcode == 2 && aa != 0 {
-        return Err(DnsFlagsError::AaInServerFailure(aa));
-    }
-
-    if rcode == 3 && aa != 1 {
-        return Err(DnsFlagsError::AaInNameError(aa));
-    }
-
-    if rcode == 5 && aa != 0 {
-        return Err(DnsFlagsError::AaInRefused(aa));
-    }
-
-    Ok(())
+/// * `[(Rule, Result<(), DnsFlagsError>); 4]` - The result of each check, with its rule.
+fn verify_response_flags(
+    opcode: u16,
+    aa: u16,
+    tc: u16,
+    rcode: u16,
+) -> [(Rule, Result<(), DnsFlagsError>); 4] {
+    let check = |failed: bool, error| if failed { Err(error) } else { Ok(()) };
+    [
+        (
+            Rule::AaTcInStatusResponse,
+            check(
+                opcode == 2 && (aa != 0 || tc != 0),
+                DnsFlagsError::AaTcInStatusResponse(aa, tc),
+            ),
+        ),
+        (
+            Rule::AaInServerFailure,
+            check(rcode == 2 && aa != 0, DnsFlagsError::AaInServerFailure(aa)),
+        ),
+        (
+            Rule::AaInNameError,
+            check(rcode == 3 && aa != 1, DnsFlagsError::AaInNameError(aa)),
+        ),
+        (
+            Rule::AaInRefused,
+            check(rcode == 5 && aa != 0, DnsFlagsError::AaInRefused(aa)),
+        ),
+    ]
 }
 
 #[cfg(test)]
@@ -214,5 +252,22 @@ mod tests {
         assert_eq!(verify_rcode(5), Ok(()));
         assert_eq!(verify_rcode(6), Err(DnsFlagsError::InvalidRCode(6)));
     }
+    #[test]
+    fn test_flag_violations() {
+        // NXDOMAIN response with AA=0 and AD set.
+        let violations: Vec<_> = flag_violations(0x81A3).collect();
+        assert_eq!(
+            violations,
+            [
+                (Rule::AdCdBits, DnsFlagsError::InvalidZField(2)),
+                (Rule::AaInNameError, DnsFlagsError::AaInNameError(0)),
+            ]
+        );
+        assert_eq!(
+            verify_dns_flags(0x81A3),
+            Err(DnsFlagsError::InvalidZField(2))
+        );
+        assert_eq!(flag_violations(0x8180).next(), None);
+    }
     // Ajoutez d'autres tests similaires pour les autres fonctions de vérification
 }
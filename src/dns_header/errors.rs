@@ -10,3 +10,14 @@ pub enum DnsHeaderError {
     #[error("DNS Flags parsing error: {0}")]
     FlagsError(#[from] DnsFlagsError),
 }
+
+impl DnsHeaderError {
+    /// The offset of the field the error is about.
+    pub(crate) fn offset(&self) -> usize {
+        match self {
+            DnsHeaderError::PacketTooShort => 0,
+            DnsHeaderError::FlagsError(_) => 2,
+            DnsHeaderError::InvalidCounts => 4,
+        }
+    }
+}
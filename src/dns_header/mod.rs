@@ -1,10 +1,15 @@
-use std::fmt;
-mod dns_flags;
-use dns_flags::verify_dns_flags;
+use core::fmt;
+pub(crate) mod dns_flags;
+use dns_flags::flag_violations;
 use errors::DnsHeaderError;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::validation::{ParseOptions, Rule, RuleSet, WarningSink};
 pub(crate) mod errors;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DnsHeader {
     pub transaction_id: u16,
     pub flags: u16,
@@ -15,14 +20,42 @@ impl TryFrom<&[u8]> for DnsHeader {
     type Error = DnsHeaderError;
 
     fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse_with(bytes, &ParseOptions::default(), &mut RuleSet::default())
+    }
+}
+
+impl DnsHeader {
+    /// Reads the header fields without checking any rule; `bytes` must hold 12 bytes.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn unchecked(bytes: &[u8]) -> Self {
+        Self {
+            transaction_id: u16::from_be_bytes([bytes[0], bytes[1]]),
+            flags: u16::from_be_bytes([bytes[2], bytes[3]]),
+            counts: parse_count(&bytes[4..12]),
+        }
+    }
+
+    /// Parses the header, applying `options` to the flag and count rules; the violations
+    /// downgraded to warnings are appended to `warnings`.
+    pub(crate) fn parse_with(
+        bytes: &[u8],
+        options: &ParseOptions,
+        warnings: &mut impl WarningSink,
+    ) -> Result<Self, DnsHeaderError> {
         check_packet_length(bytes)?;
 
         let transaction_id = u16::from_be_bytes([bytes[0], bytes[1]]);
-        // println!("transaction_id: {}", transaction_id);
-        let flags = verify_dns_flags(u16::from_be_bytes([bytes[2], bytes[3]]))?;
-        // println!("flags: {}", flags);
-        let counts = validate_and_parse_count(&bytes[4..12])?;
-        // println!("transaction_id: {}, flags: {}, counts: {:?}", transaction_id, flags, counts);
+        let flags = u16::from_be_bytes([bytes[2], bytes[3]]);
+        for (rule, error) in flag_violations(flags) {
+            options.check(rule, DnsHeaderError::FlagsError(error), warnings)?;
+        }
+        let counts = match validate_and_parse_count(&bytes[4..12]) {
+            Ok(counts) => counts,
+            Err(error) => {
+                options.check(Rule::RecordsWithoutQuestion, error, warnings)?;
+                parse_count(&bytes[4..12])
+            }
+        };
         Ok(Self {
             transaction_id,
             flags,
@@ -53,11 +86,12 @@ fn check_packet_length(bytes: &[u8]) -> Result<(), DnsHeaderError> {
     Ok(())
 }
 
+fn parse_count(bytes: &[u8]) -> [u16; 4] {
+    [0, 2, 4, 6].map(|i| u16::from_be_bytes([bytes[i], bytes[i + 1]]))
+}
+
 fn validate_and_parse_count(bytes: &[u8]) -> Result<[u16; 4], DnsHeaderError> {
-    let questions_count = u16::from_be_bytes([bytes[0], bytes[1]]);
-    let answers_count = u16::from_be_bytes([bytes[2], bytes[3]]);
-    let authorities_count = u16::from_be_bytes([bytes[4], bytes[5]]);
-    let additionals_count = u16::from_be_bytes([bytes[6], bytes[7]]);
+    let [questions_count, answers_count, authorities_count, additionals_count] = parse_count(bytes);
 
     if questions_count == 0 && (answers_count > 0 || authorities_count > 0 || additionals_count > 0)
     {
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::dns_records::errors::DnsRecordParseError;
+
 #[derive(Debug, Error)]
 pub enum DnsQueryParseError {
     #[error("Insufficient data: required {required} more bytes at offset {offset}, but only {available} bytes available")]
@@ -8,8 +10,18 @@ pub enum DnsQueryParseError {
         offset: usize,
         available: usize,
     },
-    #[error("Out of bound parse")]
-    OutOfBoundParse,
-    #[error("UTF-8 parsing error: {0}")]
-    Utf8Error(#[from] std::string::FromUtf8Error),
+    #[error("Invalid name: {0}")]
+    NameError(#[from] DnsRecordParseError),
+}
+
+impl DnsQueryParseError {
+    /// The offset of the failing byte, for the errors that tell it.
+    pub(crate) fn offset(&self) -> Option<usize> {
+        match self {
+            DnsQueryParseError::InsufficientData {
+                offset, available, ..
+            } => Some(offset + available),
+            DnsQueryParseError::NameError(error) => error.offset(),
+        }
+    }
 }
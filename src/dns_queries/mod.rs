@@ -1,28 +1,41 @@
-use std::fmt;
+#[cfg(feature = "alloc")]
+use core::fmt;
 
+#[cfg(feature = "alloc")]
 use errors::DnsQueryParseError;
-
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "alloc")]
+use crate::dns_records::parse_name;
+#[cfg(feature = "alloc")]
+use crate::prelude::*;
+#[cfg(feature = "alloc")]
 use crate::utils::{dns_class::DnsClass, dns_types::DnsType};
 
 pub(crate) mod errors;
 
-#[derive(Debug, PartialEq)]
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct DnsQueries {
     pub queries: Vec<DnsQuery>,
 }
 
+#[cfg(feature = "alloc")]
 impl DnsQueries {
+    /// Parses `count` queries at the start of `bytes`.
     pub fn from_bytes(bytes: &[u8], count: u16) -> Result<Self, DnsQueryParseError> {
-        let mut queries = Vec::with_capacity(count as usize);
         let mut offset = 0;
-        for _ in 0..count {
-            check_dns_query_size(bytes, offset, 1)?;
-            queries.push(DnsQuery::from_bytes(bytes, &mut offset)?);
-        }
+        let queries = (0..count)
+            .map(|_| DnsQuery::from_bytes(bytes, &mut offset))
+            .collect::<Result<_, _>>()?;
         Ok(DnsQueries { queries })
     }
 }
 
+#[cfg(feature = "alloc")]
 fn check_dns_query_size(
     bytes: &[u8],
     offset: usize,
@@ -38,6 +51,7 @@ fn check_dns_query_size(
     Ok(())
 }
 
+#[cfg(feature = "alloc")]
 impl fmt::Display for DnsQueries {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "DnsQueries {{ queries: [")?;
@@ -48,14 +62,19 @@ impl fmt::Display for DnsQueries {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DnsQuery {
     pub name: String,
     pub qtype: DnsType,
     pub qclass: DnsClass,
 }
 
+#[cfg(feature = "alloc")]
 impl DnsQuery {
+    /// Parses a query at `offset` in the message `bytes`; offsets are absolute so that
+    /// compressed names can be followed.
     pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsQueryParseError> {
         let (name, new_offset) = parse_name(bytes, *offset)?;
         *offset = new_offset;
@@ -72,8 +91,14 @@ impl DnsQuery {
             qclass,
         })
     }
+
+    /// In mDNS, whether the querier asks for a unicast response (the QU bit).
+    pub fn unicast_response(&self) -> bool {
+        self.qclass.split_mdns_flag().1
+    }
 }
 
+#[cfg(feature = "alloc")]
 impl fmt::Display for DnsQuery {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -84,92 +109,45 @@ impl fmt::Display for DnsQuery {
     }
 }
 
-/// Parse un nom de domaine à partir d'un tableau d'octets, en suivant le format DNS.
-///
-/// # Arguments
-/// - `bytes`: Référence à un tableau d'octets représentant le message DNS.
-/// - `offset`: Position de départ dans `bytes` pour le parsing du nom de domaine.
-///
-/// # Returns
-/// - `Ok((String, usize))` : Un tuple contenant le nom de domaine sous forme de chaîne de caractères
-///   et la nouvelle valeur d'offset après le parsing.
-/// - `Err(DnsQueryParseError)` : Retourne une erreur si les données sont insuffisantes ou
-///   si une erreur de conversion UTF-8 survient.
-///
-/// # Errors
-/// - `DnsQueryParseError::OutOfBoundParse` si les données ne contiennent pas assez d'octets pour un parsing correct.
-/// - `DnsQueryParseError::Utf8Error` si les données ne sont pas des chaînes UTF-8 valides.
-fn parse_name(bytes: &[u8], mut offset: usize) -> Result<(String, usize), DnsQueryParseError> {
-    let mut labels = Vec::new(); // Stocke chaque label extrait du nom de domaine
-
-    loop {
-        // Vérifie que l'offset ne dépasse pas la longueur du tableau, sinon retourne une erreur
-        if offset >= bytes.len() {
-            return Err(DnsQueryParseError::OutOfBoundParse);
-        }
-
-        // Lit la longueur du prochain label (octet actuel)
-        let len = bytes[offset] as usize;
-
-        // Si la longueur est 0, le nom est terminé ; on avance l'offset d'un octet pour finir
-        if len == 0 {
-            offset += 1;
-            break;
-        }
-
-        // Avance l'offset d'un octet pour pointer au début du label
-        offset += 1;
-
-        // Vérifie que le label complet est dans les limites de `bytes`, sinon retourne une erreur
-        if offset + len > bytes.len() {
-            return Err(DnsQueryParseError::OutOfBoundParse);
-        }
-
-        // Convertit le label en UTF-8 ; retourne une erreur si la conversion échoue
-        let label = String::from_utf8(bytes[offset..offset + len].to_vec())?;
-        labels.push(label); // Ajoute le label à la liste
-
-        // Avance l'offset de la longueur du label pour traiter le suivant
-        offset += len;
-    }
-
-    // Joint tous les labels avec des points pour former le nom complet
-    let name = labels.join(".");
-    Ok((name, offset)) // Retourne le nom et la nouvelle position de l'offset
-}
-
-#[cfg(test)]
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
     use super::*;
+    use crate::dns_records::errors::DnsRecordParseError;
 
     #[test]
-    fn test_parse_name() {
+    fn test_dns_query_compressed_name() {
+        // The second query's name points to "google.com" in the first one.
         let data = vec![
-            0x03, 0x77, 0x77, 0x77, // "www"
-            0x06, 0x67, 0x6f, 0x6f, 0x67, 0x6c, 0x65, // "google"
-            0x03, 0x63, 0x6f, 0x6d, // "com"
-            0x00, // Null terminator of the domain name
+            3, b'w', b'w', b'w', 6, b'g', b'o', b'o', b'g', b'l', b'e', 3, b'c', b'o', b'm', 0, 0,
+            1, 0, 1, 4, b'm', b'a', b'i', b'l', 0xC0, 4, 0, 15, 0, 1,
         ];
-        let (name, offset) = parse_name(&data, 0).unwrap();
-        assert_eq!(name, "www.google.com");
-        assert_eq!(offset, 16);
+        let mut offset = 20;
+        let query = DnsQuery::from_bytes(&data, &mut offset).unwrap();
+        assert_eq!(query.name, "mail.google.com");
+        assert_eq!(query.qtype, DnsType(15));
+        assert_eq!(offset, data.len());
     }
 
     #[test]
-    fn test_parse_name_invalid_utf8() {
+    fn test_dns_query_invalid_utf8() {
         // This data includes bytes that do not form valid UTF-8 sequences for labels.
         let data = vec![
             0x02, 0xFF, 0xFF, // Invalid UTF-8 bytes
             0x00, // Null terminator
+            0, 1, 0, 1,
         ];
 
-        let result = parse_name(&data, 0);
-        assert!(result.is_err());
-        if let Err(DnsQueryParseError::Utf8Error(_)) = result {
-            // Passed: The error is as expected.
-        } else {
-            panic!("Expected Utf8Error, but got {:?}", result);
-        }
+        let result = DnsQuery::from_bytes(&data, &mut 0);
+        assert!(
+            matches!(
+                result,
+                Err(DnsQueryParseError::NameError(
+                    DnsRecordParseError::Utf8Error(_)
+                ))
+            ),
+            "Expected Utf8Error, but got {:?}",
+            result
+        );
     }
 
     #[test]
@@ -0,0 +1,60 @@
+use thiserror::Error;
+
+#[cfg(feature = "alloc")]
+use crate::prelude::*;
+
+use crate::utils::dns_types::DnsType;
+
+#[derive(Debug, Error)]
+pub enum DnsRecordParseError {
+    #[error("Insufficient data: required {required} more bytes at offset {offset}, but only {available} bytes available")]
+    InsufficientData {
+        required: usize,
+        offset: usize,
+        available: usize,
+    },
+    #[error("Invalid compression pointer at offset {offset}: target {target} is not before the current name")]
+    InvalidPointer { offset: usize, target: usize },
+    #[error("Invalid label type {label:#04x} at offset {offset}")]
+    InvalidLabelType { label: u8, offset: usize },
+    #[error("Invalid type bitmap at offset {offset}")]
+    InvalidTypeBitmap { offset: usize },
+    #[error("Invalid SvcParam key{key} at offset {offset}")]
+    InvalidSvcParam { key: u16, offset: usize },
+    #[error("Invalid CAA tag at offset {offset}")]
+    InvalidCaaTag { offset: usize },
+    #[error("Unsupported LOC version {version} at offset {offset}")]
+    UnsupportedLocVersion { version: u8, offset: usize },
+    #[error("Invalid APL item at offset {offset}")]
+    InvalidAplItem { offset: usize },
+    #[error("RDATA length mismatch for {rtype} record: declared {declared} bytes, but decoded {decoded}")]
+    RdataLengthMismatch {
+        rtype: DnsType,
+        declared: usize,
+        decoded: usize,
+    },
+    #[cfg(feature = "alloc")]
+    #[error("Invalid presentation format for {rtype} record: {value}")]
+    InvalidPresentation { rtype: DnsType, value: String },
+    #[error("UTF-8 parsing error: {0}")]
+    Utf8Error(#[from] core::str::Utf8Error),
+}
+
+impl DnsRecordParseError {
+    /// The offset of the failing byte, for the errors that tell it.
+    pub(crate) fn offset(&self) -> Option<usize> {
+        match self {
+            DnsRecordParseError::InsufficientData {
+                offset, available, ..
+            } => Some(offset + available),
+            DnsRecordParseError::InvalidPointer { offset, .. }
+            | DnsRecordParseError::InvalidLabelType { offset, .. }
+            | DnsRecordParseError::InvalidTypeBitmap { offset }
+            | DnsRecordParseError::InvalidSvcParam { offset, .. }
+            | DnsRecordParseError::InvalidCaaTag { offset }
+            | DnsRecordParseError::UnsupportedLocVersion { offset, .. }
+            | DnsRecordParseError::InvalidAplItem { offset } => Some(*offset),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,326 @@
+#[cfg(feature = "alloc")]
+use core::fmt;
+
+use errors::DnsRecordParseError;
+pub use name::{Labels, NameRef};
+#[cfg(feature = "alloc")]
+use rdata::RData;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "alloc")]
+use crate::errors::{DnsPacketError, Section};
+#[cfg(feature = "alloc")]
+use crate::prelude::*;
+#[cfg(feature = "alloc")]
+use crate::utils::{dns_class::DnsClass, dns_types::DnsType};
+
+pub(crate) mod errors;
+mod name;
+#[cfg(feature = "alloc")]
+pub mod rdata;
+
+#[cfg(feature = "alloc")]
+/// A resource record, as found in the answer, authority and additional sections.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ResourceRecord {
+    pub name: String,     // Domain name
+    pub rtype: DnsType,   // Type of record (e.g., A, AAAA, MX, etc.)
+    pub rclass: DnsClass, // Class of record (typically IN for Internet)
+    pub ttl: u32,         // Time to live
+    pub data_length: u16, // Length of the data
+    pub rdata: RData,     // Decoded data
+}
+
+#[cfg(feature = "alloc")]
+impl ResourceRecord {
+    /// Parses a resource record starting at `offset`.
+    ///
+    /// `bytes` must be the whole DNS message, since names may be compressed with pointers
+    /// to earlier parts of it.
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        Self::parse(bytes, offset, true)
+    }
+
+    /// Parses a record, keeping its data as raw bytes unless `decode_rdata` is set.
+    pub(crate) fn parse(
+        bytes: &[u8],
+        offset: &mut usize,
+        decode_rdata: bool,
+    ) -> Result<Self, DnsRecordParseError> {
+        let (name, new_offset) = parse_name(bytes, *offset)?;
+        *offset = new_offset;
+
+        let rtype = DnsType::new(read_u16(bytes, offset)?);
+        let rclass = DnsClass::new(read_u16(bytes, offset)?);
+        let ttl = read_u32(bytes, offset)?;
+        let data_length = read_u16(bytes, offset)?;
+
+        check_record_size(bytes, *offset, data_length as usize)?;
+        let end = *offset + data_length as usize;
+        let rdata = if decode_rdata {
+            RData::from_bytes(&bytes[..end], *offset, rtype)?
+        } else {
+            RData::Unknown(bytes[*offset..end].to_vec())
+        };
+        *offset = end;
+
+        Ok(ResourceRecord {
+            name,
+            rtype,
+            rclass,
+            ttl,
+            data_length,
+            rdata,
+        })
+    }
+
+    /// In mDNS, whether the record replaces the cached records of its name, type and
+    /// class (the cache-flush bit).
+    pub fn cache_flush(&self) -> bool {
+        self.rclass.split_mdns_flag().1
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for ResourceRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ResourceRecord {{ name: {}, rtype: {}, rclass: {}, ttl: {}, data_length: {}, rdata: {} }}",
+            self.name, self.rtype, self.rclass, self.ttl, self.data_length, self.rdata
+        )
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// Parses `count` consecutive resource records starting at `offset`.
+///
+/// Returns `None` for an empty section, matching how `DnsPacket` represents absent records;
+/// errors are located in `section`.
+pub(crate) fn parse_records(
+    bytes: &[u8],
+    offset: &mut usize,
+    count: u16,
+    section: Section,
+) -> Result<Option<Vec<ResourceRecord>>, DnsPacketError> {
+    if count == 0 {
+        return Ok(None);
+    }
+    let mut records = Vec::with_capacity(count as usize);
+    for index in 0..count as usize {
+        let start = *offset;
+        let record = ResourceRecord::from_bytes(bytes, offset)
+            .map_err(|error| DnsPacketError::new(error, section, Some(index), start))?;
+        records.push(record);
+    }
+    Ok(Some(records))
+}
+
+pub(crate) fn check_record_size(
+    bytes: &[u8],
+    offset: usize,
+    required_size: usize,
+) -> Result<(), DnsRecordParseError> {
+    if offset + required_size > bytes.len() {
+        return Err(DnsRecordParseError::InsufficientData {
+            required: required_size,
+            offset,
+            available: bytes.len().saturating_sub(offset),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) fn read_u8(bytes: &[u8], offset: &mut usize) -> Result<u8, DnsRecordParseError> {
+    check_record_size(bytes, *offset, 1)?;
+    *offset += 1;
+    Ok(bytes[*offset - 1])
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) fn read_u16(bytes: &[u8], offset: &mut usize) -> Result<u16, DnsRecordParseError> {
+    Ok(u16::from_be_bytes(read_array(bytes, offset)?))
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) fn read_u32(bytes: &[u8], offset: &mut usize) -> Result<u32, DnsRecordParseError> {
+    Ok(u32::from_be_bytes(read_array(bytes, offset)?))
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) fn read_array<const N: usize>(
+    bytes: &[u8],
+    offset: &mut usize,
+) -> Result<[u8; N], DnsRecordParseError> {
+    let mut array = [0; N];
+    array.copy_from_slice(read_bytes(bytes, offset, N)?);
+    Ok(array)
+}
+
+pub(crate) fn read_bytes<'a>(
+    bytes: &'a [u8],
+    offset: &mut usize,
+    len: usize,
+) -> Result<&'a [u8], DnsRecordParseError> {
+    check_record_size(bytes, *offset, len)?;
+    *offset += len;
+    Ok(&bytes[*offset - len..*offset])
+}
+
+#[cfg(feature = "alloc")]
+/// Parses a possibly compressed domain name starting at `offset`.
+///
+/// Labels are joined with dots; dots and backslashes inside a label are escaped with a
+/// backslash, as in the presentation format. See `NameRef::parse` for the rules.
+///
+/// # Returns
+/// - `Ok((String, usize))`: the name and the offset right after it in the record
+///   (i.e. after the first pointer if the name is compressed).
+pub(crate) fn parse_name(
+    bytes: &[u8],
+    offset: usize,
+) -> Result<(String, usize), DnsRecordParseError> {
+    let (name, end) = NameRef::parse(bytes, offset)?;
+    Ok((name.to_string(), end))
+}
+
+#[cfg(feature = "alloc")]
+/// Writes `name` in uncompressed wire format.
+///
+/// `name` is in the format produced by `parse_name`: dot-separated labels where `\.` and
+/// `\\` stand for a literal dot and backslash. A trailing dot is accepted, and both the
+/// empty name and `.` are the root.
+pub(crate) fn write_name(name: &str, buf: &mut Vec<u8>) {
+    let mut label = Vec::new();
+    let mut chars = name.bytes();
+    while let Some(byte) = chars.next() {
+        match byte {
+            b'\\' => label.extend(chars.next()),
+            b'.' if !label.is_empty() => {
+                buf.push(label.len() as u8);
+                buf.append(&mut label);
+            }
+            b'.' => {}
+            _ => label.push(byte),
+        }
+    }
+    if !label.is_empty() {
+        buf.push(label.len() as u8);
+        buf.append(&mut label);
+    }
+    buf.push(0);
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::utils::{dns_class::DnsClasses, dns_types::DnsTypes};
+    use core::net::Ipv4Addr;
+
+    #[test]
+    fn test_parse_name_with_pointer() {
+        let data = vec![
+            0x07, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0x03, b'c', b'o', b'm',
+            0x00, // example.com
+            0x03, b'w', b'w', b'w', 0xC0, 0x00, // www + pointer to offset 0
+        ];
+        let (name, offset) = parse_name(&data, 13).unwrap();
+        assert_eq!(name, "www.example.com");
+        assert_eq!(offset, 19);
+    }
+
+    #[test]
+    fn test_parse_name_rejects_pointer_loop() {
+        let data = vec![0x01, b'a', 0xC0, 0x00];
+        let result = parse_name(&data, 0);
+        assert!(matches!(
+            result,
+            Err(DnsRecordParseError::InvalidPointer {
+                offset: 2,
+                target: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_name_escapes_dots() {
+        let data = vec![0x03, b'a', b'.', b'b', 0x03, b'c', b'o', b'm', 0x00];
+        let (name, _) = parse_name(&data, 0).unwrap();
+        assert_eq!(name, "a\\.b.com");
+    }
+
+    #[test]
+    fn test_write_name_round_trip() {
+        for name in ["www.example.com", "a\\.b.com", ""] {
+            let mut buf = Vec::new();
+            write_name(name, &mut buf);
+            assert_eq!(parse_name(&buf, 0).unwrap(), (name.to_string(), buf.len()));
+        }
+        let mut buf = Vec::new();
+        write_name("example.com.", &mut buf);
+        assert_eq!(buf, b"\x07example\x03com\x00");
+    }
+
+    #[test]
+    fn test_resource_record_from_bytes() {
+        let data = vec![
+            0x03, b'c', b'o', b'm', 0x00, // com
+            0xC0, 0x00, 0x00, 0x01, 0x00, 0x01, // pointer to com, A, IN
+            0x00, 0x00, 0x0e, 0x10, 0x00, 0x04, 192, 0, 2, 1, // TTL 3600, 4 bytes of data
+        ];
+        let mut offset = 5;
+        let record = ResourceRecord::from_bytes(&data, &mut offset).unwrap();
+        assert_eq!(record.name, "com");
+        assert_eq!(record.rtype, DnsTypes::A);
+        assert_eq!(record.rclass, DnsClasses::IN);
+        assert_eq!(record.ttl, 3600);
+        assert_eq!(record.rdata, RData::A(Ipv4Addr::new(192, 0, 2, 1)));
+        assert_eq!(offset, data.len());
+    }
+
+    #[test]
+    fn test_resource_record_unknown_type_round_trip() {
+        // RFC 3597 §2: the data of unknown types is kept and written back unchanged.
+        let data = vec![
+            0x00, 0xff, 0x00, 0x00, 0x63, 0x00, 0x00, 0x00,
+            0x3c, // root, TYPE65280, CLASS99, TTL 60
+            0x00, 0x05, 0xc0, 0x00, 0x00, 0x0a,
+            0x01, // 5 bytes of data, looking like a pointer
+        ];
+        let mut offset = 0;
+        let record = ResourceRecord::from_bytes(&data, &mut offset).unwrap();
+        assert_eq!(record.rtype.to_string(), "TYPE65280");
+        assert_eq!(record.rclass.to_string(), "CLASS99");
+        assert_eq!(record.rdata.to_string(), "\\# 5 C000000A01");
+
+        let mut wire = Vec::new();
+        write_name(&record.name, &mut wire);
+        wire.extend_from_slice(&record.rtype.0.to_be_bytes());
+        wire.extend_from_slice(&record.rclass.0.to_be_bytes());
+        wire.extend_from_slice(&record.ttl.to_be_bytes());
+        let mut rdata = Vec::new();
+        record.rdata.to_wire(&mut rdata);
+        wire.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        wire.extend_from_slice(&rdata);
+        assert_eq!(wire, data);
+
+        let presentation = RData::from_presentation(record.rtype, &record.rdata.to_string());
+        assert_eq!(presentation.unwrap(), record.rdata);
+    }
+
+    #[test]
+    fn test_resource_record_truncated_rdata() {
+        let data = vec![
+            0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 192,
+        ];
+        let mut offset = 0;
+        let result = ResourceRecord::from_bytes(&data, &mut offset);
+        assert!(matches!(
+            result,
+            Err(DnsRecordParseError::InsufficientData { required: 4, .. })
+        ));
+    }
+}
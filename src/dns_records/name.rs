@@ -0,0 +1,162 @@
+use core::fmt;
+
+use super::errors::DnsRecordParseError;
+use super::{check_record_size, read_bytes};
+
+/// A domain name borrowed from a message, decoded on demand.
+///
+/// A `NameRef` is only built by validating the name, so iterating over its labels
+/// cannot fail. It displays in presentation format, like the names of `ResourceRecord`.
+#[derive(Clone, Copy, Debug)]
+pub struct NameRef<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> NameRef<'a> {
+    /// Validates the possibly compressed name at `offset` in the message `bytes`, and
+    /// returns it with the offset right after it (after the first pointer if compressed).
+    ///
+    /// Compression pointers must point strictly before the previous jump target, which
+    /// rules out pointer loops, and labels must be UTF-8.
+    pub(crate) fn parse(
+        bytes: &'a [u8],
+        offset: usize,
+    ) -> Result<(Self, usize), DnsRecordParseError> {
+        let mut position = offset;
+        let mut limit = offset;
+        let mut end = None;
+
+        loop {
+            check_record_size(bytes, position, 1)?;
+            let len = bytes[position];
+
+            match len & 0xC0 {
+                0xC0 => {
+                    check_record_size(bytes, position, 2)?;
+                    let target = u16::from_be_bytes([len & 0x3F, bytes[position + 1]]) as usize;
+                    if target >= limit {
+                        return Err(DnsRecordParseError::InvalidPointer {
+                            offset: position,
+                            target,
+                        });
+                    }
+                    end.get_or_insert(position + 2);
+                    limit = target;
+                    position = target;
+                }
+                0x00 => {
+                    position += 1;
+                    if len == 0 {
+                        break;
+                    }
+                    let label = read_bytes(bytes, &mut position, len as usize)?;
+                    core::str::from_utf8(label)?;
+                }
+                _ => {
+                    return Err(DnsRecordParseError::InvalidLabelType {
+                        label: len,
+                        offset: position,
+                    })
+                }
+            }
+        }
+
+        Ok((NameRef { bytes, offset }, end.unwrap_or(position)))
+    }
+
+    /// Returns the name at `offset` with the offset right after it, without validating it
+    /// again; for names that already went through `parse`.
+    pub(crate) fn validated(bytes: &'a [u8], offset: usize) -> Option<(Self, usize)> {
+        let mut position = offset;
+        loop {
+            let len = *bytes.get(position)?;
+            match len {
+                0 => return Some((NameRef { bytes, offset }, position + 1)),
+                len if len & 0xC0 == 0xC0 => {
+                    return Some((NameRef { bytes, offset }, position + 2))
+                }
+                len => position += 1 + len as usize,
+            }
+        }
+    }
+
+    /// The labels of the name, from the leftmost one; the root has none.
+    pub fn labels(&self) -> Labels<'a> {
+        Labels {
+            bytes: self.bytes,
+            position: self.offset,
+        }
+    }
+
+    /// Whether the name is `name`, given in presentation format, ignoring ASCII case.
+    pub fn eq_ignore_ascii_case(&self, name: &str) -> bool {
+        let name = match name.strip_suffix('.') {
+            Some(stripped) if !stripped.ends_with('\\') => stripped,
+            _ => name,
+        };
+        let mut rest = name.as_bytes();
+        for label in self.labels() {
+            let mut chars = rest.iter();
+            for byte in label {
+                let expected = match chars.next() {
+                    Some(b'\\') => chars.next(),
+                    other => other.filter(|&&c| c != b'.'),
+                };
+                if !expected.is_some_and(|expected| expected.eq_ignore_ascii_case(byte)) {
+                    return false;
+                }
+            }
+            if !matches!(chars.next(), Some(b'.') | None) {
+                return false;
+            }
+            rest = chars.as_slice();
+        }
+        rest.is_empty()
+    }
+}
+
+impl fmt::Display for NameRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, label) in self.labels().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            for c in core::str::from_utf8(label).unwrap_or_default().chars() {
+                if c == '.' || c == '\\' {
+                    f.write_str("\\")?;
+                }
+                write!(f, "{}", c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The labels of a `NameRef`, following compression pointers.
+#[derive(Clone, Debug)]
+pub struct Labels<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Iterator for Labels<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let len = *self.bytes.get(self.position)? as usize;
+            if len & 0xC0 == 0xC0 {
+                let low = *self.bytes.get(self.position + 1)? as usize;
+                self.position = ((len & 0x3F) << 8) | low;
+                continue;
+            }
+            if len == 0 {
+                return None;
+            }
+            let label = self.bytes.get(self.position + 1..self.position + 1 + len)?;
+            self.position += 1 + len;
+            return Some(label);
+        }
+    }
+}
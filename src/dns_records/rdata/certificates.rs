@@ -0,0 +1,378 @@
+use core::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::character_string::write_escaped;
+use super::{read_rest, unescape};
+use crate::dns_records::{errors::DnsRecordParseError, read_bytes, read_u16, read_u8};
+use crate::prelude::*;
+use crate::utils::encoding::{base64_decode, base64_encode};
+
+/// CAA record data (RFC 8659 §4.1): a property restricting which certification
+/// authorities may issue certificates for the domain.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Caa {
+    pub flags: u8,
+    /// The property tag, e.g. `issue`, `issuewild` or `iodef`.
+    pub tag: String,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+    pub value: Vec<u8>,
+}
+
+impl Caa {
+    /// The Issuer Critical flag: a CA that does not understand the tag must not issue.
+    pub const CRITICAL: u8 = 0x80;
+
+    /// Whether the Issuer Critical flag is set.
+    pub fn critical(&self) -> bool {
+        self.flags & Caa::CRITICAL != 0
+    }
+
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let flags = read_u8(bytes, offset)?;
+        let start = *offset;
+        let length = read_u8(bytes, offset)?;
+        let tag = read_bytes(bytes, offset, length as usize)?;
+        if !is_valid_tag(tag) {
+            return Err(DnsRecordParseError::InvalidCaaTag { offset: start });
+        }
+        Ok(Caa {
+            flags,
+            tag: String::from_utf8_lossy(tag).into_owned(),
+            value: read_rest(bytes, offset),
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.push(self.flags);
+        buf.push(self.tag.len() as u8);
+        buf.extend_from_slice(self.tag.as_bytes());
+        buf.extend_from_slice(&self.value);
+    }
+
+    /// Parses the presentation tokens `flags tag value`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [flags, tag, value] if is_valid_tag(tag.as_bytes()) => Some(Caa {
+                flags: flags.parse().ok()?,
+                tag: tag.clone(),
+                value: unescape(value)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Caa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} \"", self.flags, self.tag)?;
+        write_escaped(f, &self.value)?;
+        write!(f, "\"")
+    }
+}
+
+/// A CAA tag is 1 to 15 ASCII letters and digits.
+fn is_valid_tag(tag: &[u8]) -> bool {
+    (1..=15).contains(&tag.len()) && tag.iter().all(u8::is_ascii_alphanumeric)
+}
+
+/// TLSA and SMIMEA record data (RFC 6698 §2.1, RFC 8162): the certificate or public key
+/// expected for a TLS service or an email address.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Tlsa {
+    /// How the data is matched: 0 PKIX-TA, 1 PKIX-EE, 2 DANE-TA or 3 DANE-EE.
+    pub usage: u8,
+    /// 0 for the full certificate, 1 for its SubjectPublicKeyInfo.
+    pub selector: u8,
+    /// 0 for the exact data, 1 for its SHA-256 digest, 2 for its SHA-512 digest.
+    pub matching_type: u8,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+    pub data: Vec<u8>,
+}
+
+impl Tlsa {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        Ok(Tlsa {
+            usage: read_u8(bytes, offset)?,
+            selector: read_u8(bytes, offset)?,
+            matching_type: read_u8(bytes, offset)?,
+            data: read_rest(bytes, offset),
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.push(self.usage);
+        buf.push(self.selector);
+        buf.push(self.matching_type);
+        buf.extend_from_slice(&self.data);
+    }
+
+    /// Parses the presentation tokens `usage selector matching-type data...`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [usage, selector, matching_type, data @ ..] if !data.is_empty() => Some(Tlsa {
+                usage: usage.parse().ok()?,
+                selector: selector.parse().ok()?,
+                matching_type: matching_type.parse().ok()?,
+                data: hex::decode(data.concat()).ok()?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Tlsa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.usage,
+            self.selector,
+            self.matching_type,
+            hex::encode_upper(&self.data)
+        )
+    }
+}
+
+/// SSHFP record data (RFC 4255 §3.1): the fingerprint of an SSH host key.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Sshfp {
+    /// The key algorithm: 1 RSA, 2 DSA, 3 ECDSA, 4 Ed25519 or 6 Ed448.
+    pub algorithm: u8,
+    /// The digest: 1 SHA-1 or 2 SHA-256.
+    pub fingerprint_type: u8,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+    pub fingerprint: Vec<u8>,
+}
+
+impl Sshfp {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        Ok(Sshfp {
+            algorithm: read_u8(bytes, offset)?,
+            fingerprint_type: read_u8(bytes, offset)?,
+            fingerprint: read_rest(bytes, offset),
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.push(self.algorithm);
+        buf.push(self.fingerprint_type);
+        buf.extend_from_slice(&self.fingerprint);
+    }
+
+    /// Parses the presentation tokens `algorithm fingerprint-type fingerprint...`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [algorithm, fingerprint_type, fingerprint @ ..] if !fingerprint.is_empty() => {
+                Some(Sshfp {
+                    algorithm: algorithm.parse().ok()?,
+                    fingerprint_type: fingerprint_type.parse().ok()?,
+                    fingerprint: hex::decode(fingerprint.concat()).ok()?,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Sshfp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.algorithm,
+            self.fingerprint_type,
+            hex::encode_upper(&self.fingerprint)
+        )
+    }
+}
+
+/// The certificate types of CERT records (RFC 4398 §2.1).
+#[allow(non_snake_case)]
+pub mod CertTypes {
+    pub const PKIX: u16 = 1;
+    pub const SPKI: u16 = 2;
+    pub const PGP: u16 = 3;
+    pub const IPKIX: u16 = 4;
+    pub const ISPKI: u16 = 5;
+    pub const IPGP: u16 = 6;
+    pub const ACPKIX: u16 = 7;
+    pub const IACPKIX: u16 = 8;
+    pub const URI: u16 = 253;
+    pub const OID: u16 = 254;
+}
+
+/// The mnemonics of the certificate types, displayed instead of their number.
+const CERT_TYPE_NAMES: [(u16, &str); 10] = [
+    (CertTypes::PKIX, "PKIX"),
+    (CertTypes::SPKI, "SPKI"),
+    (CertTypes::PGP, "PGP"),
+    (CertTypes::IPKIX, "IPKIX"),
+    (CertTypes::ISPKI, "ISPKI"),
+    (CertTypes::IPGP, "IPGP"),
+    (CertTypes::ACPKIX, "ACPKIX"),
+    (CertTypes::IACPKIX, "IACPKIX"),
+    (CertTypes::URI, "URI"),
+    (CertTypes::OID, "OID"),
+];
+
+/// CERT record data (RFC 4398 §2): a certificate or certificate revocation list.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cert {
+    /// The certificate type, see `CertTypes`.
+    pub cert_type: u16,
+    pub key_tag: u16,
+    /// The DNSSEC algorithm of the key, or 0 when unknown.
+    pub algorithm: u8,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+    pub certificate: Vec<u8>,
+}
+
+impl Cert {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        Ok(Cert {
+            cert_type: read_u16(bytes, offset)?,
+            key_tag: read_u16(bytes, offset)?,
+            algorithm: read_u8(bytes, offset)?,
+            certificate: read_rest(bytes, offset),
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.cert_type.to_be_bytes());
+        buf.extend_from_slice(&self.key_tag.to_be_bytes());
+        buf.push(self.algorithm);
+        buf.extend_from_slice(&self.certificate);
+    }
+
+    /// Parses the presentation tokens `type key-tag algorithm certificate...`, where the
+    /// type is a mnemonic or a number.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [cert_type, key_tag, algorithm, certificate @ ..] if !certificate.is_empty() => {
+                let cert_type = match CERT_TYPE_NAMES
+                    .iter()
+                    .find(|(_, name)| name.eq_ignore_ascii_case(cert_type))
+                {
+                    Some(&(value, _)) => value,
+                    None => cert_type.parse().ok()?,
+                };
+                Some(Cert {
+                    cert_type,
+                    key_tag: key_tag.parse().ok()?,
+                    algorithm: algorithm.parse().ok()?,
+                    certificate: base64_decode(&certificate.concat())?,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Cert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match CERT_TYPE_NAMES
+            .iter()
+            .find(|&&(value, _)| value == self.cert_type)
+        {
+            Some((_, name)) => write!(f, "{}", name)?,
+            None => write!(f, "{}", self.cert_type)?,
+        }
+        write!(
+            f,
+            " {} {} {}",
+            self.key_tag,
+            self.algorithm,
+            base64_encode(&self.certificate)
+        )
+    }
+}
+
+/// OPENPGPKEY record data (RFC 7929 §2.1): an OpenPGP Transferable Public Key.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Openpgpkey {
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+    pub public_key: Vec<u8>,
+}
+
+impl Openpgpkey {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        Ok(Openpgpkey {
+            public_key: read_rest(bytes, offset),
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.public_key);
+    }
+
+    /// Parses the presentation tokens `public-key...`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        if tokens.is_empty() {
+            return None;
+        }
+        Some(Openpgpkey {
+            public_key: base64_decode(&tokens.concat())?,
+        })
+    }
+}
+
+impl fmt::Display for Openpgpkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", base64_encode(&self.public_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caa() {
+        // RFC 8659 §4.1.1: 0 issue "ca.example.net", on the wire.
+        let wire = b"\x00\x05issueca.example.net";
+        let mut offset = 0;
+        let caa = Caa::from_bytes(wire, &mut offset).unwrap();
+        assert_eq!(offset, wire.len());
+        assert_eq!(caa.tag, "issue");
+        assert!(!caa.critical());
+        assert_eq!(caa.to_string(), "0 issue \"ca.example.net\"");
+
+        let caa = Caa::from_tokens(&tokenize("128 tbs \"Unknown; say \\\"hi\\\"\\000\"")).unwrap();
+        assert!(caa.critical());
+        assert_eq!(caa.value, b"Unknown; say \"hi\"\x00");
+        assert_eq!(caa.to_string(), "128 tbs \"Unknown; say \\\"hi\\\"\\000\"");
+
+        let mut offset = 0;
+        let result = Caa::from_bytes(b"\x00\x04is-ue", &mut offset);
+        assert!(matches!(
+            result,
+            Err(DnsRecordParseError::InvalidCaaTag { offset: 1 })
+        ));
+        assert_eq!(
+            Caa::from_tokens(&tokenize("0 \"\" \"ca.example.net\"")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cert_type_mnemonics() {
+        let cert = Cert::from_tokens(&tokenize("pgp 0 0 AQID")).unwrap();
+        assert_eq!(cert.cert_type, CertTypes::PGP);
+        assert_eq!(cert.certificate, [1, 2, 3]);
+        assert_eq!(cert.to_string(), "PGP 0 0 AQID");
+
+        let cert = Cert::from_tokens(&tokenize("65280 12345 8 AQID")).unwrap();
+        assert_eq!(cert.to_string(), "65280 12345 8 AQID");
+    }
+
+    fn tokenize(s: &str) -> Vec<String> {
+        super::super::tokenize(s).unwrap()
+    }
+}
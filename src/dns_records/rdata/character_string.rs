@@ -0,0 +1,124 @@
+use core::fmt;
+use core::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::dns_records::{errors::DnsRecordParseError, read_bytes, read_u8};
+use crate::prelude::*;
+
+/// A `<character-string>`: up to 255 arbitrary bytes prefixed by their length.
+///
+/// Displayed (and serialized) in presentation format, without the surrounding quotes:
+/// `"` and `\` are escaped with a backslash, and non-printable bytes as `\DDD`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CharacterString(pub Vec<u8>);
+
+impl CharacterString {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let len = read_u8(bytes, offset)?;
+        Ok(CharacterString(
+            read_bytes(bytes, offset, len as usize)?.to_vec(),
+        ))
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.push(self.0.len() as u8);
+        buf.extend_from_slice(&self.0);
+    }
+}
+
+impl fmt::Display for CharacterString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_escaped(f, &self.0)
+    }
+}
+
+/// Writes `bytes` with the escapes of a character-string, without the surrounding quotes.
+pub(super) fn write_escaped(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    for &byte in bytes {
+        match byte {
+            b'"' | b'\\' => write!(f, "\\{}", byte as char)?,
+            0x20..=0x7E => write!(f, "{}", byte as char)?,
+            _ => write!(f, "\\{:03}", byte)?,
+        }
+    }
+    Ok(())
+}
+
+impl FromStr for CharacterString {
+    type Err = CharacterStringParseError;
+
+    /// Parses the escaped presentation format produced by `Display`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = Vec::with_capacity(s.len());
+        let mut input = s.bytes();
+        while let Some(byte) = input.next() {
+            if byte != b'\\' {
+                bytes.push(byte);
+                continue;
+            }
+            match input.next() {
+                Some(digit @ b'0'..=b'9') => {
+                    let mut value = (digit - b'0') as u32;
+                    for _ in 0..2 {
+                        match input.next() {
+                            Some(digit @ b'0'..=b'9') => value = value * 10 + (digit - b'0') as u32,
+                            _ => return Err(CharacterStringParseError(s.to_string())),
+                        }
+                    }
+                    let value = u8::try_from(value)
+                        .map_err(|_| CharacterStringParseError(s.to_string()))?;
+                    bytes.push(value);
+                }
+                Some(escaped) => bytes.push(escaped),
+                None => return Err(CharacterStringParseError(s.to_string())),
+            }
+        }
+        if bytes.len() > 255 {
+            return Err(CharacterStringParseError(s.to_string()));
+        }
+        Ok(CharacterString(bytes))
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+#[error("Invalid character-string: {0}")]
+pub struct CharacterStringParseError(String);
+
+#[cfg(feature = "serde")]
+impl Serialize for CharacterString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for CharacterString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_character_string_round_trip() {
+        let string = CharacterString(b"say \"hi\"\\\x00\xff".to_vec());
+        let presentation = string.to_string();
+        assert_eq!(presentation, "say \\\"hi\\\"\\\\\\000\\255");
+        assert_eq!(presentation.parse(), Ok(string));
+    }
+
+    #[test]
+    fn test_character_string_invalid_escape() {
+        assert!("\\25".parse::<CharacterString>().is_err());
+        assert!("\\256".parse::<CharacterString>().is_err());
+        assert!("trailing\\".parse::<CharacterString>().is_err());
+    }
+}
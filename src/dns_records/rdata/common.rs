@@ -0,0 +1,228 @@
+use core::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::CharacterString;
+use crate::dns_records::{errors::DnsRecordParseError, parse_name, read_u16, read_u32, write_name};
+use crate::prelude::*;
+
+/// SOA record data (RFC 1035 §3.3.13).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Soa {
+    pub mname: String,
+    pub rname: String,
+    pub serial: u32,
+    pub refresh: u32,
+    pub retry: u32,
+    pub expire: u32,
+    pub minimum: u32,
+}
+
+impl Soa {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let (mname, new_offset) = parse_name(bytes, *offset)?;
+        let (rname, new_offset) = parse_name(bytes, new_offset)?;
+        *offset = new_offset;
+        Ok(Soa {
+            mname,
+            rname,
+            serial: read_u32(bytes, offset)?,
+            refresh: read_u32(bytes, offset)?,
+            retry: read_u32(bytes, offset)?,
+            expire: read_u32(bytes, offset)?,
+            minimum: read_u32(bytes, offset)?,
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        write_name(&self.mname, buf);
+        write_name(&self.rname, buf);
+        for value in [
+            self.serial,
+            self.refresh,
+            self.retry,
+            self.expire,
+            self.minimum,
+        ] {
+            buf.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+
+    /// Parses the presentation tokens `mname rname serial refresh retry expire minimum`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [mname, rname, serial, refresh, retry, expire, minimum] => Some(Soa {
+                mname: mname.clone(),
+                rname: rname.clone(),
+                serial: serial.parse().ok()?,
+                refresh: refresh.parse().ok()?,
+                retry: retry.parse().ok()?,
+                expire: expire.parse().ok()?,
+                minimum: minimum.parse().ok()?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Soa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {} {}",
+            self.mname,
+            self.rname,
+            self.serial,
+            self.refresh,
+            self.retry,
+            self.expire,
+            self.minimum
+        )
+    }
+}
+
+/// MX record data (RFC 1035 §3.3.9).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Mx {
+    pub preference: u16,
+    pub exchange: String,
+}
+
+impl Mx {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let preference = read_u16(bytes, offset)?;
+        let (exchange, new_offset) = parse_name(bytes, *offset)?;
+        *offset = new_offset;
+        Ok(Mx {
+            preference,
+            exchange,
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.preference.to_be_bytes());
+        write_name(&self.exchange, buf);
+    }
+
+    /// Parses the presentation tokens `preference exchange`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [preference, exchange] => Some(Mx {
+                preference: preference.parse().ok()?,
+                exchange: exchange.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Mx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.preference, self.exchange)
+    }
+}
+
+/// TXT record data (RFC 1035 §3.3.14): one or more character-strings.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Txt {
+    pub strings: Vec<CharacterString>,
+}
+
+impl Txt {
+    /// Parses character-strings until the end of `bytes`, which must stop at the end of the RDATA.
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let mut strings = Vec::new();
+        while *offset < bytes.len() {
+            strings.push(CharacterString::from_bytes(bytes, offset)?);
+        }
+        Ok(Txt { strings })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        for string in &self.strings {
+            string.to_wire(buf);
+        }
+    }
+
+    /// Parses one character-string per presentation token.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        let strings = tokens
+            .iter()
+            .map(|token| token.parse().ok())
+            .collect::<Option<Vec<_>>>()?;
+        Some(Txt { strings })
+    }
+}
+
+impl fmt::Display for Txt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, string) in self.strings.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "\"{}\"", string)?;
+        }
+        Ok(())
+    }
+}
+
+/// SRV record data (RFC 2782).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Srv {
+    pub priority: u16,
+    pub weight: u16,
+    pub port: u16,
+    pub target: String,
+}
+
+impl Srv {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let priority = read_u16(bytes, offset)?;
+        let weight = read_u16(bytes, offset)?;
+        let port = read_u16(bytes, offset)?;
+        let (target, new_offset) = parse_name(bytes, *offset)?;
+        *offset = new_offset;
+        Ok(Srv {
+            priority,
+            weight,
+            port,
+            target,
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.priority.to_be_bytes());
+        buf.extend_from_slice(&self.weight.to_be_bytes());
+        buf.extend_from_slice(&self.port.to_be_bytes());
+        write_name(&self.target, buf);
+    }
+
+    /// Parses the presentation tokens `priority weight port target`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [priority, weight, port, target] => Some(Srv {
+                priority: priority.parse().ok()?,
+                weight: weight.parse().ok()?,
+                port: port.parse().ok()?,
+                target: target.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Srv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.priority, self.weight, self.port, self.target
+        )
+    }
+}
@@ -0,0 +1,657 @@
+use alloc::collections::BTreeSet;
+use core::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::read_rest;
+use crate::dns_records::{
+    errors::DnsRecordParseError, parse_name, read_bytes, read_u16, read_u32, read_u8, write_name,
+};
+use crate::prelude::*;
+use crate::utils::dns_types::DnsType;
+use crate::utils::encoding::{base32hex_decode, base32hex_encode, base64_decode, base64_encode};
+
+/// DNSKEY and CDNSKEY record data (RFC 4034 §2, RFC 7344).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Dnskey {
+    pub flags: u16,
+    pub protocol: u8,
+    pub algorithm: u8,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+    pub public_key: Vec<u8>,
+}
+
+impl Dnskey {
+    /// The Zone Key flag: the key may sign the zone's records.
+    pub const ZONE: u16 = 0x0100;
+    /// The Secure Entry Point flag, set on key-signing keys.
+    pub const SEP: u16 = 0x0001;
+    /// The REVOKE flag (RFC 5011).
+    pub const REVOKE: u16 = 0x0080;
+
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        Ok(Dnskey {
+            flags: read_u16(bytes, offset)?,
+            protocol: read_u8(bytes, offset)?,
+            algorithm: read_u8(bytes, offset)?,
+            public_key: read_rest(bytes, offset),
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.flags.to_be_bytes());
+        buf.push(self.protocol);
+        buf.push(self.algorithm);
+        buf.extend_from_slice(&self.public_key);
+    }
+
+    /// The key tag that RRSIG and DS records use to refer to the key (RFC 4034 appendix B).
+    pub fn key_tag(&self) -> u16 {
+        if self.algorithm == 1 {
+            // RSA/MD5: the most significant 16 of the last 24 bits of the modulus.
+            let key = &self.public_key;
+            return match key.len() {
+                len if len >= 3 => u16::from_be_bytes([key[len - 3], key[len - 2]]),
+                _ => 0,
+            };
+        }
+        let mut wire = Vec::with_capacity(4 + self.public_key.len());
+        self.to_wire(&mut wire);
+        let mut sum: u32 = wire
+            .iter()
+            .enumerate()
+            .map(|(i, &byte)| {
+                if i & 1 == 0 {
+                    (byte as u32) << 8
+                } else {
+                    byte as u32
+                }
+            })
+            .sum();
+        sum += sum >> 16 & 0xFFFF;
+        sum as u16
+    }
+
+    /// Parses the presentation tokens `flags protocol algorithm public-key...`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [flags, protocol, algorithm, key @ ..] if !key.is_empty() => Some(Dnskey {
+                flags: flags.parse().ok()?,
+                protocol: protocol.parse().ok()?,
+                algorithm: algorithm.parse().ok()?,
+                public_key: base64_decode(&key.concat())?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Dnskey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.flags,
+            self.protocol,
+            self.algorithm,
+            base64_encode(&self.public_key)
+        )
+    }
+}
+
+/// A signature inception or expiration time: seconds since the Unix epoch, modulo 2^32
+/// (RFC 4034 §3.1.5).
+///
+/// It is displayed as `YYYYMMDDHHmmSS` in UTC.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Timestamp(pub u32);
+
+impl Timestamp {
+    /// Parses the `YYYYMMDDHHmmSS` form, or a number of seconds.
+    pub(crate) fn from_token(token: &str) -> Option<Self> {
+        if token.len() != 14 {
+            return token.parse().ok().map(Timestamp);
+        }
+        let field = |range: core::ops::Range<usize>| token.get(range)?.parse::<u32>().ok();
+        let (year, month, day) = (field(0..4)?, field(4..6)?, field(6..8)?);
+        let (hour, minute, second) = (field(8..10)?, field(10..12)?, field(12..14)?);
+        if !(1970..=2106).contains(&year)
+            || !(1..=12).contains(&month)
+            || !(1..=31).contains(&day)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return None;
+        }
+        let seconds =
+            days_from_civil(year, month, day) * 86400 + (hour * 3600 + minute * 60 + second) as i64;
+        u32::try_from(seconds).ok().map(Timestamp)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.0 as i64;
+        let (year, month, day) = civil_from_days(seconds / 86400);
+        let time = seconds % 86400;
+        write!(
+            f,
+            "{:04}{:02}{:02}{:02}{:02}{:02}",
+            year,
+            month,
+            day,
+            time / 3600,
+            time / 60 % 60,
+            time % 60
+        )
+    }
+}
+
+/// Days since 1970-01-01 of a date of the proleptic Gregorian calendar.
+fn days_from_civil(year: u32, month: u32, day: u32) -> i64 {
+    let year = year as i64 - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// The date of a number of days since 1970-01-01, the inverse of `days_from_civil`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// RRSIG record data (RFC 4034 §3).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rrsig {
+    pub type_covered: DnsType,
+    pub algorithm: u8,
+    pub labels: u8,
+    pub original_ttl: u32,
+    pub expiration: Timestamp,
+    pub inception: Timestamp,
+    pub key_tag: u16,
+    pub signer: String,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+    pub signature: Vec<u8>,
+}
+
+impl Rrsig {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let type_covered = DnsType(read_u16(bytes, offset)?);
+        let algorithm = read_u8(bytes, offset)?;
+        let labels = read_u8(bytes, offset)?;
+        let original_ttl = read_u32(bytes, offset)?;
+        let expiration = Timestamp(read_u32(bytes, offset)?);
+        let inception = Timestamp(read_u32(bytes, offset)?);
+        let key_tag = read_u16(bytes, offset)?;
+        let (signer, new_offset) = parse_name(bytes, *offset)?;
+        *offset = new_offset;
+        Ok(Rrsig {
+            type_covered,
+            algorithm,
+            labels,
+            original_ttl,
+            expiration,
+            inception,
+            key_tag,
+            signer,
+            signature: read_rest(bytes, offset),
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.type_covered.0.to_be_bytes());
+        buf.push(self.algorithm);
+        buf.push(self.labels);
+        buf.extend_from_slice(&self.original_ttl.to_be_bytes());
+        buf.extend_from_slice(&self.expiration.0.to_be_bytes());
+        buf.extend_from_slice(&self.inception.0.to_be_bytes());
+        buf.extend_from_slice(&self.key_tag.to_be_bytes());
+        write_name(&self.signer, buf);
+        buf.extend_from_slice(&self.signature);
+    }
+
+    /// Parses the presentation tokens
+    /// `type algorithm labels ttl expiration inception key-tag signer signature...`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [type_covered, algorithm, labels, ttl, expiration, inception, key_tag, signer, signature @ ..]
+                if !signature.is_empty() =>
+            {
+                Some(Rrsig {
+                    type_covered: type_covered.parse().ok()?,
+                    algorithm: algorithm.parse().ok()?,
+                    labels: labels.parse().ok()?,
+                    original_ttl: ttl.parse().ok()?,
+                    expiration: Timestamp::from_token(expiration)?,
+                    inception: Timestamp::from_token(inception)?,
+                    key_tag: key_tag.parse().ok()?,
+                    signer: signer.clone(),
+                    signature: base64_decode(&signature.concat())?,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Rrsig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {} {} {} {}",
+            self.type_covered,
+            self.algorithm,
+            self.labels,
+            self.original_ttl,
+            self.expiration,
+            self.inception,
+            self.key_tag,
+            self.signer,
+            base64_encode(&self.signature)
+        )
+    }
+}
+
+/// DS and CDS record data (RFC 4034 §5, RFC 7344).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Ds {
+    pub key_tag: u16,
+    pub algorithm: u8,
+    pub digest_type: u8,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+    pub digest: Vec<u8>,
+}
+
+impl Ds {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        Ok(Ds {
+            key_tag: read_u16(bytes, offset)?,
+            algorithm: read_u8(bytes, offset)?,
+            digest_type: read_u8(bytes, offset)?,
+            digest: read_rest(bytes, offset),
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.key_tag.to_be_bytes());
+        buf.push(self.algorithm);
+        buf.push(self.digest_type);
+        buf.extend_from_slice(&self.digest);
+    }
+
+    /// Parses the presentation tokens `key-tag algorithm digest-type digest...`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [key_tag, algorithm, digest_type, digest @ ..] if !digest.is_empty() => Some(Ds {
+                key_tag: key_tag.parse().ok()?,
+                algorithm: algorithm.parse().ok()?,
+                digest_type: digest_type.parse().ok()?,
+                digest: hex::decode(digest.concat()).ok()?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Ds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.key_tag,
+            self.algorithm,
+            self.digest_type,
+            hex::encode_upper(&self.digest)
+        )
+    }
+}
+
+/// NSEC record data (RFC 4034 §4): the next owner name of the zone and the types present
+/// at the owner.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Nsec {
+    pub next_domain: String,
+    pub types: BTreeSet<DnsType>,
+}
+
+impl Nsec {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let (next_domain, new_offset) = parse_name(bytes, *offset)?;
+        *offset = new_offset;
+        Ok(Nsec {
+            next_domain,
+            types: read_type_bitmap(bytes, offset)?,
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        write_name(&self.next_domain, buf);
+        write_type_bitmap(&self.types, buf);
+    }
+
+    /// Parses the presentation tokens `next-domain types...`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [next_domain, types @ ..] => Some(Nsec {
+                next_domain: next_domain.clone(),
+                types: parse_types(types)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Nsec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.next_domain)?;
+        write_types(f, &self.types)
+    }
+}
+
+/// NSEC3 record data (RFC 5155 §3): the next hashed owner name of the zone and the types
+/// present at the owner.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Nsec3 {
+    pub hash_algorithm: u8,
+    pub flags: u8,
+    pub iterations: u16,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+    pub salt: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+    pub next_hashed_owner: Vec<u8>,
+    pub types: BTreeSet<DnsType>,
+}
+
+impl Nsec3 {
+    /// Whether the Opt-Out flag is set: the span may cover unsigned delegations.
+    pub fn opt_out(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let hash_algorithm = read_u8(bytes, offset)?;
+        let flags = read_u8(bytes, offset)?;
+        let iterations = read_u16(bytes, offset)?;
+        let salt_length = read_u8(bytes, offset)?;
+        let salt = read_bytes(bytes, offset, salt_length as usize)?.to_vec();
+        let hash_length = read_u8(bytes, offset)?;
+        let next_hashed_owner = read_bytes(bytes, offset, hash_length as usize)?.to_vec();
+        Ok(Nsec3 {
+            hash_algorithm,
+            flags,
+            iterations,
+            salt,
+            next_hashed_owner,
+            types: read_type_bitmap(bytes, offset)?,
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.push(self.hash_algorithm);
+        buf.push(self.flags);
+        buf.extend_from_slice(&self.iterations.to_be_bytes());
+        buf.push(self.salt.len() as u8);
+        buf.extend_from_slice(&self.salt);
+        buf.push(self.next_hashed_owner.len() as u8);
+        buf.extend_from_slice(&self.next_hashed_owner);
+        write_type_bitmap(&self.types, buf);
+    }
+
+    /// Parses the presentation tokens
+    /// `hash-algorithm flags iterations salt next-hashed-owner types...`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [hash_algorithm, flags, iterations, salt, next_hashed_owner, types @ ..] => {
+                Some(Nsec3 {
+                    hash_algorithm: hash_algorithm.parse().ok()?,
+                    flags: flags.parse().ok()?,
+                    iterations: iterations.parse().ok()?,
+                    salt: parse_salt(salt)?,
+                    next_hashed_owner: base32hex_decode(next_hashed_owner)?,
+                    types: parse_types(types)?,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Nsec3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {}",
+            self.hash_algorithm,
+            self.flags,
+            self.iterations,
+            Salt(&self.salt),
+            base32hex_encode(&self.next_hashed_owner)
+        )?;
+        write_types(f, &self.types)
+    }
+}
+
+/// NSEC3PARAM record data (RFC 5155 §4): the hash parameters of the zone's NSEC3 chain.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Nsec3Param {
+    pub hash_algorithm: u8,
+    pub flags: u8,
+    pub iterations: u16,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+    pub salt: Vec<u8>,
+}
+
+impl Nsec3Param {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let hash_algorithm = read_u8(bytes, offset)?;
+        let flags = read_u8(bytes, offset)?;
+        let iterations = read_u16(bytes, offset)?;
+        let salt_length = read_u8(bytes, offset)?;
+        Ok(Nsec3Param {
+            hash_algorithm,
+            flags,
+            iterations,
+            salt: read_bytes(bytes, offset, salt_length as usize)?.to_vec(),
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.push(self.hash_algorithm);
+        buf.push(self.flags);
+        buf.extend_from_slice(&self.iterations.to_be_bytes());
+        buf.push(self.salt.len() as u8);
+        buf.extend_from_slice(&self.salt);
+    }
+
+    /// Parses the presentation tokens `hash-algorithm flags iterations salt`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [hash_algorithm, flags, iterations, salt] => Some(Nsec3Param {
+                hash_algorithm: hash_algorithm.parse().ok()?,
+                flags: flags.parse().ok()?,
+                iterations: iterations.parse().ok()?,
+                salt: parse_salt(salt)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Nsec3Param {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.hash_algorithm,
+            self.flags,
+            self.iterations,
+            Salt(&self.salt)
+        )
+    }
+}
+
+/// Reads the type bitmap that ends the RDATA of NSEC and NSEC3 records (RFC 4034 §4.1.2):
+/// blocks of up to 32 bytes, one per window of 256 types, in increasing window order.
+fn read_type_bitmap(
+    bytes: &[u8],
+    offset: &mut usize,
+) -> Result<BTreeSet<DnsType>, DnsRecordParseError> {
+    let mut types = BTreeSet::new();
+    let mut previous_window = None;
+    while *offset < bytes.len() {
+        let start = *offset;
+        let window = read_u8(bytes, offset)?;
+        let length = read_u8(bytes, offset)?;
+        if !(1..=32).contains(&length) || previous_window >= Some(window) {
+            return Err(DnsRecordParseError::InvalidTypeBitmap { offset: start });
+        }
+        previous_window = Some(window);
+        let bitmap = read_bytes(bytes, offset, length as usize)?;
+        for (index, &byte) in bitmap.iter().enumerate() {
+            for bit in 0..8 {
+                if byte & (0x80 >> bit) != 0 {
+                    let value = (window as u16) << 8 | (index * 8 + bit) as u16;
+                    types.insert(DnsType(value));
+                }
+            }
+        }
+    }
+    Ok(types)
+}
+
+fn write_type_bitmap(types: &BTreeSet<DnsType>, buf: &mut Vec<u8>) {
+    let mut types = types.iter().map(|dns_type| dns_type.0).peekable();
+    while let Some(&first) = types.peek() {
+        let window = (first >> 8) as u8;
+        let mut bitmap = [0u8; 32];
+        let mut length = 0;
+        while let Some(value) = types.next_if(|value| (value >> 8) as u8 == window) {
+            let low = (value & 0xFF) as usize;
+            bitmap[low / 8] |= 0x80 >> (low % 8);
+            length = low / 8 + 1;
+        }
+        buf.push(window);
+        buf.push(length as u8);
+        buf.extend_from_slice(&bitmap[..length]);
+    }
+}
+
+fn parse_types(tokens: &[String]) -> Option<BTreeSet<DnsType>> {
+    tokens.iter().map(|token| token.parse().ok()).collect()
+}
+
+fn write_types(f: &mut fmt::Formatter<'_>, types: &BTreeSet<DnsType>) -> fmt::Result {
+    for &dns_type in types {
+        write!(f, " {}", dns_type)?;
+    }
+    Ok(())
+}
+
+/// Parses a salt in hex, `-` standing for an empty salt.
+fn parse_salt(token: &str) -> Option<Vec<u8>> {
+    match token {
+        "-" => Some(Vec::new()),
+        _ => hex::decode(token).ok(),
+    }
+}
+
+/// Displays a salt in hex, or `-` when empty.
+struct Salt<'a>(&'a [u8]);
+
+impl fmt::Display for Salt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            [] => write!(f, "-"),
+            salt => write!(f, "{}", hex::encode_upper(salt)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::dns_types::DnsTypes;
+
+    #[test]
+    fn test_dnskey_key_tag() {
+        // The root zone KSK-2017 (key tag 20326).
+        let key = Dnskey::from_tokens(&tokenize(
+            "257 3 8 AwEAAaz/tAm8yTn4Mfeh5eyI96WSVexTBAvkMgJzkKTOiW1vkIbzxeF3+/4RgWOq7HrxRixHlFlExOLAJr5emLvN7SWXgnLh4+B5xQlNVz8Og8kvArMtNROxVQuCaSnIDdD5LKyWbRd2n9WGe2R8PzgCmr3EgVLrjyBxWezF0jLHwVN8efS3rCj/EWgvIWgb9tarpVUDK/b58Da+sqqls3eNbuv7pr+eoZG+SrDK6nWeL3c6H5Apxz7LjVc1uTIdsIXxuOLYA4/ilBmSVIzuDWfdRUfhHdY6+cn8HFRm+2hM8AnXGXws9555KrUB5qihylGa8subX2Nn6UwNR1AkUTV74bU=",
+        ))
+        .unwrap();
+        assert_eq!(key.flags, Dnskey::ZONE | Dnskey::SEP);
+        assert_eq!(key.key_tag(), 20326);
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let timestamp = Timestamp::from_token("20240101120000").unwrap();
+        assert_eq!(timestamp, Timestamp(1704110400));
+        assert_eq!(timestamp.to_string(), "20240101120000");
+        assert_eq!(Timestamp(0).to_string(), "19700101000000");
+        assert_eq!(Timestamp(u32::MAX).to_string(), "21060207062815");
+        assert_eq!(Timestamp::from_token("1704110400"), Some(timestamp));
+        assert_eq!(Timestamp::from_token("20241301000000"), None);
+    }
+
+    #[test]
+    fn test_type_bitmap() {
+        // RFC 4034 §4.3: A MX RRSIG NSEC TYPE1234.
+        let wire = hex::decode(
+            "0006400100000003041b000000000000000000000000000000000000000000000000000020",
+        )
+        .unwrap();
+        let mut offset = 0;
+        let types = read_type_bitmap(&wire, &mut offset).unwrap();
+        let expected = [
+            DnsTypes::A,
+            DnsTypes::MX,
+            DnsTypes::RRSIG,
+            DnsTypes::NSEC,
+            DnsType(1234),
+        ];
+        assert_eq!(types, BTreeSet::from(expected));
+
+        let mut buf = Vec::new();
+        write_type_bitmap(&types, &mut buf);
+        assert_eq!(buf, wire);
+
+        let mut offset = 0;
+        let result = read_type_bitmap(&[0x00, 0x00], &mut offset);
+        assert!(matches!(
+            result,
+            Err(DnsRecordParseError::InvalidTypeBitmap { offset: 0 })
+        ));
+    }
+
+    fn tokenize(s: &str) -> Vec<String> {
+        super::super::tokenize(s).unwrap()
+    }
+}
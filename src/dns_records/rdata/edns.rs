@@ -0,0 +1,66 @@
+use core::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::dns_records::{errors::DnsRecordParseError, read_bytes, read_u16};
+use crate::prelude::*;
+
+/// OPT pseudo-record data (RFC 6891): a list of EDNS options.
+///
+/// The requestor's UDP payload size, extended RCODE, version and flags live in the
+/// record's CLASS and TTL fields, not in the RDATA.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Opt {
+    pub options: Vec<EdnsOption>,
+}
+
+impl Opt {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let mut options = Vec::new();
+        while *offset < bytes.len() {
+            let code = read_u16(bytes, offset)?;
+            let len = read_u16(bytes, offset)?;
+            let data = read_bytes(bytes, offset, len as usize)?.to_vec();
+            options.push(EdnsOption { code, data });
+        }
+        Ok(Opt { options })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        for option in &self.options {
+            buf.extend_from_slice(&option.code.to_be_bytes());
+            buf.extend_from_slice(&(option.data.len() as u16).to_be_bytes());
+            buf.extend_from_slice(&option.data);
+        }
+    }
+}
+
+impl fmt::Display for Opt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, option) in self.options.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", option)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single EDNS option, e.g. a client subnet (code 8) or a cookie (code 10).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EdnsOption {
+    pub code: u16,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+    pub data: Vec<u8>,
+}
+
+impl fmt::Display for EdnsOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.code, hex::encode(&self.data))
+    }
+}
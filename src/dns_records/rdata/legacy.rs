@@ -0,0 +1,897 @@
+use alloc::collections::BTreeSet;
+use core::fmt;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::character_string::write_escaped;
+use super::{name_from_token, presentation_name, unescape, CharacterString};
+use crate::dns_records::{
+    errors::DnsRecordParseError, parse_name, read_array, read_bytes, read_u16, read_u32, read_u8,
+    write_name,
+};
+use crate::prelude::*;
+
+/// HINFO record data (RFC 1035 §3.3.2): the CPU and operating system of a host.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Hinfo {
+    pub cpu: CharacterString,
+    pub os: CharacterString,
+}
+
+impl Hinfo {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        Ok(Hinfo {
+            cpu: CharacterString::from_bytes(bytes, offset)?,
+            os: CharacterString::from_bytes(bytes, offset)?,
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        self.cpu.to_wire(buf);
+        self.os.to_wire(buf);
+    }
+
+    /// Parses the presentation tokens `cpu os`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [cpu, os] => Some(Hinfo {
+                cpu: cpu.parse().ok()?,
+                os: os.parse().ok()?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Hinfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\" \"{}\"", self.cpu, self.os)
+    }
+}
+
+/// MINFO record data (RFC 1035 §3.3.7): the mailboxes responsible for a mailing list and
+/// receiving its errors.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Minfo {
+    pub rmailbx: String,
+    pub emailbx: String,
+}
+
+impl Minfo {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let (rmailbx, new_offset) = parse_name(bytes, *offset)?;
+        let (emailbx, new_offset) = parse_name(bytes, new_offset)?;
+        *offset = new_offset;
+        Ok(Minfo { rmailbx, emailbx })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        write_name(&self.rmailbx, buf);
+        write_name(&self.emailbx, buf);
+    }
+
+    /// Parses the presentation tokens `rmailbx emailbx`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [rmailbx, emailbx] => Some(Minfo {
+                rmailbx: name_from_token(rmailbx),
+                emailbx: name_from_token(emailbx),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Minfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            presentation_name(&self.rmailbx),
+            presentation_name(&self.emailbx)
+        )
+    }
+}
+
+/// RP record data (RFC 1183 §2.2): the mailbox of the person responsible for a name, and
+/// a name holding TXT records about them. Either is the root when absent.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rp {
+    pub mbox: String,
+    pub txt: String,
+}
+
+impl Rp {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let (mbox, new_offset) = parse_name(bytes, *offset)?;
+        let (txt, new_offset) = parse_name(bytes, new_offset)?;
+        *offset = new_offset;
+        Ok(Rp { mbox, txt })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        write_name(&self.mbox, buf);
+        write_name(&self.txt, buf);
+    }
+
+    /// Parses the presentation tokens `mbox txt`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [mbox, txt] => Some(Rp {
+                mbox: name_from_token(mbox),
+                txt: name_from_token(txt),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Rp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            presentation_name(&self.mbox),
+            presentation_name(&self.txt)
+        )
+    }
+}
+
+/// AFSDB record data (RFC 1183 §1): an AFS cell database server (subtype 1) or a DCE
+/// authenticated name server (subtype 2).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Afsdb {
+    pub subtype: u16,
+    pub hostname: String,
+}
+
+impl Afsdb {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let subtype = read_u16(bytes, offset)?;
+        let (hostname, new_offset) = parse_name(bytes, *offset)?;
+        *offset = new_offset;
+        Ok(Afsdb { subtype, hostname })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.subtype.to_be_bytes());
+        write_name(&self.hostname, buf);
+    }
+
+    /// Parses the presentation tokens `subtype hostname`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [subtype, hostname] => Some(Afsdb {
+                subtype: subtype.parse().ok()?,
+                hostname: name_from_token(hostname),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Afsdb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.subtype, presentation_name(&self.hostname))
+    }
+}
+
+/// KX record data (RFC 2230 §3): a key exchanger for the owner name.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Kx {
+    pub preference: u16,
+    pub exchanger: String,
+}
+
+impl Kx {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let preference = read_u16(bytes, offset)?;
+        let (exchanger, new_offset) = parse_name(bytes, *offset)?;
+        *offset = new_offset;
+        Ok(Kx {
+            preference,
+            exchanger,
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.preference.to_be_bytes());
+        write_name(&self.exchanger, buf);
+    }
+
+    /// Parses the presentation tokens `preference exchanger`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [preference, exchanger] => Some(Kx {
+                preference: preference.parse().ok()?,
+                exchanger: name_from_token(exchanger),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Kx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            self.preference,
+            presentation_name(&self.exchanger)
+        )
+    }
+}
+
+/// NAPTR record data (RFC 3403 §4.1): a rewrite rule of the Dynamic Delegation Discovery
+/// System, e.g. from a SIP domain to the SRV records of its transports.
+///
+/// A rule either rewrites the input with `regexp` or replaces it with `replacement`; the
+/// unused one is empty (the root for `replacement`).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Naptr {
+    pub order: u16,
+    pub preference: u16,
+    /// E.g. `S` for an SRV lookup, `A` for an address lookup, `U` for a terminal URI.
+    pub flags: CharacterString,
+    /// E.g. `SIP+D2U` or `E2U+sip`.
+    pub services: CharacterString,
+    pub regexp: CharacterString,
+    pub replacement: String,
+}
+
+impl Naptr {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let order = read_u16(bytes, offset)?;
+        let preference = read_u16(bytes, offset)?;
+        let flags = CharacterString::from_bytes(bytes, offset)?;
+        let services = CharacterString::from_bytes(bytes, offset)?;
+        let regexp = CharacterString::from_bytes(bytes, offset)?;
+        let (replacement, new_offset) = parse_name(bytes, *offset)?;
+        *offset = new_offset;
+        Ok(Naptr {
+            order,
+            preference,
+            flags,
+            services,
+            regexp,
+            replacement,
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.order.to_be_bytes());
+        buf.extend_from_slice(&self.preference.to_be_bytes());
+        self.flags.to_wire(buf);
+        self.services.to_wire(buf);
+        self.regexp.to_wire(buf);
+        write_name(&self.replacement, buf);
+    }
+
+    /// Parses the presentation tokens `order preference flags services regexp replacement`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [order, preference, flags, services, regexp, replacement] => Some(Naptr {
+                order: order.parse().ok()?,
+                preference: preference.parse().ok()?,
+                flags: flags.parse().ok()?,
+                services: services.parse().ok()?,
+                regexp: regexp.parse().ok()?,
+                replacement: name_from_token(replacement),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Naptr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} \"{}\" \"{}\" \"{}\" {}",
+            self.order,
+            self.preference,
+            self.flags,
+            self.services,
+            self.regexp,
+            presentation_name(&self.replacement)
+        )
+    }
+}
+
+/// URI record data (RFC 7553 §4.5).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Uri {
+    pub priority: u16,
+    pub weight: u16,
+    pub target: String,
+}
+
+impl Uri {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let priority = read_u16(bytes, offset)?;
+        let weight = read_u16(bytes, offset)?;
+        let target = read_bytes(bytes, offset, bytes.len() - *offset)?;
+        Ok(Uri {
+            priority,
+            weight,
+            target: core::str::from_utf8(target)?.to_string(),
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.priority.to_be_bytes());
+        buf.extend_from_slice(&self.weight.to_be_bytes());
+        buf.extend_from_slice(self.target.as_bytes());
+    }
+
+    /// Parses the presentation tokens `priority weight target`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        match tokens {
+            [priority, weight, target] => Some(Uri {
+                priority: priority.parse().ok()?,
+                weight: weight.parse().ok()?,
+                target: String::from_utf8(unescape(target)?).ok()?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Uri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} \"", self.priority, self.weight)?;
+        write_escaped(f, self.target.as_bytes())?;
+        write!(f, "\"")
+    }
+}
+
+/// LOC record data (RFC 1876 §2): the location and size of a host, kept in its wire
+/// encoding.
+///
+/// The `*_degrees` and `*_meters` methods convert the fields.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Loc {
+    pub version: u8,
+    /// The diameter of a sphere enclosing the host, as a mantissa (high nibble) and a
+    /// power of ten (low nibble) of centimeters; the precisions use the same encoding.
+    pub size: u8,
+    pub horizontal_precision: u8,
+    pub vertical_precision: u8,
+    /// Thousandths of an arc second, 2^31 being the equator.
+    pub latitude: u32,
+    /// Thousandths of an arc second, 2^31 being the prime meridian.
+    pub longitude: u32,
+    /// Centimeters above a base 100000 meters below the WGS 84 reference spheroid.
+    pub altitude: u32,
+}
+
+/// The wire value of the equator and the prime meridian.
+const LOC_EQUATOR: i64 = 1 << 31;
+/// The wire value of the altitude 0, in centimeters.
+const LOC_ALTITUDE_BASE: i64 = 10_000_000;
+
+impl Loc {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let version = read_u8(bytes, offset)?;
+        if version != 0 {
+            // The layout of other versions is unknown (RFC 1876 §2).
+            return Err(DnsRecordParseError::UnsupportedLocVersion {
+                version,
+                offset: *offset - 1,
+            });
+        }
+        Ok(Loc {
+            version,
+            size: read_u8(bytes, offset)?,
+            horizontal_precision: read_u8(bytes, offset)?,
+            vertical_precision: read_u8(bytes, offset)?,
+            latitude: read_u32(bytes, offset)?,
+            longitude: read_u32(bytes, offset)?,
+            altitude: read_u32(bytes, offset)?,
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&[
+            self.version,
+            self.size,
+            self.horizontal_precision,
+            self.vertical_precision,
+        ]);
+        buf.extend_from_slice(&self.latitude.to_be_bytes());
+        buf.extend_from_slice(&self.longitude.to_be_bytes());
+        buf.extend_from_slice(&self.altitude.to_be_bytes());
+    }
+
+    /// The latitude in degrees, positive to the north.
+    pub fn latitude_degrees(&self) -> f64 {
+        (self.latitude as i64 - LOC_EQUATOR) as f64 / 3_600_000.0
+    }
+
+    /// The longitude in degrees, positive to the east.
+    pub fn longitude_degrees(&self) -> f64 {
+        (self.longitude as i64 - LOC_EQUATOR) as f64 / 3_600_000.0
+    }
+
+    /// The altitude in meters above the WGS 84 reference spheroid.
+    pub fn altitude_meters(&self) -> f64 {
+        (self.altitude as i64 - LOC_ALTITUDE_BASE) as f64 / 100.0
+    }
+
+    pub fn size_meters(&self) -> f64 {
+        precision_centimeters(self.size) as f64 / 100.0
+    }
+
+    pub fn horizontal_precision_meters(&self) -> f64 {
+        precision_centimeters(self.horizontal_precision) as f64 / 100.0
+    }
+
+    pub fn vertical_precision_meters(&self) -> f64 {
+        precision_centimeters(self.vertical_precision) as f64 / 100.0
+    }
+
+    /// Parses the presentation tokens
+    /// `d1 [m1 [s1]] {N|S} d2 [m2 [s2]] {E|W} alt[m] [size[m] [hp[m] [vp[m]]]]`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        let mut tokens = tokens.iter();
+        let latitude = parse_coordinate(&mut tokens, ["N", "S"], 90)?;
+        let longitude = parse_coordinate(&mut tokens, ["E", "W"], 180)?;
+        let altitude = tokens.next()?.trim_end_matches('m');
+        let altitude = match altitude.strip_prefix('-') {
+            Some(depth) => LOC_ALTITUDE_BASE.checked_sub_unsigned(parse_decimal(depth, 2)?),
+            None => LOC_ALTITUDE_BASE.checked_add_unsigned(parse_decimal(altitude, 2)?),
+        }?;
+        // The defaults are 1m, 10000m and 10m (RFC 1876 appendix A).
+        let mut precisions = [0x12, 0x16, 0x13];
+        for precision in precisions.iter_mut() {
+            if let Some(token) = tokens.next() {
+                *precision = encode_precision(parse_decimal(token.trim_end_matches('m'), 2)?)?;
+            }
+        }
+        if tokens.next().is_some() {
+            return None;
+        }
+        let [size, horizontal_precision, vertical_precision] = precisions;
+        Some(Loc {
+            version: 0,
+            size,
+            horizontal_precision,
+            vertical_precision,
+            latitude,
+            longitude,
+            altitude: u32::try_from(altitude).ok()?,
+        })
+    }
+}
+
+impl fmt::Display for Loc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_coordinate(f, self.latitude, ['N', 'S'])?;
+        write!(f, " ")?;
+        write_coordinate(f, self.longitude, ['E', 'W'])?;
+        let altitude = self.altitude as i64 - LOC_ALTITUDE_BASE;
+        let sign = if altitude < 0 { "-" } else { "" };
+        let altitude = altitude.unsigned_abs();
+        write!(f, " {}{}.{:02}m", sign, altitude / 100, altitude % 100)?;
+        for precision in [
+            self.size,
+            self.horizontal_precision,
+            self.vertical_precision,
+        ] {
+            let centimeters = precision_centimeters(precision);
+            match centimeters % 100 {
+                0 => write!(f, " {}m", centimeters / 100)?,
+                rest => write!(f, " {}.{:02}m", centimeters / 100, rest)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn precision_centimeters(precision: u8) -> u64 {
+    (precision >> 4) as u64 * 10u64.pow((precision & 0x0F) as u32)
+}
+
+/// Encodes centimeters as a mantissa and an exponent, rounding down.
+fn encode_precision(mut centimeters: u64) -> Option<u8> {
+    let mut exponent = 0;
+    while centimeters > 9 {
+        centimeters /= 10;
+        exponent += 1;
+    }
+    (exponent <= 9).then_some((centimeters as u8) << 4 | exponent)
+}
+
+/// Parses a decimal number with up to `digits` decimals into an integer of the smallest unit.
+fn parse_decimal(token: &str, digits: usize) -> Option<u64> {
+    let (integer, fraction) = token.split_once('.').unwrap_or((token, ""));
+    if integer.is_empty()
+        || fraction.len() > digits
+        || !(integer.bytes().chain(fraction.bytes())).all(|byte| byte.is_ascii_digit())
+    {
+        return None;
+    }
+    let mut value: u64 = integer.parse().ok()?;
+    for i in 0..digits {
+        let digit = fraction.as_bytes().get(i).map_or(0, |byte| byte - b'0');
+        value = value.checked_mul(10)?.checked_add(digit as u64)?;
+    }
+    Some(value)
+}
+
+/// Parses `degrees [minutes [seconds]] hemisphere`, the first hemisphere being positive.
+fn parse_coordinate(
+    tokens: &mut core::slice::Iter<'_, String>,
+    hemispheres: [&str; 2],
+    max_degrees: u64,
+) -> Option<u32> {
+    // Degrees, minutes and thousandths of seconds.
+    let mut fields = [0u64; 3];
+    for i in 0..=fields.len() {
+        let token = tokens.next()?;
+        if let Some(hemisphere) = hemispheres
+            .iter()
+            .position(|name| token.eq_ignore_ascii_case(name))
+        {
+            let [degrees, minutes, milliseconds] = fields;
+            if i == 0 || degrees > max_degrees || minutes > 59 || milliseconds > 59_999 {
+                return None;
+            }
+            let value = degrees * 3_600_000 + minutes * 60_000 + milliseconds;
+            if value > max_degrees * 3_600_000 {
+                return None;
+            }
+            let value = match hemisphere {
+                0 => LOC_EQUATOR + value as i64,
+                _ => LOC_EQUATOR - value as i64,
+            };
+            return u32::try_from(value).ok();
+        }
+        *fields.get_mut(i)? = match i {
+            2 => parse_decimal(token, 3)?,
+            _ => token.parse().ok()?,
+        };
+    }
+    None
+}
+
+fn write_coordinate(f: &mut fmt::Formatter<'_>, value: u32, hemispheres: [char; 2]) -> fmt::Result {
+    let value = value as i64 - LOC_EQUATOR;
+    let hemisphere = if value < 0 {
+        hemispheres[1]
+    } else {
+        hemispheres[0]
+    };
+    let value = value.unsigned_abs();
+    write!(
+        f,
+        "{} {} {}.{:03} {}",
+        value / 3_600_000,
+        value / 60_000 % 60,
+        value / 1000 % 60,
+        value % 1000,
+        hemisphere
+    )
+}
+
+/// APL record data (RFC 3123 §4): a list of address prefixes, each possibly negated.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Apl {
+    pub items: Vec<AplItem>,
+}
+
+/// An address prefix of an APL record, IPv4 (family 1) or IPv6 (family 2).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AplItem {
+    pub negation: bool,
+    pub address: IpAddr,
+    pub prefix: u8,
+}
+
+impl Apl {
+    /// Parses prefixes until the end of `bytes`, which must stop at the end of the RDATA.
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let mut items = Vec::new();
+        while *offset < bytes.len() {
+            let start = *offset;
+            let family = read_u16(bytes, offset)?;
+            let prefix = read_u8(bytes, offset)?;
+            let length = read_u8(bytes, offset)?;
+            let part = read_bytes(bytes, offset, (length & 0x7F) as usize)?;
+            let invalid = DnsRecordParseError::InvalidAplItem { offset: start };
+            let address = match family {
+                1 if part.len() <= 4 && prefix <= 32 => {
+                    let mut octets = [0; 4];
+                    octets[..part.len()].copy_from_slice(part);
+                    IpAddr::V4(Ipv4Addr::from(octets))
+                }
+                2 if part.len() <= 16 && prefix <= 128 => {
+                    let mut octets = [0; 16];
+                    octets[..part.len()].copy_from_slice(part);
+                    IpAddr::V6(Ipv6Addr::from(octets))
+                }
+                _ => return Err(invalid),
+            };
+            items.push(AplItem {
+                negation: length & 0x80 != 0,
+                address,
+                prefix,
+            });
+        }
+        Ok(Apl { items })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        for item in &self.items {
+            let (family, octets) = match item.address {
+                IpAddr::V4(address) => (1u16, address.octets().to_vec()),
+                IpAddr::V6(address) => (2u16, address.octets().to_vec()),
+            };
+            // Trailing zero octets are left out (RFC 3123 §4).
+            let length = octets
+                .iter()
+                .rposition(|&byte| byte != 0)
+                .map_or(0, |i| i + 1);
+            buf.extend_from_slice(&family.to_be_bytes());
+            buf.push(item.prefix);
+            buf.push(length as u8 | if item.negation { 0x80 } else { 0 });
+            buf.extend_from_slice(&octets[..length]);
+        }
+    }
+
+    /// Parses one `[!]family:address/prefix` item per presentation token.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        let items = tokens
+            .iter()
+            .map(|token| {
+                let (negation, item) = match token.strip_prefix('!') {
+                    Some(item) => (true, item),
+                    None => (false, token.as_str()),
+                };
+                let (family, item) = item.split_once(':')?;
+                let (address, prefix) = item.split_once('/')?;
+                let prefix: u8 = prefix.parse().ok()?;
+                let address = match family {
+                    "1" if prefix <= 32 => IpAddr::V4(address.parse().ok()?),
+                    "2" if prefix <= 128 => IpAddr::V6(address.parse().ok()?),
+                    _ => return None,
+                };
+                Some(AplItem {
+                    negation,
+                    address,
+                    prefix,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Apl { items })
+    }
+}
+
+impl fmt::Display for Apl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", item)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for AplItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negation {
+            write!(f, "!")?;
+        }
+        match self.address {
+            IpAddr::V4(address) => write!(f, "1:{}/{}", address, self.prefix),
+            IpAddr::V6(address) => write!(f, "2:{}/{}", address, self.prefix),
+        }
+    }
+}
+
+/// WKS record data (RFC 1035 §3.4.2): the ports on which a host offers services over
+/// an IP protocol.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Wks {
+    pub address: Ipv4Addr,
+    /// The IP protocol number, e.g. 6 for TCP and 17 for UDP.
+    pub protocol: u8,
+    pub ports: BTreeSet<u16>,
+}
+
+impl Wks {
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let address = Ipv4Addr::from(read_array::<4>(bytes, offset)?);
+        let protocol = read_u8(bytes, offset)?;
+        // The bitmap has one bit per port, from the most significant bit of the first byte.
+        let bitmap = read_bytes(bytes, offset, bytes.len() - *offset)?;
+        let ports = bitmap
+            .iter()
+            .take(8192)
+            .enumerate()
+            .flat_map(|(i, &byte)| {
+                (0..8)
+                    .filter(move |bit| byte & 0x80 >> bit != 0)
+                    .map(move |bit| (i * 8 + bit) as u16)
+            })
+            .collect();
+        Ok(Wks {
+            address,
+            protocol,
+            ports,
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.address.octets());
+        buf.push(self.protocol);
+        let Some(&last) = self.ports.last() else {
+            return;
+        };
+        let mut bitmap = vec![0u8; last as usize / 8 + 1];
+        for &port in &self.ports {
+            bitmap[port as usize / 8] |= 0x80 >> (port % 8);
+        }
+        buf.extend_from_slice(&bitmap);
+    }
+
+    /// Parses the presentation tokens `address protocol ports...`, where the protocol is
+    /// a number, `tcp` or `udp`.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        let [address, protocol, ports @ ..] = tokens else {
+            return None;
+        };
+        let protocol = match protocol.to_ascii_lowercase().as_str() {
+            "tcp" => 6,
+            "udp" => 17,
+            protocol => protocol.parse().ok()?,
+        };
+        Some(Wks {
+            address: address.parse().ok()?,
+            protocol,
+            ports: ports
+                .iter()
+                .map(|port| port.parse().ok())
+                .collect::<Option<_>>()?,
+        })
+    }
+}
+
+impl fmt::Display for Wks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.address, self.protocol)?;
+        for port in &self.ports {
+            write!(f, " {}", port)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rdata::RData;
+    use crate::utils::dns_types::DnsTypes;
+
+    #[test]
+    fn test_naptr() {
+        // RFC 3403 §6.2: the SIP example.
+        let naptr = Naptr::from_tokens(&tokenize(
+            "100 10 \"S\" \"SIP+D2U\" \"\" _sip._udp.example.com.",
+        ))
+        .unwrap();
+        assert_eq!(naptr.services, CharacterString(b"SIP+D2U".to_vec()));
+        let mut wire = Vec::new();
+        naptr.to_wire(&mut wire);
+        assert_eq!(
+            hex::encode(&wire),
+            "0064000a0153075349502b44325500045f736970045f756470076578616d706c6503636f6d00"
+        );
+        let mut offset = 0;
+        let decoded = Naptr::from_bytes(&wire, &mut offset).unwrap();
+        assert_eq!(
+            decoded.to_string(),
+            "100 10 \"S\" \"SIP+D2U\" \"\" _sip._udp.example.com"
+        );
+
+        let naptr = Naptr::from_tokens(&tokenize(
+            "100 10 \"u\" \"E2U+sip\" \"!^.*$!sip:info@example.com!\" .",
+        ))
+        .unwrap();
+        assert_eq!(naptr.replacement, "");
+        assert_eq!(
+            naptr.to_string(),
+            "100 10 \"u\" \"E2U+sip\" \"!^.*$!sip:info@example.com!\" ."
+        );
+    }
+
+    #[test]
+    fn test_loc() {
+        // RFC 1876 §4: cambridge-net.kei.com.
+        let loc = Loc::from_tokens(&tokenize("42 21 54 N 71 06 18 W -24m 30m")).unwrap();
+        let mut wire = Vec::new();
+        loc.to_wire(&mut wire);
+        assert_eq!(hex::encode(&wire), "0033161389172dd070be15f000988d20");
+        assert_eq!(
+            loc.to_string(),
+            "42 21 54.000 N 71 6 18.000 W -24.00m 30m 10000m 10m"
+        );
+        assert!((loc.latitude_degrees() - 42.365).abs() < 1e-9);
+        assert!((loc.longitude_degrees() + 71.105).abs() < 1e-9);
+        assert_eq!(loc.altitude_meters(), -24.0);
+        assert_eq!(loc.size_meters(), 30.0);
+        assert_eq!(loc.horizontal_precision_meters(), 10000.0);
+        assert_eq!(loc.vertical_precision_meters(), 10.0);
+
+        assert_eq!(Loc::from_tokens(&tokenize("91 N 0 E 0m")), None);
+        assert_eq!(Loc::from_tokens(&tokenize("1 60 N 0 E 0m")), None);
+        assert_eq!(Loc::from_tokens(&tokenize("90 0 0.001 N 0 E 0m")), None);
+        assert_eq!(Loc::from_tokens(&tokenize("99999999999999 N 0 E 0m")), None);
+        assert_eq!(
+            Loc::from_tokens(&tokenize("0 N 0 E 92233720368547758m")),
+            None
+        );
+        assert!(RData::from_presentation(DnsTypes::LOC, "99999999999999 N 0 E 0m").is_err());
+        let mut offset = 0;
+        let result = Loc::from_bytes(&[1; 16], &mut offset);
+        assert!(matches!(
+            result,
+            Err(DnsRecordParseError::UnsupportedLocVersion {
+                version: 1,
+                offset: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn test_apl() {
+        // RFC 3123 §5: 1:192.168.32.0/21 !1:192.168.38.0/28.
+        let wire = hex::decode("00011503c0a82000011c83c0a826").unwrap();
+        let mut offset = 0;
+        let apl = Apl::from_bytes(&wire, &mut offset).unwrap();
+        assert_eq!(apl.to_string(), "1:192.168.32.0/21 !1:192.168.38.0/28");
+        let mut buf = Vec::new();
+        apl.to_wire(&mut buf);
+        assert_eq!(buf, wire);
+
+        let mut offset = 0;
+        let result = Apl::from_bytes(&hex::decode("00031503c0a820").unwrap(), &mut offset);
+        assert!(matches!(
+            result,
+            Err(DnsRecordParseError::InvalidAplItem { offset: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_wks_bitmap() {
+        // SMTP (25) and HTTP (80) over TCP.
+        let wks = Wks::from_tokens(&tokenize("192.0.2.1 tcp 80 25")).unwrap();
+        let mut wire = Vec::new();
+        wks.to_wire(&mut wire);
+        assert_eq!(hex::encode(&wire), "c0000201060000004000000000000080");
+        let mut offset = 0;
+        let decoded = Wks::from_bytes(&wire, &mut offset).unwrap();
+        assert_eq!(decoded, wks);
+        assert_eq!(decoded.to_string(), "192.0.2.1 6 25 80");
+    }
+
+    fn tokenize(s: &str) -> Vec<String> {
+        super::super::tokenize(s).unwrap()
+    }
+}
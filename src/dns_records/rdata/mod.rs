@@ -0,0 +1,605 @@
+use core::fmt;
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::dns_records::{errors::DnsRecordParseError, parse_name, read_array, write_name};
+use crate::prelude::*;
+use crate::utils::dns_types::{DnsType, DnsTypes};
+
+mod certificates;
+mod character_string;
+mod common;
+mod dnssec;
+mod edns;
+mod legacy;
+mod svcb;
+mod tsig;
+
+pub use certificates::{Caa, Cert, CertTypes, Openpgpkey, Sshfp, Tlsa};
+pub use character_string::{CharacterString, CharacterStringParseError};
+pub use common::{Mx, Soa, Srv, Txt};
+pub use dnssec::{Dnskey, Ds, Nsec, Nsec3, Nsec3Param, Rrsig, Timestamp};
+pub use edns::{EdnsOption, Opt};
+pub use legacy::{Afsdb, Apl, AplItem, Hinfo, Kx, Loc, Minfo, Naptr, Rp, Uri, Wks};
+pub use svcb::{SvcParam, SvcParamKeys, Svcb};
+pub use tsig::{Tkey, TkeyModes, Tsig, TsigAlgorithms};
+
+/// The decoded RDATA of a resource record.
+///
+/// Types without a dedicated decoder are kept as raw bytes in `Unknown`, and so are the
+/// malformed records of the types decoded since the DNSSEC ones.
+///
+/// With the `serde` feature, the data is serialized as an object with a single member
+/// named after the variant, e.g. `{"A": "192.0.2.1"}` or
+/// `{"MX": {"preference": 10, "exchange": "mail.example.com"}}`. Raw bytes are
+/// serialized as lowercase hex strings.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RData {
+    A(Ipv4Addr),
+    NS(String),
+    CNAME(String),
+    SOA(Soa),
+    PTR(String),
+    MX(Mx),
+    TXT(Txt),
+    AAAA(Ipv6Addr),
+    SRV(Srv),
+    DNAME(String),
+    OPT(Opt),
+    DS(Ds),
+    RRSIG(Rrsig),
+    NSEC(Nsec),
+    DNSKEY(Dnskey),
+    NSEC3(Nsec3),
+    NSEC3PARAM(Nsec3Param),
+    CDS(Ds),
+    CDNSKEY(Dnskey),
+    SVCB(Svcb),
+    HTTPS(Svcb),
+    CAA(Caa),
+    TLSA(Tlsa),
+    SMIMEA(Tlsa),
+    SSHFP(Sshfp),
+    CERT(Cert),
+    OPENPGPKEY(Openpgpkey),
+    WKS(Wks),
+    HINFO(Hinfo),
+    MINFO(Minfo),
+    RP(Rp),
+    AFSDB(Afsdb),
+    LOC(Loc),
+    NAPTR(Naptr),
+    KX(Kx),
+    APL(Apl),
+    URI(Uri),
+    SIG(Rrsig),
+    KEY(Dnskey),
+    TKEY(Tkey),
+    TSIG(Tsig),
+    Unknown(#[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))] Vec<u8>),
+}
+
+impl RData {
+    /// Decodes the RDATA of a record of type `rtype` starting at `offset`.
+    ///
+    /// `bytes` must hold the DNS message up to the end of the RDATA: names may point
+    /// backwards into the message, and the decoder must consume exactly the rest of the slice.
+    /// Only the malformed records of the basic types (A, NS, CNAME, SOA, PTR, MX, TXT, AAAA,
+    /// SRV, DNAME and OPT) are errors; those of the other types are kept in `Unknown`.
+    pub fn from_bytes(
+        bytes: &[u8],
+        offset: usize,
+        rtype: DnsType,
+    ) -> Result<Self, DnsRecordParseError> {
+        let mut position = offset;
+        let rdata = match rtype {
+            DnsTypes::A => RData::A(Ipv4Addr::from(read_array::<4>(bytes, &mut position)?)),
+            DnsTypes::NS => RData::NS(parse_name_at(bytes, &mut position)?),
+            DnsTypes::CNAME => RData::CNAME(parse_name_at(bytes, &mut position)?),
+            DnsTypes::SOA => RData::SOA(Soa::from_bytes(bytes, &mut position)?),
+            DnsTypes::PTR => RData::PTR(parse_name_at(bytes, &mut position)?),
+            DnsTypes::MX => RData::MX(Mx::from_bytes(bytes, &mut position)?),
+            DnsTypes::TXT => RData::TXT(Txt::from_bytes(bytes, &mut position)?),
+            DnsTypes::AAAA => RData::AAAA(Ipv6Addr::from(read_array::<16>(bytes, &mut position)?)),
+            DnsTypes::SRV => RData::SRV(Srv::from_bytes(bytes, &mut position)?),
+            DnsTypes::DNAME => RData::DNAME(parse_name_at(bytes, &mut position)?),
+            DnsTypes::OPT => RData::OPT(Opt::from_bytes(bytes, &mut position)?),
+            _ => {
+                // The other types were kept as raw bytes before they had a decoder: a
+                // malformed record of one of them stays raw instead of failing the message.
+                let mut typed_position = position;
+                match Self::decode_typed(bytes, &mut typed_position, rtype) {
+                    Ok(Some(rdata)) if typed_position == bytes.len() => {
+                        position = typed_position;
+                        rdata
+                    }
+                    _ => {
+                        position = bytes.len();
+                        RData::Unknown(bytes[offset..].to_vec())
+                    }
+                }
+            }
+        };
+
+        if position != bytes.len() {
+            return Err(DnsRecordParseError::RdataLengthMismatch {
+                rtype,
+                declared: bytes.len() - offset,
+                decoded: position - offset,
+            });
+        }
+        Ok(rdata)
+    }
+
+    /// Decodes the data of the types that have a decoder but whose malformed records are
+    /// kept raw, or returns `None` for the other types.
+    fn decode_typed(
+        bytes: &[u8],
+        position: &mut usize,
+        rtype: DnsType,
+    ) -> Result<Option<Self>, DnsRecordParseError> {
+        Ok(Some(match rtype {
+            DnsTypes::DS => RData::DS(Ds::from_bytes(bytes, position)?),
+            DnsTypes::RRSIG => RData::RRSIG(Rrsig::from_bytes(bytes, position)?),
+            DnsTypes::NSEC => RData::NSEC(Nsec::from_bytes(bytes, position)?),
+            DnsTypes::DNSKEY => RData::DNSKEY(Dnskey::from_bytes(bytes, position)?),
+            DnsTypes::NSEC3 => RData::NSEC3(Nsec3::from_bytes(bytes, position)?),
+            DnsTypes::NSEC3PARAM => RData::NSEC3PARAM(Nsec3Param::from_bytes(bytes, position)?),
+            DnsTypes::CDS => RData::CDS(Ds::from_bytes(bytes, position)?),
+            DnsTypes::CDNSKEY => RData::CDNSKEY(Dnskey::from_bytes(bytes, position)?),
+            DnsTypes::SVCB => RData::SVCB(Svcb::from_bytes(bytes, position)?),
+            DnsTypes::HTTPS => RData::HTTPS(Svcb::from_bytes(bytes, position)?),
+            DnsTypes::CAA => RData::CAA(Caa::from_bytes(bytes, position)?),
+            DnsTypes::TLSA => RData::TLSA(Tlsa::from_bytes(bytes, position)?),
+            DnsTypes::SMIMEA => RData::SMIMEA(Tlsa::from_bytes(bytes, position)?),
+            DnsTypes::SSHFP => RData::SSHFP(Sshfp::from_bytes(bytes, position)?),
+            DnsTypes::CERT => RData::CERT(Cert::from_bytes(bytes, position)?),
+            DnsTypes::OPENPGPKEY => RData::OPENPGPKEY(Openpgpkey::from_bytes(bytes, position)?),
+            DnsTypes::WKS => RData::WKS(Wks::from_bytes(bytes, position)?),
+            DnsTypes::HINFO => RData::HINFO(Hinfo::from_bytes(bytes, position)?),
+            DnsTypes::MINFO => RData::MINFO(Minfo::from_bytes(bytes, position)?),
+            DnsTypes::RP => RData::RP(Rp::from_bytes(bytes, position)?),
+            DnsTypes::AFSDB => RData::AFSDB(Afsdb::from_bytes(bytes, position)?),
+            DnsTypes::LOC => RData::LOC(Loc::from_bytes(bytes, position)?),
+            DnsTypes::NAPTR => RData::NAPTR(Naptr::from_bytes(bytes, position)?),
+            DnsTypes::KX => RData::KX(Kx::from_bytes(bytes, position)?),
+            DnsTypes::APL => RData::APL(Apl::from_bytes(bytes, position)?),
+            DnsTypes::URI => RData::URI(Uri::from_bytes(bytes, position)?),
+            DnsTypes::SIG => RData::SIG(Rrsig::from_bytes(bytes, position)?),
+            DnsTypes::KEY => RData::KEY(Dnskey::from_bytes(bytes, position)?),
+            DnsTypes::TKEY => RData::TKEY(Tkey::from_bytes(bytes, position)?),
+            DnsTypes::TSIG => RData::TSIG(Tsig::from_bytes(bytes, position)?),
+            _ => return Ok(None),
+        }))
+    }
+
+    /// Appends the data in uncompressed wire format to `buf`.
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        match self {
+            RData::A(address) => buf.extend_from_slice(&address.octets()),
+            RData::NS(name) | RData::CNAME(name) | RData::PTR(name) | RData::DNAME(name) => {
+                write_name(name, buf)
+            }
+            RData::SOA(soa) => soa.to_wire(buf),
+            RData::MX(mx) => mx.to_wire(buf),
+            RData::TXT(txt) => txt.to_wire(buf),
+            RData::AAAA(address) => buf.extend_from_slice(&address.octets()),
+            RData::SRV(srv) => srv.to_wire(buf),
+            RData::OPT(opt) => opt.to_wire(buf),
+            RData::DS(ds) | RData::CDS(ds) => ds.to_wire(buf),
+            RData::RRSIG(rrsig) | RData::SIG(rrsig) => rrsig.to_wire(buf),
+            RData::NSEC(nsec) => nsec.to_wire(buf),
+            RData::DNSKEY(dnskey) | RData::CDNSKEY(dnskey) | RData::KEY(dnskey) => {
+                dnskey.to_wire(buf)
+            }
+            RData::NSEC3(nsec3) => nsec3.to_wire(buf),
+            RData::NSEC3PARAM(nsec3param) => nsec3param.to_wire(buf),
+            RData::SVCB(svcb) | RData::HTTPS(svcb) => svcb.to_wire(buf),
+            RData::CAA(caa) => caa.to_wire(buf),
+            RData::TLSA(tlsa) | RData::SMIMEA(tlsa) => tlsa.to_wire(buf),
+            RData::SSHFP(sshfp) => sshfp.to_wire(buf),
+            RData::CERT(cert) => cert.to_wire(buf),
+            RData::OPENPGPKEY(openpgpkey) => openpgpkey.to_wire(buf),
+            RData::WKS(wks) => wks.to_wire(buf),
+            RData::HINFO(hinfo) => hinfo.to_wire(buf),
+            RData::MINFO(minfo) => minfo.to_wire(buf),
+            RData::RP(rp) => rp.to_wire(buf),
+            RData::AFSDB(afsdb) => afsdb.to_wire(buf),
+            RData::LOC(loc) => loc.to_wire(buf),
+            RData::NAPTR(naptr) => naptr.to_wire(buf),
+            RData::KX(kx) => kx.to_wire(buf),
+            RData::APL(apl) => apl.to_wire(buf),
+            RData::URI(uri) => uri.to_wire(buf),
+            RData::TKEY(tkey) => tkey.to_wire(buf),
+            RData::TSIG(tsig) => tsig.to_wire(buf),
+            RData::Unknown(data) => buf.extend_from_slice(data),
+        }
+    }
+
+    /// Parses data of type `rtype` from its presentation format, the inverse of `Display`.
+    ///
+    /// Only the types with a textual representation in zone files are supported, but any
+    /// type may be given in the generic form `\# <length> <hex>` of RFC 3597 §5.
+    pub fn from_presentation(rtype: DnsType, s: &str) -> Result<Self, DnsRecordParseError> {
+        let invalid = || DnsRecordParseError::InvalidPresentation {
+            rtype,
+            value: s.to_string(),
+        };
+        let tokens = tokenize(s).ok_or_else(invalid)?;
+        let single = || match tokens.as_slice() {
+            [token] => Some(token.clone()),
+            _ => None,
+        };
+        if tokens.first().is_some_and(|token| token == "\\#") {
+            let data = match &tokens[1..] {
+                [length, hex @ ..] => hex::decode(hex.concat())
+                    .ok()
+                    .filter(|data| length.parse() == Ok(data.len())),
+                _ => None,
+            }
+            .ok_or_else(invalid)?;
+            return RData::from_bytes(&data, 0, rtype);
+        }
+        let rdata = match rtype {
+            DnsTypes::A => single().and_then(|t| t.parse().ok()).map(RData::A),
+            DnsTypes::NS => single().map(RData::NS),
+            DnsTypes::CNAME => single().map(RData::CNAME),
+            DnsTypes::SOA => Soa::from_tokens(&tokens).map(RData::SOA),
+            DnsTypes::PTR => single().map(RData::PTR),
+            DnsTypes::MX => Mx::from_tokens(&tokens).map(RData::MX),
+            DnsTypes::TXT => Txt::from_tokens(&tokens).map(RData::TXT),
+            DnsTypes::AAAA => single().and_then(|t| t.parse().ok()).map(RData::AAAA),
+            DnsTypes::SRV => Srv::from_tokens(&tokens).map(RData::SRV),
+            DnsTypes::DNAME => single().map(RData::DNAME),
+            DnsTypes::DS => Ds::from_tokens(&tokens).map(RData::DS),
+            DnsTypes::RRSIG => Rrsig::from_tokens(&tokens).map(RData::RRSIG),
+            DnsTypes::NSEC => Nsec::from_tokens(&tokens).map(RData::NSEC),
+            DnsTypes::DNSKEY => Dnskey::from_tokens(&tokens).map(RData::DNSKEY),
+            DnsTypes::NSEC3 => Nsec3::from_tokens(&tokens).map(RData::NSEC3),
+            DnsTypes::NSEC3PARAM => Nsec3Param::from_tokens(&tokens).map(RData::NSEC3PARAM),
+            DnsTypes::CDS => Ds::from_tokens(&tokens).map(RData::CDS),
+            DnsTypes::CDNSKEY => Dnskey::from_tokens(&tokens).map(RData::CDNSKEY),
+            DnsTypes::SVCB => Svcb::from_tokens(&tokens).map(RData::SVCB),
+            DnsTypes::HTTPS => Svcb::from_tokens(&tokens).map(RData::HTTPS),
+            DnsTypes::CAA => Caa::from_tokens(&tokens).map(RData::CAA),
+            DnsTypes::TLSA => Tlsa::from_tokens(&tokens).map(RData::TLSA),
+            DnsTypes::SMIMEA => Tlsa::from_tokens(&tokens).map(RData::SMIMEA),
+            DnsTypes::SSHFP => Sshfp::from_tokens(&tokens).map(RData::SSHFP),
+            DnsTypes::CERT => Cert::from_tokens(&tokens).map(RData::CERT),
+            DnsTypes::OPENPGPKEY => Openpgpkey::from_tokens(&tokens).map(RData::OPENPGPKEY),
+            DnsTypes::WKS => Wks::from_tokens(&tokens).map(RData::WKS),
+            DnsTypes::HINFO => Hinfo::from_tokens(&tokens).map(RData::HINFO),
+            DnsTypes::MINFO => Minfo::from_tokens(&tokens).map(RData::MINFO),
+            DnsTypes::RP => Rp::from_tokens(&tokens).map(RData::RP),
+            DnsTypes::AFSDB => Afsdb::from_tokens(&tokens).map(RData::AFSDB),
+            DnsTypes::LOC => Loc::from_tokens(&tokens).map(RData::LOC),
+            DnsTypes::NAPTR => Naptr::from_tokens(&tokens).map(RData::NAPTR),
+            DnsTypes::KX => Kx::from_tokens(&tokens).map(RData::KX),
+            DnsTypes::APL => Apl::from_tokens(&tokens).map(RData::APL),
+            DnsTypes::URI => Uri::from_tokens(&tokens).map(RData::URI),
+            DnsTypes::SIG => Rrsig::from_tokens(&tokens).map(RData::SIG),
+            DnsTypes::KEY => Dnskey::from_tokens(&tokens).map(RData::KEY),
+            _ => None,
+        };
+        rdata.ok_or_else(invalid)
+    }
+}
+
+/// Splits presentation data into whitespace-separated tokens.
+///
+/// Double quotes group a token and are removed; backslash escapes are kept as is so that
+/// names and character-strings can interpret them. Returns `None` on an unterminated quote.
+pub(crate) fn tokenize(s: &str) -> Option<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(&first) = chars.peek() else {
+            return Some(tokens);
+        };
+        let quoted = first == '"';
+        if quoted {
+            chars.next();
+        }
+        let mut token = String::new();
+        loop {
+            match chars.next() {
+                Some('\\') => {
+                    token.push('\\');
+                    token.push(chars.next()?);
+                }
+                Some('"') if quoted => break,
+                Some(c) if c.is_whitespace() && !quoted => break,
+                Some(c) => token.push(c),
+                None if quoted => return None,
+                None => break,
+            }
+        }
+        tokens.push(token);
+    }
+}
+
+/// Takes the remaining bytes of the RDATA, which `bytes` must end with.
+fn read_rest(bytes: &[u8], offset: &mut usize) -> Vec<u8> {
+    let rest = bytes[*offset..].to_vec();
+    *offset = bytes.len();
+    rest
+}
+
+/// Removes the character-string escapes (`\X` and `\DDD`) of a presentation value.
+fn unescape(value: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut input = value.bytes();
+    while let Some(byte) = input.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match input.next()? {
+            digit @ b'0'..=b'9' => {
+                let mut decimal = (digit - b'0') as u32;
+                for _ in 0..2 {
+                    match input.next()? {
+                        digit @ b'0'..=b'9' => decimal = decimal * 10 + (digit - b'0') as u32,
+                        _ => return None,
+                    }
+                }
+                bytes.push(u8::try_from(decimal).ok()?);
+            }
+            escaped => bytes.push(escaped),
+        }
+    }
+    Some(bytes)
+}
+
+/// The name of a presentation token, where the root `.` is stored as `""` like
+/// `parse_name` decodes it from the wire.
+pub(crate) fn name_from_token(token: &str) -> String {
+    match token {
+        "." => String::new(),
+        name => name.to_string(),
+    }
+}
+
+/// The presentation form of a name, where the root is written `.`.
+pub(crate) fn presentation_name(name: &str) -> &str {
+    match name {
+        "" => ".",
+        name => name,
+    }
+}
+
+fn parse_name_at(bytes: &[u8], offset: &mut usize) -> Result<String, DnsRecordParseError> {
+    let (name, new_offset) = parse_name(bytes, *offset)?;
+    *offset = new_offset;
+    Ok(name)
+}
+
+impl fmt::Display for RData {
+    /// Formats the data in presentation format, as it would appear in a zone file.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RData::A(address) => write!(f, "{}", address),
+            RData::NS(name) | RData::CNAME(name) | RData::PTR(name) | RData::DNAME(name) => {
+                write!(f, "{}", name)
+            }
+            RData::SOA(soa) => write!(f, "{}", soa),
+            RData::MX(mx) => write!(f, "{}", mx),
+            RData::TXT(txt) => write!(f, "{}", txt),
+            RData::AAAA(address) => write!(f, "{}", address),
+            RData::SRV(srv) => write!(f, "{}", srv),
+            RData::OPT(opt) => write!(f, "{}", opt),
+            RData::DS(ds) | RData::CDS(ds) => write!(f, "{}", ds),
+            RData::RRSIG(rrsig) | RData::SIG(rrsig) => write!(f, "{}", rrsig),
+            RData::NSEC(nsec) => write!(f, "{}", nsec),
+            RData::DNSKEY(dnskey) | RData::CDNSKEY(dnskey) | RData::KEY(dnskey) => {
+                write!(f, "{}", dnskey)
+            }
+            RData::NSEC3(nsec3) => write!(f, "{}", nsec3),
+            RData::NSEC3PARAM(nsec3param) => write!(f, "{}", nsec3param),
+            RData::SVCB(svcb) | RData::HTTPS(svcb) => write!(f, "{}", svcb),
+            RData::CAA(caa) => write!(f, "{}", caa),
+            RData::TLSA(tlsa) | RData::SMIMEA(tlsa) => write!(f, "{}", tlsa),
+            RData::SSHFP(sshfp) => write!(f, "{}", sshfp),
+            RData::CERT(cert) => write!(f, "{}", cert),
+            RData::OPENPGPKEY(openpgpkey) => write!(f, "{}", openpgpkey),
+            RData::WKS(wks) => write!(f, "{}", wks),
+            RData::HINFO(hinfo) => write!(f, "{}", hinfo),
+            RData::MINFO(minfo) => write!(f, "{}", minfo),
+            RData::RP(rp) => write!(f, "{}", rp),
+            RData::AFSDB(afsdb) => write!(f, "{}", afsdb),
+            RData::LOC(loc) => write!(f, "{}", loc),
+            RData::NAPTR(naptr) => write!(f, "{}", naptr),
+            RData::KX(kx) => write!(f, "{}", kx),
+            RData::APL(apl) => write!(f, "{}", apl),
+            RData::URI(uri) => write!(f, "{}", uri),
+            RData::TKEY(tkey) => write!(f, "{}", tkey),
+            RData::TSIG(tsig) => write!(f, "{}", tsig),
+            RData::Unknown(data) => {
+                write!(f, "\\# {}", data.len())?;
+                if !data.is_empty() {
+                    write!(f, " {}", hex::encode_upper(data))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rdata_mx_with_pointer() {
+        let data = vec![
+            0x03, b'c', b'o', b'm', 0x00, // com
+            0x00, 0x0a, 0x04, b'm', b'a', b'i', b'l', 0xC0, 0x00, // 10 mail.com
+        ];
+        let rdata = RData::from_bytes(&data, 5, DnsTypes::MX).unwrap();
+        assert_eq!(
+            rdata,
+            RData::MX(Mx {
+                preference: 10,
+                exchange: "mail.com".to_string()
+            })
+        );
+        assert_eq!(rdata.to_string(), "10 mail.com");
+    }
+
+    #[test]
+    fn test_rdata_txt() {
+        let data = b"\x05hello\x03a\"b".to_vec();
+        let rdata = RData::from_bytes(&data, 0, DnsTypes::TXT).unwrap();
+        assert_eq!(rdata.to_string(), "\"hello\" \"a\\\"b\"");
+    }
+
+    #[test]
+    fn test_rdata_presentation_round_trip() {
+        let cases = [
+            (DnsTypes::A, "192.0.2.1"),
+            (DnsTypes::AAAA, "2001:db8::1"),
+            (DnsTypes::CNAME, "www.example.com"),
+            (DnsTypes::MX, "10 mail.example.com"),
+            (DnsTypes::SRV, "0 5 5060 sip.example.com"),
+            (
+                DnsTypes::SOA,
+                "ns.example.com hostmaster.example.com 2024010101 7200 3600 1209600 300",
+            ),
+            (DnsTypes::TXT, "\"v=spf1 -all\" \"a \\\"quoted\\\" word\""),
+            (
+                DnsTypes::DS,
+                "20326 8 2 E06D44B80B8F1D39A95C0B0D7C65D08458E880409BBC683457104237C7F8EC8D",
+            ),
+            (DnsTypes::DNSKEY, "256 3 13 AQIDBA=="),
+            (
+                DnsTypes::RRSIG,
+                "A 13 2 3600 20240201000000 20240101000000 12345 example.com c2ln",
+            ),
+            (DnsTypes::NSEC, "host.example.com A MX RRSIG NSEC TYPE1234"),
+            (
+                DnsTypes::NSEC3,
+                "1 1 10 AABBCCDD 2T7B4G4VSA5SMI47K61MV5BV1A22BOJR A RRSIG",
+            ),
+            (DnsTypes::NSEC3PARAM, "1 0 0 -"),
+            (DnsTypes::SVCB, "0 svc.example.com"),
+            (
+                DnsTypes::HTTPS,
+                "1 . alpn=h3,h2 ipv4hint=192.0.2.1 ech=AEX+DQBB ipv6hint=2001:db8::1",
+            ),
+            (DnsTypes::CAA, "0 issue \"letsencrypt.org\""),
+            (
+                DnsTypes::TLSA,
+                "3 1 1 0B9FA5A59EED715C26C1020C711B4F6EC42D58B0015E14337A39DAD301C5AFC3",
+            ),
+            (DnsTypes::SMIMEA, "0 0 1 D2ABDE240D7CD3EE6B4B28C54DF034B9"),
+            (
+                DnsTypes::SSHFP,
+                "4 2 123456789ABCDEF67890123456789ABCDEF67890123456789ABCDEF123456789",
+            ),
+            (DnsTypes::CERT, "PKIX 12345 8 MIIBCgKCAQEA"),
+            (
+                DnsTypes::OPENPGPKEY,
+                "mQINBFit2jsBEADrbl5vjVxYeAE0g0IDYCBpHirv1Sjl",
+            ),
+            (DnsTypes::WKS, "192.0.2.1 6 25 80"),
+            (DnsTypes::HINFO, "\"INTEL-386\" \"Unix\""),
+            (
+                DnsTypes::MINFO,
+                "list-request.example.com owner.example.com",
+            ),
+            (DnsTypes::RP, "louie.trantor.umd.edu ."),
+            (DnsTypes::AFSDB, "1 afsdb.example.com"),
+            (
+                DnsTypes::LOC,
+                "52 22 23.000 N 4 53 32.000 E -2.00m 0m 10000m 10m",
+            ),
+            (
+                DnsTypes::NAPTR,
+                "100 50 \"s\" \"SIP+D2T\" \"\" _sip._tcp.example.com",
+            ),
+            (DnsTypes::KX, "10 kx.example.com"),
+            (DnsTypes::APL, "1:192.168.32.0/21 !2:2001:db8::/32"),
+            (DnsTypes::URI, "10 1 \"ftp://ftp1.example.com/public\""),
+        ];
+        for (rtype, presentation) in cases {
+            let rdata = RData::from_presentation(rtype, presentation).unwrap();
+            assert_eq!(rdata.to_string(), presentation);
+
+            let mut wire = Vec::new();
+            rdata.to_wire(&mut wire);
+            assert_eq!(RData::from_bytes(&wire, 0, rtype).unwrap(), rdata);
+        }
+    }
+
+    #[test]
+    fn test_rdata_invalid_presentation() {
+        assert!(RData::from_presentation(DnsTypes::A, "192.0.2").is_err());
+        assert!(RData::from_presentation(DnsTypes::MX, "mail.example.com").is_err());
+        assert!(RData::from_presentation(DnsTypes::TXT, "\"unterminated").is_err());
+        assert!(RData::from_presentation(DnsTypes::OPT, "").is_err());
+    }
+
+    #[test]
+    fn test_rdata_length_mismatch() {
+        let data = vec![192, 0, 2, 1, 0];
+        let result = RData::from_bytes(&data, 0, DnsTypes::A);
+        assert!(matches!(
+            result,
+            Err(DnsRecordParseError::RdataLengthMismatch {
+                declared: 5,
+                decoded: 4,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_rdata_unknown_keeps_raw_bytes() {
+        let data = vec![0xde, 0xad, 0xbe, 0xef];
+        let rdata = RData::from_bytes(&data, 0, DnsType(65280)).unwrap();
+        assert_eq!(rdata, RData::Unknown(data));
+        assert_eq!(rdata.to_string(), "\\# 4 DEADBEEF");
+    }
+
+    #[test]
+    fn test_rdata_generic_presentation() {
+        // RFC 3597 §5: the hex may be split, and known types may use the generic form.
+        let rdata = RData::from_presentation(DnsType(65280), "\\# 4 dead BEEF").unwrap();
+        assert_eq!(rdata, RData::Unknown(vec![0xde, 0xad, 0xbe, 0xef]));
+        let rdata = RData::from_presentation(DnsType(731), "\\# 0").unwrap();
+        assert_eq!(rdata.to_string(), "\\# 0");
+        let rdata = RData::from_presentation(DnsTypes::A, "\\# 4 C0000201").unwrap();
+        assert_eq!(rdata, RData::A(Ipv4Addr::new(192, 0, 2, 1)));
+
+        assert!(RData::from_presentation(DnsType(65280), "\\# 3 DEADBEEF").is_err());
+        assert!(RData::from_presentation(DnsType(65280), "\\# 1 XY").is_err());
+        assert!(RData::from_presentation(DnsType(65280), "DEADBEEF").is_err());
+        assert!(RData::from_presentation(DnsTypes::A, "\\# 3 C00002").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_rdata_serde() {
+        let rdata = RData::A(Ipv4Addr::new(192, 0, 2, 1));
+        assert_eq!(
+            serde_json::to_string(&rdata).unwrap(),
+            r#"{"A":"192.0.2.1"}"#
+        );
+        let rdata = RData::TXT(Txt {
+            strings: vec![CharacterString(b"v=spf1 -all".to_vec())],
+        });
+        let json = serde_json::to_string(&rdata).unwrap();
+        assert_eq!(json, r#"{"TXT":["v=spf1 -all"]}"#);
+        assert_eq!(serde_json::from_str::<RData>(&json).unwrap(), rdata);
+        let rdata = RData::Unknown(vec![0xde, 0xad]);
+        assert_eq!(
+            serde_json::to_string(&rdata).unwrap(),
+            r#"{"Unknown":"dead"}"#
+        );
+    }
+}
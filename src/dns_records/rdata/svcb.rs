@@ -0,0 +1,502 @@
+use core::fmt;
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{name_from_token, presentation_name, unescape, CharacterString};
+use crate::dns_records::{
+    errors::DnsRecordParseError, parse_name, read_array, read_bytes, read_u16, write_name,
+};
+use crate::prelude::*;
+use crate::utils::encoding::{base64_decode, base64_encode};
+
+/// The SvcParamKeys (IANA "Service Parameter Keys (SvcParamKeys)").
+#[allow(non_snake_case)]
+#[allow(non_upper_case_globals)]
+pub mod SvcParamKeys {
+    pub const MANDATORY: u16 = 0;
+    pub const ALPN: u16 = 1;
+    pub const NO_DEFAULT_ALPN: u16 = 2;
+    pub const PORT: u16 = 3;
+    pub const IPV4HINT: u16 = 4;
+    pub const ECH: u16 = 5;
+    pub const IPV6HINT: u16 = 6;
+    pub const DOHPATH: u16 = 7;
+}
+
+/// The presentation names of the keys with a dedicated decoder, indexed by key.
+const KEY_NAMES: [&str; 8] = [
+    "mandatory",
+    "alpn",
+    "no-default-alpn",
+    "port",
+    "ipv4hint",
+    "ech",
+    "ipv6hint",
+    "dohpath",
+];
+
+/// SVCB and HTTPS record data (RFC 9460 §2.2).
+///
+/// A priority of 0 is AliasMode, pointing to `target` without parameters; otherwise the
+/// record is in ServiceMode and `params` describe the endpoint, in increasing key order.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Svcb {
+    pub priority: u16,
+    pub target: String,
+    pub params: Vec<SvcParam>,
+}
+
+impl Svcb {
+    /// Whether the record is in AliasMode.
+    pub fn is_alias(&self) -> bool {
+        self.priority == 0
+    }
+
+    /// The parameter with `key` (see `SvcParamKeys`), if present.
+    pub fn param(&self, key: u16) -> Option<&SvcParam> {
+        self.params.iter().find(|param| param.key() == key)
+    }
+
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let priority = read_u16(bytes, offset)?;
+        let (target, new_offset) = parse_name(bytes, *offset)?;
+        *offset = new_offset;
+        let mut params: Vec<SvcParam> = Vec::new();
+        while *offset < bytes.len() {
+            let start = *offset;
+            let key = read_u16(bytes, offset)?;
+            let length = read_u16(bytes, offset)?;
+            let value = read_bytes(bytes, offset, length as usize)?;
+            // Keys must be unique and in increasing order (RFC 9460 §2.2).
+            let in_order = params.last().is_none_or(|last| last.key() < key);
+            let param = SvcParam::from_value(key, value)
+                .filter(|_| in_order)
+                .ok_or(DnsRecordParseError::InvalidSvcParam { key, offset: start })?;
+            params.push(param);
+        }
+        Ok(Svcb {
+            priority,
+            target,
+            params,
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.priority.to_be_bytes());
+        write_name(&self.target, buf);
+        for param in &self.params {
+            let mut value = Vec::new();
+            param.value_to_wire(&mut value);
+            buf.extend_from_slice(&param.key().to_be_bytes());
+            buf.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            buf.extend_from_slice(&value);
+        }
+    }
+
+    /// Parses the presentation tokens `priority target params...`, where each parameter
+    /// is `key` or `key=value`, in any order.
+    pub(crate) fn from_tokens(tokens: &[String]) -> Option<Self> {
+        let [priority, target, rest @ ..] = tokens else {
+            return None;
+        };
+        let mut params = Vec::new();
+        let mut rest = rest.iter();
+        while let Some(token) = rest.next() {
+            let (key, value) = match token.split_once('=') {
+                Some((key, value)) => (key, Some(value.to_string())),
+                None => (token.as_str(), None),
+            };
+            // A quoted value containing spaces was split by the tokenizer.
+            let value = match value {
+                Some(mut value) if value.starts_with('"') => {
+                    while value.len() < 2 || !value.ends_with('"') {
+                        value.push(' ');
+                        value.push_str(rest.next()?);
+                    }
+                    Some(value[1..value.len() - 1].to_string())
+                }
+                value => value,
+            };
+            params.push(SvcParam::from_presentation(key, value.as_deref())?);
+        }
+        params.sort_by_key(SvcParam::key);
+        if params.windows(2).any(|pair| pair[0].key() == pair[1].key()) {
+            return None;
+        }
+        Some(Svcb {
+            priority: priority.parse().ok()?,
+            target: name_from_token(target),
+            params,
+        })
+    }
+}
+
+impl fmt::Display for Svcb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.priority, presentation_name(&self.target))?;
+        for param in &self.params {
+            write!(f, " {}", param)?;
+        }
+        Ok(())
+    }
+}
+
+/// A service parameter of an SVCB or HTTPS record (RFC 9460 §7, RFC 9461 §5).
+///
+/// Keys without a dedicated decoder are kept as raw bytes in `Unknown`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SvcParam {
+    /// The keys a client must support to use the record.
+    Mandatory(Vec<u16>),
+    /// The ALPN protocol identifiers, e.g. `h2` and `h3`.
+    Alpn(Vec<CharacterString>),
+    NoDefaultAlpn,
+    Port(u16),
+    Ipv4Hint(Vec<Ipv4Addr>),
+    /// An encoded ECHConfigList, for TLS Encrypted Client Hello.
+    Ech(#[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))] Vec<u8>),
+    Ipv6Hint(Vec<Ipv6Addr>),
+    /// The URI template of a DNS over HTTPS endpoint, e.g. `/dns-query{?dns}`.
+    DohPath(String),
+    Unknown {
+        key: u16,
+        #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+        value: Vec<u8>,
+    },
+}
+
+impl SvcParam {
+    pub fn key(&self) -> u16 {
+        match self {
+            SvcParam::Mandatory(_) => SvcParamKeys::MANDATORY,
+            SvcParam::Alpn(_) => SvcParamKeys::ALPN,
+            SvcParam::NoDefaultAlpn => SvcParamKeys::NO_DEFAULT_ALPN,
+            SvcParam::Port(_) => SvcParamKeys::PORT,
+            SvcParam::Ipv4Hint(_) => SvcParamKeys::IPV4HINT,
+            SvcParam::Ech(_) => SvcParamKeys::ECH,
+            SvcParam::Ipv6Hint(_) => SvcParamKeys::IPV6HINT,
+            SvcParam::DohPath(_) => SvcParamKeys::DOHPATH,
+            SvcParam::Unknown { key, .. } => *key,
+        }
+    }
+
+    /// Decodes the wire `value` of `key`, or `None` when it is malformed.
+    fn from_value(key: u16, value: &[u8]) -> Option<Self> {
+        let mut offset = 0;
+        let param = match key {
+            SvcParamKeys::MANDATORY if !value.is_empty() => {
+                let mut keys = Vec::new();
+                while offset < value.len() {
+                    keys.push(read_u16(value, &mut offset).ok()?);
+                }
+                SvcParam::Mandatory(keys)
+            }
+            SvcParamKeys::ALPN if !value.is_empty() => {
+                let mut ids = Vec::new();
+                while offset < value.len() {
+                    ids.push(CharacterString::from_bytes(value, &mut offset).ok()?);
+                }
+                SvcParam::Alpn(ids)
+            }
+            SvcParamKeys::NO_DEFAULT_ALPN if value.is_empty() => SvcParam::NoDefaultAlpn,
+            SvcParamKeys::PORT => SvcParam::Port(u16::from_be_bytes(value.try_into().ok()?)),
+            SvcParamKeys::IPV4HINT if !value.is_empty() => {
+                let mut addresses = Vec::new();
+                while offset < value.len() {
+                    addresses.push(Ipv4Addr::from(read_array::<4>(value, &mut offset).ok()?));
+                }
+                SvcParam::Ipv4Hint(addresses)
+            }
+            SvcParamKeys::ECH => SvcParam::Ech(value.to_vec()),
+            SvcParamKeys::IPV6HINT if !value.is_empty() => {
+                let mut addresses = Vec::new();
+                while offset < value.len() {
+                    addresses.push(Ipv6Addr::from(read_array::<16>(value, &mut offset).ok()?));
+                }
+                SvcParam::Ipv6Hint(addresses)
+            }
+            SvcParamKeys::DOHPATH => SvcParam::DohPath(String::from_utf8(value.to_vec()).ok()?),
+            SvcParamKeys::MANDATORY
+            | SvcParamKeys::ALPN
+            | SvcParamKeys::NO_DEFAULT_ALPN
+            | SvcParamKeys::IPV4HINT
+            | SvcParamKeys::IPV6HINT => return None,
+            _ => SvcParam::Unknown {
+                key,
+                value: value.to_vec(),
+            },
+        };
+        Some(param)
+    }
+
+    fn value_to_wire(&self, buf: &mut Vec<u8>) {
+        match self {
+            SvcParam::Mandatory(keys) => {
+                for key in keys {
+                    buf.extend_from_slice(&key.to_be_bytes());
+                }
+            }
+            SvcParam::Alpn(ids) => {
+                for id in ids {
+                    id.to_wire(buf);
+                }
+            }
+            SvcParam::NoDefaultAlpn => {}
+            SvcParam::Port(port) => buf.extend_from_slice(&port.to_be_bytes()),
+            SvcParam::Ipv4Hint(addresses) => {
+                for address in addresses {
+                    buf.extend_from_slice(&address.octets());
+                }
+            }
+            SvcParam::Ipv6Hint(addresses) => {
+                for address in addresses {
+                    buf.extend_from_slice(&address.octets());
+                }
+            }
+            SvcParam::DohPath(template) => buf.extend_from_slice(template.as_bytes()),
+            SvcParam::Ech(value) | SvcParam::Unknown { value, .. } => buf.extend_from_slice(value),
+        }
+    }
+
+    /// Parses a `key=value` parameter, the value being unquoted but still escaped.
+    fn from_presentation(key: &str, value: Option<&str>) -> Option<Self> {
+        let key = parse_key(key)?;
+        let value = match value {
+            Some(value) => Some(unescape(value)?),
+            None => None,
+        };
+        let list = || {
+            value
+                .as_deref()
+                .map(split_value_list)
+                .filter(|items| !items.is_empty())
+        };
+        let text = || {
+            value
+                .clone()
+                .and_then(|value| String::from_utf8(value).ok())
+        };
+        let param = match key {
+            SvcParamKeys::MANDATORY => {
+                let mut keys = list()?
+                    .iter()
+                    .map(|key| parse_key(core::str::from_utf8(key).ok()?))
+                    .collect::<Option<Vec<_>>>()?;
+                // The keys are sorted on the wire (RFC 9460 §8).
+                keys.sort_unstable();
+                SvcParam::Mandatory(keys)
+            }
+            SvcParamKeys::ALPN => SvcParam::Alpn(
+                list()?
+                    .into_iter()
+                    .map(|id| (id.len() <= 255).then_some(CharacterString(id)))
+                    .collect::<Option<_>>()?,
+            ),
+            SvcParamKeys::NO_DEFAULT_ALPN if value.is_none() => SvcParam::NoDefaultAlpn,
+            SvcParamKeys::PORT => SvcParam::Port(text()?.parse().ok()?),
+            SvcParamKeys::IPV4HINT => SvcParam::Ipv4Hint(parse_addresses(&text()?)?),
+            SvcParamKeys::ECH => SvcParam::Ech(base64_decode(&text()?)?),
+            SvcParamKeys::IPV6HINT => SvcParam::Ipv6Hint(parse_addresses(&text()?)?),
+            SvcParamKeys::DOHPATH => SvcParam::DohPath(text()?),
+            SvcParamKeys::NO_DEFAULT_ALPN => return None,
+            _ => SvcParam::Unknown {
+                key,
+                value: value.unwrap_or_default(),
+            },
+        };
+        Some(param)
+    }
+}
+
+impl fmt::Display for SvcParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", KeyName(self.key()))?;
+        let value = match self {
+            SvcParam::Mandatory(keys) => join(keys.iter().map(|&key| KeyName(key).to_string())),
+            SvcParam::Alpn(ids) => join(ids.iter().map(|id| {
+                // Commas and backslashes are escaped within the list, then once more as
+                // a character-string (RFC 9460 Appendix A.1).
+                let mut escaped = Vec::new();
+                for &byte in &id.0 {
+                    if byte == b',' || byte == b'\\' {
+                        escaped.push(b'\\');
+                    }
+                    escaped.push(byte);
+                }
+                CharacterString(escaped).to_string()
+            })),
+            SvcParam::NoDefaultAlpn => return Ok(()),
+            SvcParam::Port(port) => port.to_string(),
+            SvcParam::Ipv4Hint(addresses) => join(addresses.iter().map(ToString::to_string)),
+            SvcParam::Ech(value) => base64_encode(value),
+            SvcParam::Ipv6Hint(addresses) => join(addresses.iter().map(ToString::to_string)),
+            SvcParam::DohPath(template) => {
+                CharacterString(template.as_bytes().to_vec()).to_string()
+            }
+            SvcParam::Unknown { value, .. } if value.is_empty() => return Ok(()),
+            SvcParam::Unknown { value, .. } => CharacterString(value.clone()).to_string(),
+        };
+        match value.contains(' ') {
+            true => write!(f, "=\"{}\"", value),
+            false => write!(f, "={}", value),
+        }
+    }
+}
+
+/// Displays a key as its name, or as `key<n>` when it has no dedicated decoder.
+struct KeyName(u16);
+
+impl fmt::Display for KeyName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match KEY_NAMES.get(self.0 as usize) {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "key{}", self.0),
+        }
+    }
+}
+
+fn parse_key(name: &str) -> Option<u16> {
+    match KEY_NAMES.iter().position(|&known| known == name) {
+        Some(key) => Some(key as u16),
+        None => name.strip_prefix("key")?.parse().ok(),
+    }
+}
+
+fn join(items: impl Iterator<Item = String>) -> String {
+    items.collect::<Vec<_>>().join(",")
+}
+
+/// Splits an unescaped value on the commas not escaped by a backslash.
+fn split_value_list(value: &[u8]) -> Vec<Vec<u8>> {
+    let mut items = Vec::new();
+    let mut item = Vec::new();
+    let mut bytes = value.iter();
+    while let Some(&byte) = bytes.next() {
+        match byte {
+            b'\\' => item.extend(bytes.next()),
+            b',' => items.push(core::mem::take(&mut item)),
+            _ => item.push(byte),
+        }
+    }
+    items.push(item);
+    items
+}
+
+fn parse_addresses<T: core::str::FromStr>(value: &str) -> Option<Vec<T>> {
+    value
+        .split(',')
+        .map(|address| address.parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_presentation(s: &str) -> Svcb {
+        Svcb::from_tokens(&super::super::tokenize(s).unwrap()).unwrap()
+    }
+
+    fn wire(svcb: &Svcb) -> String {
+        let mut buf = Vec::new();
+        svcb.to_wire(&mut buf);
+        hex::encode(buf)
+    }
+
+    #[test]
+    fn test_svcb_rfc9460_vectors() {
+        // RFC 9460 Appendix D.
+        let cases = [
+            ("0 foo.example.com", "000003666f6f076578616d706c6503636f6d00"),
+            ("1 .", "000100"),
+            (
+                "16 foo.example.com port=53",
+                "001003666f6f076578616d706c6503636f6d00 000300020035",
+            ),
+            (
+                "1 foo.example.com key667=hello",
+                "000103666f6f076578616d706c6503636f6d00029b000568656c6c6f",
+            ),
+            (
+                r#"1 foo.example.com key667="hello\210qoo""#,
+                "000103666f6f076578616d706c6503636f6d00029b000968656c6c6fd2716f6f",
+            ),
+            (
+                r#"1 foo.example.com ipv6hint="2001:db8::1,2001:db8::53:1""#,
+                "000103666f6f076578616d706c6503636f6d00 00060020 20010db8000000000000000000000001 20010db8000000000000000000530001",
+            ),
+            (
+                "16 foo.example.org alpn=h2,h3-19 mandatory=ipv4hint,alpn ipv4hint=192.0.2.1",
+                "001003666f6f076578616d706c65036f726700 0000000400010004 000100090268320568332d3139 00040004c0000201",
+            ),
+            (
+                r#"16 foo.example.org alpn="f\\\\oo\\,bar,h2""#,
+                "001003666f6f076578616d706c65036f726700 0001000c 08665c6f6f2c626172 026832",
+            ),
+        ];
+        for (presentation, expected) in cases {
+            let svcb = from_presentation(presentation);
+            assert_eq!(wire(&svcb), expected.replace(' ', ""), "{}", presentation);
+
+            let bytes = hex::decode(expected.replace(' ', "")).unwrap();
+            let mut offset = 0;
+            let decoded = Svcb::from_bytes(&bytes, &mut offset).unwrap();
+            assert_eq!(decoded, svcb);
+            assert_eq!(from_presentation(&decoded.to_string()), svcb);
+        }
+
+        let svcb = from_presentation(r#"16 foo.example.org alpn="f\\\\oo\\,bar,h2""#);
+        assert_eq!(
+            svcb.to_string(),
+            r#"16 foo.example.org alpn=f\\\\oo\\,bar,h2"#
+        );
+        assert!(svcb.param(SvcParamKeys::ECH).is_none());
+    }
+
+    #[test]
+    fn test_svcb_params() {
+        let svcb = from_presentation(
+            "1 . alpn=h3,h2 no-default-alpn port=8443 ipv4hint=192.0.2.1,192.0.2.2 ech=AEX+DQBB dohpath=/dns-query{?dns}",
+        );
+        assert!(!svcb.is_alias());
+        assert_eq!(
+            svcb.param(SvcParamKeys::ALPN),
+            Some(&SvcParam::Alpn(vec![
+                CharacterString(b"h3".to_vec()),
+                CharacterString(b"h2".to_vec())
+            ]))
+        );
+        assert_eq!(
+            svcb.param(SvcParamKeys::ECH),
+            Some(&SvcParam::Ech(vec![0x00, 0x45, 0xfe, 0x0d, 0x00, 0x41]))
+        );
+        assert_eq!(
+            svcb.to_string(),
+            "1 . alpn=h3,h2 no-default-alpn port=8443 ipv4hint=192.0.2.1,192.0.2.2 ech=AEX+DQBB dohpath=/dns-query{?dns}"
+        );
+
+        // Parameters out of order, duplicated, or with malformed values.
+        for hex in [
+            "0001 00 0003000201bb 00010003026832",
+            "0001 00 0003000201bb 0003000201bb",
+            "0001 00 000300030001bb",
+            "0001 00 0002000100",
+            "0001 00 00010000",
+        ] {
+            let bytes = hex::decode(hex.replace(' ', "")).unwrap();
+            let mut offset = 0;
+            assert!(matches!(
+                Svcb::from_bytes(&bytes, &mut offset),
+                Err(DnsRecordParseError::InvalidSvcParam { offset: 3.., .. })
+            ));
+        }
+        let tokens = |s| super::super::tokenize(s).unwrap();
+        assert_eq!(Svcb::from_tokens(&tokens("1 . port=53 port=54")), None);
+        assert_eq!(Svcb::from_tokens(&tokens("1 . no-default-alpn=x")), None);
+        assert_eq!(Svcb::from_tokens(&tokens("1 . unknown=x")), None);
+    }
+}
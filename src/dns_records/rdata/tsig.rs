@@ -0,0 +1,323 @@
+use core::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{presentation_name, Timestamp};
+use crate::dns_records::{
+    errors::DnsRecordParseError, parse_name, read_array, read_bytes, read_u16, read_u32, write_name,
+};
+use crate::prelude::*;
+use crate::utils::encoding::base64_encode;
+
+/// The TSIG algorithm names (IANA "TSIG Algorithm Names"), also used by TKEY records.
+#[allow(non_snake_case)]
+pub mod TsigAlgorithms {
+    pub const HMAC_SHA1: &str = "hmac-sha1";
+    pub const HMAC_SHA256: &str = "hmac-sha256";
+    pub const HMAC_SHA384: &str = "hmac-sha384";
+    pub const HMAC_SHA512: &str = "hmac-sha512";
+    /// GSS-TSIG (RFC 3645): the MAC is made by a GSS-API security context, such as the
+    /// Kerberos one of Active Directory.
+    pub const GSS_TSIG: &str = "gss-tsig";
+    /// The name of GSS-TSIG used by Windows 2000 DNS servers and clients.
+    pub const GSS_MICROSOFT: &str = "gss.microsoft.com";
+}
+
+/// Whether `algorithm` names GSS-TSIG, under its standard or its Microsoft name.
+fn is_gss_algorithm(algorithm: &str) -> bool {
+    let algorithm = algorithm.trim_end_matches('.');
+    algorithm.eq_ignore_ascii_case(TsigAlgorithms::GSS_TSIG)
+        || algorithm.eq_ignore_ascii_case(TsigAlgorithms::GSS_MICROSOFT)
+}
+
+/// TSIG record data (RFC 8945 §4.2): the transaction signature of a message, sent as the
+/// last additional record and owned by the name of the key.
+///
+/// TSIG records only appear in messages, so they have no presentation format to parse.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Tsig {
+    /// The MAC algorithm, e.g. `hmac-sha256`.
+    pub algorithm: String,
+    /// Seconds since the Unix epoch, on 48 bits.
+    pub time_signed: u64,
+    /// The seconds of error permitted in `time_signed`.
+    pub fudge: u16,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+    pub mac: Vec<u8>,
+    /// The message ID when the message was signed, before any forwarder changed it.
+    pub original_id: u16,
+    /// An extended RCODE, such as `Tsig::BADSIG`, or 0.
+    pub error: u16,
+    /// Empty, except in BADTIME errors where it holds the server time on 48 bits.
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+    pub other: Vec<u8>,
+}
+
+impl Tsig {
+    /// The MAC did not verify.
+    pub const BADSIG: u16 = 16;
+    /// The key is not known by the server.
+    pub const BADKEY: u16 = 17;
+    /// The time signed is outside the fudge of the server time.
+    pub const BADTIME: u16 = 18;
+    /// The MAC is truncated below the length the server accepts.
+    pub const BADTRUNC: u16 = 22;
+
+    /// Whether the MAC is made by a GSS-API security context (RFC 3645).
+    pub fn is_gss(&self) -> bool {
+        is_gss_algorithm(&self.algorithm)
+    }
+
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let (algorithm, new_offset) = parse_name(bytes, *offset)?;
+        *offset = new_offset;
+        let mut time_signed = [0; 8];
+        time_signed[2..].copy_from_slice(&read_array::<6>(bytes, offset)?);
+        let fudge = read_u16(bytes, offset)?;
+        let mac_size = read_u16(bytes, offset)?;
+        let mac = read_bytes(bytes, offset, mac_size as usize)?.to_vec();
+        let original_id = read_u16(bytes, offset)?;
+        let error = read_u16(bytes, offset)?;
+        let other_len = read_u16(bytes, offset)?;
+        Ok(Tsig {
+            algorithm,
+            time_signed: u64::from_be_bytes(time_signed),
+            fudge,
+            mac,
+            original_id,
+            error,
+            other: read_bytes(bytes, offset, other_len as usize)?.to_vec(),
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        write_name(&self.algorithm, buf);
+        buf.extend_from_slice(&self.time_signed.to_be_bytes()[2..]);
+        buf.extend_from_slice(&self.fudge.to_be_bytes());
+        buf.extend_from_slice(&(self.mac.len() as u16).to_be_bytes());
+        buf.extend_from_slice(&self.mac);
+        buf.extend_from_slice(&self.original_id.to_be_bytes());
+        buf.extend_from_slice(&self.error.to_be_bytes());
+        buf.extend_from_slice(&(self.other.len() as u16).to_be_bytes());
+        buf.extend_from_slice(&self.other);
+    }
+}
+
+impl fmt::Display for Tsig {
+    /// Formats the fields in order, with the MAC and other data in base64 when present.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            presentation_name(&self.algorithm),
+            self.time_signed,
+            self.fudge,
+            self.mac.len()
+        )?;
+        if !self.mac.is_empty() {
+            write!(f, " {}", base64_encode(&self.mac))?;
+        }
+        write!(
+            f,
+            " {} {} {}",
+            self.original_id,
+            self.error,
+            self.other.len()
+        )?;
+        if !self.other.is_empty() {
+            write!(f, " {}", base64_encode(&self.other))?;
+        }
+        Ok(())
+    }
+}
+
+/// The modes of TKEY records (RFC 2930 §2.5).
+#[allow(non_snake_case)]
+pub mod TkeyModes {
+    pub const SERVER_ASSIGNMENT: u16 = 1;
+    pub const DIFFIE_HELLMAN: u16 = 2;
+    pub const GSS_API: u16 = 3;
+    pub const RESOLVER_ASSIGNMENT: u16 = 4;
+    pub const KEY_DELETION: u16 = 5;
+}
+
+/// The names of the TKEY modes, displayed instead of their number.
+const TKEY_MODE_NAMES: [(u16, &str); 5] = [
+    (TkeyModes::SERVER_ASSIGNMENT, "SERVER-ASSIGNMENT"),
+    (TkeyModes::DIFFIE_HELLMAN, "DIFFIE-HELLMAN"),
+    (TkeyModes::GSS_API, "GSS-API"),
+    (TkeyModes::RESOLVER_ASSIGNMENT, "RESOLVER-ASSIGNMENT"),
+    (TkeyModes::KEY_DELETION, "KEY-DELETION"),
+];
+
+/// TKEY record data (RFC 2930 §2): a step of the negotiation of a TSIG key, owned by
+/// the name of the key. With GSS-TSIG (RFC 3645), the key data carries the GSS-API
+/// tokens, such as the Kerberos exchange of Active Directory clients.
+///
+/// Like TSIG records, TKEY records only appear in messages and have no presentation
+/// format to parse.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Tkey {
+    /// The algorithm of the negotiated key, e.g. `gss-tsig`.
+    pub algorithm: String,
+    /// The start of the validity period of the key.
+    pub inception: Timestamp,
+    /// The end of the validity period of the key.
+    pub expiration: Timestamp,
+    /// The negotiation mode, see `TkeyModes`.
+    pub mode: u16,
+    /// An extended RCODE, such as `Tkey::BADMODE`, or 0.
+    pub error: u16,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+    pub key_data: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_hex"))]
+    pub other: Vec<u8>,
+}
+
+impl Tkey {
+    /// The mode is not supported by the server.
+    pub const BADMODE: u16 = 19;
+    /// The key name is not acceptable.
+    pub const BADNAME: u16 = 20;
+    /// The algorithm is not supported by the server.
+    pub const BADALG: u16 = 21;
+
+    /// The name of the mode, such as `GSS-API`, if it is known.
+    pub fn mode_name(&self) -> Option<&'static str> {
+        TKEY_MODE_NAMES
+            .iter()
+            .find(|&&(value, _)| value == self.mode)
+            .map(|&(_, name)| name)
+    }
+
+    /// Whether the key is negotiated for GSS-TSIG (RFC 3645).
+    pub fn is_gss(&self) -> bool {
+        is_gss_algorithm(&self.algorithm)
+    }
+
+    pub fn from_bytes(bytes: &[u8], offset: &mut usize) -> Result<Self, DnsRecordParseError> {
+        let (algorithm, new_offset) = parse_name(bytes, *offset)?;
+        *offset = new_offset;
+        let inception = Timestamp(read_u32(bytes, offset)?);
+        let expiration = Timestamp(read_u32(bytes, offset)?);
+        let mode = read_u16(bytes, offset)?;
+        let error = read_u16(bytes, offset)?;
+        let key_size = read_u16(bytes, offset)?;
+        let key_data = read_bytes(bytes, offset, key_size as usize)?.to_vec();
+        let other_size = read_u16(bytes, offset)?;
+        Ok(Tkey {
+            algorithm,
+            inception,
+            expiration,
+            mode,
+            error,
+            key_data,
+            other: read_bytes(bytes, offset, other_size as usize)?.to_vec(),
+        })
+    }
+
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        write_name(&self.algorithm, buf);
+        buf.extend_from_slice(&self.inception.0.to_be_bytes());
+        buf.extend_from_slice(&self.expiration.0.to_be_bytes());
+        buf.extend_from_slice(&self.mode.to_be_bytes());
+        buf.extend_from_slice(&self.error.to_be_bytes());
+        buf.extend_from_slice(&(self.key_data.len() as u16).to_be_bytes());
+        buf.extend_from_slice(&self.key_data);
+        buf.extend_from_slice(&(self.other.len() as u16).to_be_bytes());
+        buf.extend_from_slice(&self.other);
+    }
+}
+
+impl fmt::Display for Tkey {
+    /// Formats the fields in order, with the mode name when known and the key and other
+    /// data in base64 when present.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} ",
+            presentation_name(&self.algorithm),
+            self.inception,
+            self.expiration
+        )?;
+        match self.mode_name() {
+            Some(name) => write!(f, "{}", name)?,
+            None => write!(f, "{}", self.mode)?,
+        }
+        write!(f, " {} {}", self.error, self.key_data.len())?;
+        if !self.key_data.is_empty() {
+            write!(f, " {}", base64_encode(&self.key_data))?;
+        }
+        write!(f, " {}", self.other.len())?;
+        if !self.other.is_empty() {
+            write!(f, " {}", base64_encode(&self.other))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tkey_gss_api() {
+        // The TKEY of a GSS-TSIG negotiation, with a 4-byte token.
+        let wire = b"\x08gss-tsig\x00\x65\x53\xf1\x00\x65\x55\x42\x80\x00\x03\x00\x00\
+            \x00\x04\x60\x82\x01\x02\x00\x00";
+        let mut offset = 0;
+        let tkey = Tkey::from_bytes(wire, &mut offset).unwrap();
+        assert_eq!(offset, wire.len());
+        assert_eq!(tkey.mode, TkeyModes::GSS_API);
+        assert_eq!(tkey.mode_name(), Some("GSS-API"));
+        assert!(tkey.is_gss());
+        assert_eq!(tkey.expiration.0 - tkey.inception.0, 86400);
+        assert_eq!(
+            tkey.to_string(),
+            "gss-tsig 20231114221320 20231115221320 GSS-API 0 4 YIIBAg== 0"
+        );
+        let mut buf = Vec::new();
+        tkey.to_wire(&mut buf);
+        assert_eq!(buf, wire);
+
+        let tkey = Tkey {
+            algorithm: "hmac-sha256".to_string(),
+            mode: 9,
+            error: Tkey::BADMODE,
+            key_data: Vec::new(),
+            ..tkey
+        };
+        assert!(!tkey.is_gss());
+        assert_eq!(tkey.mode_name(), None);
+        assert_eq!(
+            tkey.to_string(),
+            "hmac-sha256 20231114221320 20231115221320 9 19 0 0"
+        );
+    }
+
+    #[test]
+    fn test_tsig_gss_algorithm() {
+        let tsig = Tsig {
+            algorithm: "GSS.Microsoft.com.".to_string(),
+            time_signed: 1_700_000_000,
+            fudge: 300,
+            mac: vec![1, 2, 3],
+            original_id: 7,
+            error: 0,
+            other: Vec::new(),
+        };
+        assert!(tsig.is_gss());
+        let mut wire = Vec::new();
+        tsig.to_wire(&mut wire);
+        let mut offset = 0;
+        let decoded = Tsig::from_bytes(&wire, &mut offset).unwrap();
+        assert_eq!(decoded.algorithm, "GSS.Microsoft.com");
+        assert_eq!(
+            decoded.to_string(),
+            "GSS.Microsoft.com 1700000000 300 3 AQID 7 0 0"
+        );
+    }
+}
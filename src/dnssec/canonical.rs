@@ -0,0 +1,163 @@
+//! The canonical forms that DNSSEC signatures cover (RFC 4034 §6).
+
+use core::cmp::Ordering;
+
+use super::errors::BogusReason;
+use crate::dns_records::rdata::{Afsdb, Kx, Minfo, Mx, Naptr, RData, Rp, Rrsig, Soa, Srv};
+use crate::dns_records::{parse_name, write_name, ResourceRecord};
+use crate::prelude::*;
+
+/// The canonical wire form of `name`: uncompressed and lowercased.
+pub(crate) fn name(name: &str) -> Vec<u8> {
+    let mut wire = Vec::new();
+    write_name(&name.to_ascii_lowercase(), &mut wire);
+    wire
+}
+
+/// The labels of a name in wire form, without the root label.
+pub(crate) fn labels(wire: &[u8]) -> Vec<&[u8]> {
+    let mut labels = Vec::new();
+    let mut position = 0;
+    while let Some(&length) = wire.get(position).filter(|&&length| length > 0) {
+        let start = position + 1;
+        position = start + length as usize;
+        labels.push(&wire[start..position]);
+    }
+    labels
+}
+
+/// Whether the canonical name `ancestor` is `name` or one of its ancestors.
+pub(crate) fn is_ancestor(ancestor: &[u8], name: &[u8]) -> bool {
+    let (ancestor, name) = (labels(ancestor), labels(name));
+    name.ends_with(&ancestor)
+}
+
+/// The canonical order of names in wire form (RFC 4034 §6.1): label by label from the
+/// rightmost one.
+pub(crate) fn compare(a: &[u8], b: &[u8]) -> Ordering {
+    labels(a).iter().rev().cmp(labels(b).iter().rev())
+}
+
+/// `name` without its first `count` labels.
+pub(crate) fn ancestor(name: &[u8], count: usize) -> &[u8] {
+    let mut position = 0;
+    for _ in 0..count {
+        position += 1 + name[position] as usize;
+    }
+    &name[position..]
+}
+
+/// The presentation form of a canonical name.
+pub(crate) fn display(wire: &[u8]) -> String {
+    parse_name(wire, 0)
+        .map(|(name, _)| name)
+        .unwrap_or_default()
+}
+
+/// The data an RRSIG signs (RFC 4034 §3.1.8.1): its RDATA up to the signature, then the
+/// records of the RRset in canonical form and order, with the original TTL.
+pub(crate) fn signed_data(rrset: &[ResourceRecord], rrsig: &Rrsig) -> Result<Vec<u8>, BogusReason> {
+    let mut data = Vec::new();
+    Rrsig {
+        signer: rrsig.signer.to_ascii_lowercase(),
+        signature: Vec::new(),
+        ..rrsig.clone()
+    }
+    .to_wire(&mut data);
+
+    let first = rrset.first().ok_or(BogusReason::EmptyRrset)?;
+    let owner = owner(&first.name, rrsig.labels)?;
+    let mut rdatas: Vec<Vec<u8>> = rrset.iter().map(|record| rdata(&record.rdata)).collect();
+    rdatas.sort();
+    rdatas.dedup();
+    for rdata in rdatas {
+        data.extend_from_slice(&owner);
+        data.extend_from_slice(&first.rtype.0.to_be_bytes());
+        data.extend_from_slice(&first.rclass.0.to_be_bytes());
+        data.extend_from_slice(&rrsig.original_ttl.to_be_bytes());
+        data.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        data.extend_from_slice(&rdata);
+    }
+    Ok(data)
+}
+
+/// The owner name as signed: when the RRSIG has fewer labels than the owner, the RRset
+/// was synthesized from a wildcard, which is what was signed (RFC 4035 §5.3.2).
+fn owner(owner: &str, rrsig_labels: u8) -> Result<Vec<u8>, BogusReason> {
+    let wire = name(owner);
+    let labels = labels(&wire);
+    let rrsig_labels = rrsig_labels as usize;
+    if rrsig_labels > labels.len() {
+        return Err(BogusReason::LabelCount {
+            labels: rrsig_labels as u8,
+            owner_labels: labels.len(),
+        });
+    }
+    if rrsig_labels == labels.len() {
+        return Ok(wire);
+    }
+    let mut wildcard = vec![1, b'*'];
+    for label in &labels[labels.len() - rrsig_labels..] {
+        wildcard.push(label.len() as u8);
+        wildcard.extend_from_slice(label);
+    }
+    wildcard.push(0);
+    Ok(wildcard)
+}
+
+/// The canonical RDATA: the names embedded in the types listed by RFC 4034 §6.2 (as
+/// amended by RFC 6840 §5.1) are lowercased.
+fn rdata(rdata: &RData) -> Vec<u8> {
+    let lower = |name: &String| name.to_ascii_lowercase();
+    let canonical = match rdata {
+        RData::NS(name) => RData::NS(lower(name)),
+        RData::CNAME(name) => RData::CNAME(lower(name)),
+        RData::PTR(name) => RData::PTR(lower(name)),
+        RData::DNAME(name) => RData::DNAME(lower(name)),
+        RData::SOA(soa) => RData::SOA(Soa {
+            mname: lower(&soa.mname),
+            rname: lower(&soa.rname),
+            ..soa.clone()
+        }),
+        RData::MX(mx) => RData::MX(Mx {
+            exchange: lower(&mx.exchange),
+            ..mx.clone()
+        }),
+        RData::SRV(srv) => RData::SRV(Srv {
+            target: lower(&srv.target),
+            ..srv.clone()
+        }),
+        RData::RRSIG(rrsig) => RData::RRSIG(Rrsig {
+            signer: lower(&rrsig.signer),
+            ..rrsig.clone()
+        }),
+        RData::SIG(sig) => RData::SIG(Rrsig {
+            signer: lower(&sig.signer),
+            ..sig.clone()
+        }),
+        RData::MINFO(minfo) => RData::MINFO(Minfo {
+            rmailbx: lower(&minfo.rmailbx),
+            emailbx: lower(&minfo.emailbx),
+        }),
+        RData::RP(rp) => RData::RP(Rp {
+            mbox: lower(&rp.mbox),
+            txt: lower(&rp.txt),
+        }),
+        RData::AFSDB(afsdb) => RData::AFSDB(Afsdb {
+            hostname: lower(&afsdb.hostname),
+            ..afsdb.clone()
+        }),
+        RData::NAPTR(naptr) => RData::NAPTR(Naptr {
+            replacement: lower(&naptr.replacement),
+            ..naptr.clone()
+        }),
+        RData::KX(kx) => RData::KX(Kx {
+            exchanger: lower(&kx.exchanger),
+            ..kx.clone()
+        }),
+        other => other.clone(),
+    };
+    let mut wire = Vec::new();
+    canonical.to_wire(&mut wire);
+    wire
+}
@@ -0,0 +1,344 @@
+//! Chains of trust from a trust anchor down to the zone of a name (RFC 4035 §5), built
+//! from captured responses.
+
+use super::errors::{BogusReason, ChainError};
+use super::{canonical, ds_matches, validate_rrset, Status};
+use crate::dns_records::rdata::{Dnskey, RData};
+use crate::dns_records::ResourceRecord;
+use crate::prelude::*;
+use crate::utils::dns_types::{DnsType, DnsTypes};
+use crate::DnsPacket;
+
+/// A zone whose DNSKEY RRset was authenticated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecureZone {
+    pub name: String,
+    /// The DNSKEY records of the zone.
+    pub keys: Vec<ResourceRecord>,
+}
+
+/// The zones authenticated from a trust anchor down, each delegating to the next with
+/// a signed DS RRset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrustChain {
+    pub zones: Vec<SecureZone>,
+}
+
+impl TrustChain {
+    /// The deepest authenticated zone.
+    pub fn zone(&self) -> &SecureZone {
+        self.zones
+            .last()
+            .expect("a chain starts at its trust anchor")
+    }
+
+    /// Validates `rrset` with one of `rrsigs` made by a key of the deepest zone of the
+    /// chain enclosing it.
+    pub fn validate_rrset(
+        &self,
+        rrset: &[ResourceRecord],
+        rrsigs: &[ResourceRecord],
+        now: u64,
+    ) -> Status {
+        let Some(first) = rrset.first() else {
+            return Status::Bogus(BogusReason::EmptyRrset);
+        };
+        let owner = canonical::name(&first.name);
+        let zone = self
+            .zones
+            .iter()
+            .rev()
+            .find(|zone| canonical::is_ancestor(&canonical::name(&zone.name), &owner));
+        let Some(zone) = zone else {
+            return Status::Bogus(BogusReason::MissingSignature);
+        };
+        match validate_signed(rrset, rrsigs, &zone.keys, now) {
+            Ok(()) => Status::Secure,
+            Err(reason) => Status::Bogus(reason),
+        }
+    }
+}
+
+/// Builds the chain of trust from `anchors` towards `name`, at `now` (seconds since the
+/// Unix epoch).
+///
+/// `anchors` are the DS or DNSKEY records trusted for one zone, usually the root. The
+/// records of all sections of `responses` are pooled: the chain authenticates the
+/// DNSKEY RRset of the anchor zone, then follows the signed DS RRsets of the zones
+/// enclosing `name`, and stops at the deepest zone whose DS RRset was captured. A DS
+/// RRset or DNSKEY RRset failing validation on the way is an error.
+pub fn build_chain(
+    anchors: &[ResourceRecord],
+    responses: &[DnsPacket],
+    name: &str,
+    now: u64,
+) -> Result<TrustChain, ChainError> {
+    let anchor = anchors.first().ok_or(ChainError::NoTrustAnchor)?;
+    let records: Vec<&ResourceRecord> = responses.iter().flat_map(DnsPacket::records).collect();
+    let target = canonical::name(name);
+
+    let mut zone = anchor.name.clone();
+    let mut trusted = anchors.to_vec();
+    let mut zones = Vec::new();
+    loop {
+        let keys = zone_keys(&zone, &trusted, &records, now)?;
+        zones.push(SecureZone {
+            name: zone.clone(),
+            keys: keys.clone(),
+        });
+
+        // The next delegation is the closest DS RRset below the zone, towards the name.
+        let zone_name = canonical::name(&zone);
+        let child = records
+            .iter()
+            .filter(|record| record.rtype == DnsTypes::DS)
+            .map(|record| (canonical::name(&record.name), record))
+            .filter(|(owner, _)| {
+                *owner != zone_name
+                    && canonical::is_ancestor(&zone_name, owner)
+                    && canonical::is_ancestor(owner, &target)
+            })
+            .min_by_key(|(owner, _)| canonical::labels(owner).len());
+        let Some((_, child)) = child else {
+            break;
+        };
+        let child = child.name.clone();
+        let ds = rrset(&records, &child, DnsTypes::DS);
+        let rrsigs = rrsigs(&records, &child, DnsTypes::DS);
+        validate_signed(&ds, &rrsigs, &keys, now).map_err(|reason| ChainError::Bogus {
+            owner: child.clone(),
+            rtype: DnsTypes::DS,
+            reason,
+        })?;
+        zone = child;
+        trusted = ds;
+    }
+    Ok(TrustChain { zones })
+}
+
+/// Authenticates the DNSKEY RRset of `zone` with a self-signature by a key `trusted`
+/// designates.
+fn zone_keys(
+    zone: &str,
+    trusted: &[ResourceRecord],
+    records: &[&ResourceRecord],
+    now: u64,
+) -> Result<Vec<ResourceRecord>, ChainError> {
+    let dnskeys = rrset(records, zone, DnsTypes::DNSKEY);
+    if dnskeys.is_empty() {
+        return Err(ChainError::MissingDnskey {
+            zone: zone.to_string(),
+        });
+    }
+    let entry_keys: Vec<ResourceRecord> = dnskeys
+        .iter()
+        .filter(|key| trusted.iter().any(|anchor| designates(anchor, key)))
+        .cloned()
+        .collect();
+    if entry_keys.is_empty() {
+        return Err(ChainError::NoMatchingKey {
+            zone: zone.to_string(),
+        });
+    }
+    let rrsigs = rrsigs(records, zone, DnsTypes::DNSKEY);
+    validate_signed(&dnskeys, &rrsigs, &entry_keys, now).map_err(|reason| ChainError::Bogus {
+        owner: zone.to_string(),
+        rtype: DnsTypes::DNSKEY,
+        reason,
+    })?;
+    Ok(dnskeys)
+}
+
+/// Whether the trusted DS or DNSKEY record `anchor` designates the unrevoked `key`.
+fn designates(anchor: &ResourceRecord, key: &ResourceRecord) -> bool {
+    let RData::DNSKEY(dnskey) = &key.rdata else {
+        return false;
+    };
+    if dnskey.flags & Dnskey::REVOKE != 0 {
+        return false;
+    }
+    match &anchor.rdata {
+        RData::DS(ds) => ds_matches(&key.name, dnskey, ds),
+        RData::DNSKEY(trusted) => trusted == dnskey,
+        _ => false,
+    }
+}
+
+/// Validates `rrset` with any of `rrsigs` made by one of `keys`.
+fn validate_signed(
+    rrset: &[ResourceRecord],
+    rrsigs: &[ResourceRecord],
+    keys: &[ResourceRecord],
+    now: u64,
+) -> Result<(), BogusReason> {
+    let mut reason = BogusReason::MissingSignature;
+    for rrsig in rrsigs {
+        let RData::RRSIG(signature) = &rrsig.rdata else {
+            continue;
+        };
+        for key in keys {
+            let RData::DNSKEY(dnskey) = &key.rdata else {
+                continue;
+            };
+            if dnskey.algorithm != signature.algorithm || dnskey.key_tag() != signature.key_tag {
+                continue;
+            }
+            match validate_rrset(rrset, rrsig, key, now) {
+                Status::Secure => return Ok(()),
+                Status::Bogus(bogus) => reason = bogus,
+            }
+        }
+    }
+    Err(reason)
+}
+
+/// The records of `owner` and `rtype`, without duplicates.
+fn rrset(records: &[&ResourceRecord], owner: &str, rtype: DnsType) -> Vec<ResourceRecord> {
+    let owner = canonical::name(owner);
+    let mut rrset: Vec<ResourceRecord> = Vec::new();
+    for record in records {
+        if record.rtype == rtype
+            && canonical::name(&record.name) == owner
+            && !rrset.iter().any(|known| known.rdata == record.rdata)
+        {
+            rrset.push((*record).clone());
+        }
+    }
+    rrset
+}
+
+/// The RRSIG records of `owner` covering `covered`.
+fn rrsigs(records: &[&ResourceRecord], owner: &str, covered: DnsType) -> Vec<ResourceRecord> {
+    rrset(records, owner, DnsTypes::RRSIG)
+        .into_iter()
+        .filter(
+            |record| matches!(&record.rdata, RData::RRSIG(rrsig) if rrsig.type_covered == covered),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::dns_class::DnsClasses;
+
+    const NOW: u64 = 1_705_000_000;
+
+    const ROOT_DS: &str =
+        "60795 15 2 649A3A39EE9C7C3D4BF3B879521AC8B76B05D8598FACA53604ADA9A4D423933A";
+
+    /// A signed delegation chain from the root to example.com with Ed25519 keys, and
+    /// an unsigned record.
+    const RECORDS: [(&str, DnsType, &str); 13] = [
+        (".", DnsTypes::DNSKEY, "257 3 15 iojj3XQJ8ZX9UtstPLpdcspnCb8dlBIb83SIAbQPb1w="),
+        (".", DnsTypes::RRSIG, "DNSKEY 15 0 3600 20240201000000 20240101000000 60795 . IGjfPlVXw6czSPRVNODRhl9iYPxyif5oHsUF7LjHY88SRs/JYSrqsMbmMYu9X5XE2s7tGeFlO4nd9uJJmpW9DQ=="),
+        ("com", DnsTypes::DS, "40119 15 2 5E522FC2FA2549B59FA573C3C7C0110D213F34205EE6F9A0BAA19DBF4F2D1E5F"),
+        ("com", DnsTypes::RRSIG, "DS 15 1 3600 20240201000000 20240101000000 60795 . ubEtDPkgowFFlGryrYgojVD7ZkRnwbMZrrlmVHu7mVr7YnVvWNbWapLtgLbSftSSw7ZlTxGRWFKy/sMOw3MGCg=="),
+        ("com", DnsTypes::DNSKEY, "257 3 15 gTl3Dqh9F19Wo1Rmw0x+zMuNipG07jeiXfYPW4/Js5Q="),
+        ("com", DnsTypes::RRSIG, "DNSKEY 15 1 3600 20240201000000 20240101000000 40119 com +uLJQN+7AR4kGI9XgFNa8n8pf73nGl6MSDm+ogsAkbhwpnGcNmMzSKO4tCdigcylatEDN/S55cEmSlGoda4EDg=="),
+        ("example.com", DnsTypes::DS, "8433 15 2 CFFB1C5AD3E4D05888204A11A531598F0B1D494642F888B806695A94329BDAE4"),
+        ("example.com", DnsTypes::RRSIG, "DS 15 2 3600 20240201000000 20240101000000 40119 com s5nGNhU/Hh4KdKIBFoImmBXTOmxuHXqd3PuCFevF10argj2LaL6MeHt0IXDq22aEJ/2DvtKyXO6pTlWQawhKAw=="),
+        ("example.com", DnsTypes::DNSKEY, "257 3 15 7UkoxijRwsbq6QM4kFmVYSlZJzpcY/k2NsFGFKyHN9E="),
+        ("example.com", DnsTypes::RRSIG, "DNSKEY 15 2 3600 20240201000000 20240101000000 8433 example.com HeAQv5GDR4Nqz+ROMdeMnD0ot5jCPRphPjKDihpyxJw1hCJl1NPumV/QFrPjCdBGB/ywpIOC6jj7CmsoRP+IAg=="),
+        ("www.example.com", DnsTypes::A, "192.0.2.1"),
+        ("www.example.com", DnsTypes::RRSIG, "A 15 3 3600 20240201000000 20240101000000 8433 example.com 20fXTaeS+zAbE07QLJNByQROjd0ahwogJUjNmz1TwQ7eQ88p1iCukGUS2ogp+lUC5C7vqI32iAj014NKEL2yCA=="),
+        ("www.example.com", DnsTypes::AAAA, "2001:db8::1"),
+    ];
+
+    fn record(name: &str, rtype: DnsType, rdata: &str) -> ResourceRecord {
+        ResourceRecord {
+            name: name.to_string(),
+            rtype,
+            rclass: DnsClasses::IN,
+            ttl: 3600,
+            data_length: 0,
+            rdata: RData::from_presentation(rtype, rdata).unwrap(),
+        }
+    }
+
+    /// One response per zone cut, as a resolver would have received them.
+    fn responses(skip: Option<usize>) -> Vec<DnsPacket> {
+        let empty = DnsPacket::try_from(&[0, 1, 0x81, 0x80, 0, 0, 0, 0, 0, 0, 0, 0][..]).unwrap();
+        RECORDS
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| Some(*i) != skip)
+            .map(|(_, &(name, rtype, rdata))| DnsPacket {
+                answers: Some(vec![record(name, rtype, rdata)]),
+                ..empty.clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_build_chain() {
+        let anchor = [record(".", DnsTypes::DS, ROOT_DS)];
+        let chain = build_chain(&anchor, &responses(None), "www.example.com", NOW).unwrap();
+        let zones: Vec<&str> = chain.zones.iter().map(|zone| zone.name.as_str()).collect();
+        assert_eq!(zones, [".", "com", "example.com"]);
+
+        let a = [record("www.example.com", DnsTypes::A, "192.0.2.1")];
+        let rrsig = [record("www.example.com", DnsTypes::RRSIG, RECORDS[11].2)];
+        assert!(chain.validate_rrset(&a, &rrsig, NOW).is_secure());
+        let aaaa = [record("www.example.com", DnsTypes::AAAA, "2001:db8::1")];
+        assert_eq!(
+            chain.validate_rrset(&aaaa, &rrsig, NOW),
+            Status::Bogus(BogusReason::TypeMismatch {
+                covered: DnsTypes::A,
+                rrset: DnsTypes::AAAA
+            })
+        );
+        assert_eq!(
+            chain.validate_rrset(&aaaa, &[], NOW),
+            Status::Bogus(BogusReason::MissingSignature)
+        );
+
+        // A DNSKEY anchor works as well, and the chain stops above names not delegated.
+        let anchor = [record(".", DnsTypes::DNSKEY, RECORDS[0].2)];
+        let chain = build_chain(&anchor, &responses(None), "www.example.org", NOW).unwrap();
+        assert_eq!(chain.zone().name, ".");
+    }
+
+    #[test]
+    fn test_build_chain_broken() {
+        let anchor = [record(".", DnsTypes::DS, ROOT_DS)];
+        assert_eq!(
+            build_chain(&[], &responses(None), "www.example.com", NOW),
+            Err(ChainError::NoTrustAnchor)
+        );
+        assert_eq!(
+            build_chain(&anchor, &responses(Some(4)), "www.example.com", NOW),
+            Err(ChainError::MissingDnskey {
+                zone: "com".to_string()
+            })
+        );
+        assert!(matches!(
+            build_chain(&anchor, &responses(Some(7)), "www.example.com", NOW),
+            Err(ChainError::Bogus {
+                rtype: DnsTypes::DS,
+                reason: BogusReason::MissingSignature,
+                ..
+            })
+        ));
+        assert!(matches!(
+            build_chain(&anchor, &responses(None), "www.example.com", 1_706_745_601),
+            Err(ChainError::Bogus {
+                rtype: DnsTypes::DNSKEY,
+                reason: BogusReason::Expired { .. },
+                ..
+            })
+        ));
+
+        let wrong_anchor = [record(
+            ".",
+            DnsTypes::DS,
+            "60795 15 2 0000000000000000000000000000000000000000000000000000000000000000",
+        )];
+        assert_eq!(
+            build_chain(&wrong_anchor, &responses(None), "www.example.com", NOW),
+            Err(ChainError::NoMatchingKey {
+                zone: ".".to_string()
+            })
+        );
+    }
+}
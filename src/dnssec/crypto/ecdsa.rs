@@ -0,0 +1,50 @@
+//! ECDSA verification on P-256 and P-384, with keys in the DNSKEY format of RFC 6605: the
+//! uncompressed point `x | y`, without a prefix byte.
+
+use p256::ecdsa::signature::Verifier;
+
+use crate::dnssec::errors::BogusReason;
+use crate::prelude::*;
+
+/// The SEC1 encoding of an uncompressed point `x | y`, checking that it has `size` bytes.
+fn sec1_point(public_key: &[u8], size: usize) -> Result<Vec<u8>, BogusReason> {
+    if public_key.len() != size {
+        return Err(BogusReason::InvalidKey);
+    }
+    let mut point = Vec::with_capacity(1 + size);
+    point.push(0x04);
+    point.extend_from_slice(public_key);
+    Ok(point)
+}
+
+/// Verifies an ECDSA P-256 signature `r | s` over `data`, hashed with SHA-256.
+pub(crate) fn verify_p256(
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> Result<(), BogusReason> {
+    use p256::ecdsa::{Signature, VerifyingKey};
+
+    let point = sec1_point(public_key, 64)?;
+    let key = VerifyingKey::from_sec1_bytes(&point).map_err(|_| BogusReason::InvalidKey)?;
+    let signature =
+        Signature::from_slice(signature).map_err(|_| BogusReason::MalformedSignature)?;
+    key.verify(data, &signature)
+        .map_err(|_| BogusReason::SignatureMismatch)
+}
+
+/// Verifies an ECDSA P-384 signature `r | s` over `data`, hashed with SHA-384.
+pub(crate) fn verify_p384(
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> Result<(), BogusReason> {
+    use p384::ecdsa::{Signature, VerifyingKey};
+
+    let point = sec1_point(public_key, 96)?;
+    let key = VerifyingKey::from_sec1_bytes(&point).map_err(|_| BogusReason::InvalidKey)?;
+    let signature =
+        Signature::from_slice(signature).map_err(|_| BogusReason::MalformedSignature)?;
+    key.verify(data, &signature)
+        .map_err(|_| BogusReason::SignatureMismatch)
+}
@@ -0,0 +1,18 @@
+//! Ed25519 verification (RFC 8032), with the 32-byte public keys of RFC 8080.
+
+use ed25519_dalek::{Signature, VerifyingKey};
+
+use crate::dnssec::errors::BogusReason;
+
+pub(crate) fn verify(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), BogusReason> {
+    let public_key = public_key.try_into().map_err(|_| BogusReason::InvalidKey)?;
+    let key = VerifyingKey::from_bytes(public_key).map_err(|_| BogusReason::InvalidKey)?;
+    let signature =
+        Signature::from_slice(signature).map_err(|_| BogusReason::MalformedSignature)?;
+    key.verify_strict(message, &signature)
+        .map_err(|_| BogusReason::SignatureMismatch)
+}
@@ -0,0 +1,27 @@
+//! The signature algorithms of DNSSEC, verified with the RustCrypto implementations.
+
+use sha2::{Sha256, Sha512};
+
+use super::errors::BogusReason;
+use super::Algorithms;
+
+mod ecdsa;
+mod ed25519;
+mod rsa;
+
+/// Verifies `signature` over `data` with a DNSKEY public key of `algorithm`.
+pub(crate) fn verify(
+    algorithm: u8,
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> Result<(), BogusReason> {
+    match algorithm {
+        Algorithms::RSASHA256 => rsa::verify::<Sha256>(public_key, data, signature),
+        Algorithms::RSASHA512 => rsa::verify::<Sha512>(public_key, data, signature),
+        Algorithms::ECDSAP256SHA256 => ecdsa::verify_p256(public_key, data, signature),
+        Algorithms::ECDSAP384SHA384 => ecdsa::verify_p384(public_key, data, signature),
+        Algorithms::ED25519 => ed25519::verify(public_key, data, signature),
+        _ => Err(BogusReason::UnsupportedAlgorithm(algorithm)),
+    }
+}
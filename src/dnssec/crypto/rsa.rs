@@ -0,0 +1,88 @@
+//! RSASSA-PKCS1-v1_5 verification (RFC 8017 §8.2.2) with keys in the DNSKEY format of
+//! RFC 3110.
+
+use rsa::{BigUint, Pkcs1v15Sign, RsaPublicKey};
+use sha2::digest::const_oid::AssociatedOid;
+use sha2::Digest;
+
+use crate::dnssec::errors::BogusReason;
+
+/// The smallest and largest moduli of RSA DNSKEYs (RFC 3110 §2, RFC 5702 §2).
+const MODULUS_BITS: core::ops::RangeInclusive<usize> = 512..=4096;
+
+/// The longest exponent accepted: the cost of verifying grows with its size, and the
+/// exponents in use (3, 65537) fit in 4 bytes.
+const MAX_EXPONENT_LEN: usize = 4;
+
+/// Verifies the RSASSA-PKCS1-v1_5 `signature` over `data`, hashed with `D`.
+pub(crate) fn verify<D: Digest + AssociatedOid>(
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> Result<(), BogusReason> {
+    let (exponent, modulus) = split_key(public_key).ok_or(BogusReason::InvalidKey)?;
+    if signature.len() != modulus.len() {
+        return Err(BogusReason::MalformedSignature);
+    }
+    let key = RsaPublicKey::new(
+        BigUint::from_bytes_be(modulus),
+        BigUint::from_bytes_be(exponent),
+    )
+    .map_err(|_| BogusReason::InvalidKey)?;
+    key.verify(Pkcs1v15Sign::new::<D>(), &D::digest(data), signature)
+        .map_err(|_| BogusReason::SignatureMismatch)
+}
+
+/// Splits a DNSKEY RSA public key into its exponent and modulus (RFC 3110 §2), checking
+/// their sizes.
+fn split_key(public_key: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (&first, rest) = public_key.split_first()?;
+    let (length, rest) = match first {
+        0 => {
+            let (length, rest) = rest.split_at_checked(2)?;
+            (u16::from_be_bytes([length[0], length[1]]) as usize, rest)
+        }
+        length => (length as usize, rest),
+    };
+    let (exponent, modulus) = rest.split_at_checked(length)?;
+    let &first = modulus.first().filter(|&&byte| byte != 0)?;
+    let modulus_bits = modulus.len() * 8 - first.leading_zeros() as usize;
+    let valid = !exponent.is_empty()
+        && exponent.len() <= MAX_EXPONENT_LEN.min(modulus.len())
+        && MODULUS_BITS.contains(&modulus_bits);
+    valid.then_some((exponent, modulus))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A DNSKEY public key with `exponent` and a modulus of `bits` bits.
+    fn key(exponent: &[u8], bits: usize) -> Vec<u8> {
+        let mut key = vec![exponent.len() as u8];
+        key.extend_from_slice(exponent);
+        let mut modulus = vec![0xFF; bits.div_ceil(8)];
+        modulus[0] >>= (8 - bits % 8) % 8;
+        key.extend_from_slice(&modulus);
+        key
+    }
+
+    #[test]
+    fn test_key_size_bounds() {
+        let verify = |key: &[u8]| verify::<sha2::Sha256>(key, b"data", &[0; 1]);
+        // Keys within the bounds only fail on the signature length.
+        for bits in [512, 1024, 4096] {
+            assert_eq!(
+                verify(&key(&[1, 0, 1], bits)),
+                Err(BogusReason::MalformedSignature)
+            );
+        }
+        assert_eq!(verify(&key(&[1, 0, 1], 511)), Err(BogusReason::InvalidKey));
+        assert_eq!(verify(&key(&[1, 0, 1], 4097)), Err(BogusReason::InvalidKey));
+        assert_eq!(
+            verify(&key(&[1, 0, 0, 0, 1], 2048)),
+            Err(BogusReason::InvalidKey)
+        );
+        assert_eq!(verify(&key(&[], 2048)), Err(BogusReason::InvalidKey));
+    }
+}
@@ -0,0 +1,566 @@
+//! Authenticated denial of existence: checking that the NSEC (RFC 4035 §5.4) or NSEC3
+//! (RFC 5155 §8) records of a response prove that a name or type does not exist.
+
+use super::canonical;
+use super::errors::DenialError;
+use crate::dns_records::rdata::{Nsec, Nsec3, RData};
+use crate::dns_records::ResourceRecord;
+use crate::prelude::*;
+use crate::utils::dns_types::{DnsType, DnsTypes};
+use crate::utils::encoding::base32hex_decode;
+use alloc::collections::BTreeSet;
+use core::cmp::Ordering;
+use sha1::{Digest, Sha1};
+
+/// The only NSEC3 hash algorithm, SHA-1.
+const SHA1: u8 = 1;
+/// Iteration count above which NSEC3 records are not hashed (RFC 9276 §3.2), each proof
+/// costing a few hashes per label of the queried name.
+const MAX_ITERATIONS: u16 = 150;
+
+/// What a set of NSEC or NSEC3 records proves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Denial {
+    /// The name exists, without records of the type (NODATA).
+    NoData,
+    /// The name does not exist, and no wildcard could have answered (NXDOMAIN).
+    NameError,
+    /// The name does not exist, and the wildcard that would have answered has no records
+    /// of the type.
+    WildcardNoData,
+    /// The name is covered by an Opt-Out NSEC3 span: it may be an unsigned delegation, so
+    /// the denial is insecure.
+    OptOut,
+}
+
+/// Hashes `name` as NSEC3 does (RFC 5155 §5): SHA-1 over the canonical name and the
+/// salt, iterated `iterations` more times over the digest and the salt.
+pub fn nsec3_hash(name: &str, salt: &[u8], iterations: u16) -> [u8; 20] {
+    hash(&canonical::name(name), salt, iterations)
+}
+
+fn hash(name: &[u8], salt: &[u8], iterations: u16) -> [u8; 20] {
+    let mut digest = Sha1::new().chain_update(name).chain_update(salt).finalize();
+    for _ in 0..iterations {
+        digest = Sha1::new()
+            .chain_update(digest)
+            .chain_update(salt)
+            .finalize();
+    }
+    digest.into()
+}
+
+/// Determines what the NSEC or NSEC3 records among `records`, e.g. the authority section
+/// of a response, prove about `qname` and `qtype`.
+///
+/// NSEC3 records are used when there are any. Their signatures are not checked here: the
+/// NSEC or NSEC3 RRsets must be validated on their own, e.g. with
+/// `TrustChain::validate_rrset`. The caller compares the result with the response code:
+/// an NXDOMAIN response needs `NameError` (or `OptOut`), a NODATA one `NoData` or
+/// `WildcardNoData`.
+pub fn verify_denial(
+    qname: &str,
+    qtype: DnsType,
+    records: &[ResourceRecord],
+) -> Result<Denial, DenialError> {
+    let qname = canonical::name(qname);
+    let nsec3s: Vec<(&ResourceRecord, &Nsec3)> = records
+        .iter()
+        .filter_map(|record| match &record.rdata {
+            RData::NSEC3(nsec3) => Some((record, nsec3)),
+            _ => None,
+        })
+        .collect();
+    if !nsec3s.is_empty() {
+        return Nsec3Chain::new(&nsec3s)?.verify(&qname, qtype);
+    }
+
+    let nsecs: Vec<NsecSpan> = records
+        .iter()
+        .filter_map(|record| match &record.rdata {
+            RData::NSEC(nsec) => Some(NsecSpan {
+                owner: canonical::name(&record.name),
+                next: canonical::name(&nsec.next_domain),
+                nsec,
+            }),
+            _ => None,
+        })
+        .collect();
+    if nsecs.is_empty() {
+        return Err(DenialError::NoRecords);
+    }
+    verify_nsec(&nsecs, &qname, qtype)
+}
+
+/// Checks that the types at `name` do not answer `qtype`.
+fn check_types(name: &[u8], types: &BTreeSet<DnsType>, qtype: DnsType) -> Result<(), DenialError> {
+    for rtype in [qtype, DnsTypes::CNAME] {
+        if types.contains(&rtype) {
+            return Err(DenialError::TypeExists {
+                name: canonical::display(name),
+                rtype,
+            });
+        }
+    }
+    // The parent side of a delegation proves nothing about the child zone's data.
+    if qtype != DnsTypes::DS && types.contains(&DnsTypes::NS) && !types.contains(&DnsTypes::SOA) {
+        return Err(DenialError::Delegation {
+            name: canonical::display(name),
+        });
+    }
+    Ok(())
+}
+
+/// The wildcard name `*.<name>`.
+fn wildcard(name: &[u8]) -> Vec<u8> {
+    [&[1, b'*'][..], name].concat()
+}
+
+struct NsecSpan<'a> {
+    owner: Vec<u8>,
+    next: Vec<u8>,
+    nsec: &'a Nsec,
+}
+
+impl NsecSpan<'_> {
+    /// Whether `name` sorts strictly between the owner and the next name, the last NSEC
+    /// of the zone wrapping around to its apex.
+    fn covers(&self, name: &[u8]) -> bool {
+        let after_owner = canonical::compare(&self.owner, name) == Ordering::Less;
+        let before_next = canonical::compare(name, &self.next) == Ordering::Less;
+        match canonical::compare(&self.owner, &self.next) {
+            Ordering::Less => after_owner && before_next,
+            _ => after_owner || before_next,
+        }
+    }
+}
+
+fn verify_nsec(nsecs: &[NsecSpan], qname: &[u8], qtype: DnsType) -> Result<Denial, DenialError> {
+    if let Some(span) = nsecs.iter().find(|span| span.owner == qname) {
+        check_types(qname, &span.nsec.types, qtype)?;
+        return Ok(Denial::NoData);
+    }
+    let covering = nsecs
+        .iter()
+        .find(|span| span.covers(qname))
+        .ok_or_else(|| DenialError::NotCovered {
+            name: canonical::display(qname),
+        })?;
+
+    // The closest encloser is the longest ancestor shared with either end of the span.
+    let shared = |name: &[u8]| {
+        let (name, qname) = (canonical::labels(name), canonical::labels(qname));
+        name.iter()
+            .rev()
+            .zip(qname.iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count()
+    };
+    let shared = shared(&covering.owner).max(shared(&covering.next));
+    let labels = canonical::labels(qname).len();
+    let wildcard = wildcard(canonical::ancestor(qname, labels - shared));
+
+    if let Some(span) = nsecs.iter().find(|span| span.owner == wildcard) {
+        check_types(&wildcard, &span.nsec.types, qtype)?;
+        return Ok(Denial::WildcardNoData);
+    }
+    match nsecs.iter().any(|span| span.covers(&wildcard)) {
+        true => Ok(Denial::NameError),
+        false => Err(DenialError::NotCovered {
+            name: canonical::display(&wildcard),
+        }),
+    }
+}
+
+struct Nsec3Span<'a> {
+    owner_hash: Vec<u8>,
+    nsec3: &'a Nsec3,
+}
+
+/// The NSEC3 records of one zone, with the same hash parameters.
+struct Nsec3Chain<'a> {
+    zone: Vec<u8>,
+    salt: &'a [u8],
+    iterations: u16,
+    spans: Vec<Nsec3Span<'a>>,
+}
+
+impl<'a> Nsec3Chain<'a> {
+    /// Keeps the records sharing the zone and hash parameters of the first usable one,
+    /// as RFC 5155 §8.2 asks.
+    fn new(records: &[(&ResourceRecord, &'a Nsec3)]) -> Result<Self, DenialError> {
+        let mut chain: Option<Nsec3Chain> = None;
+        for &(record, nsec3) in records {
+            if nsec3.hash_algorithm != SHA1 {
+                continue;
+            }
+            let owner = canonical::name(&record.name);
+            let Some(&label) = canonical::labels(&owner).first() else {
+                continue;
+            };
+            let Some(owner_hash) = core::str::from_utf8(label).ok().and_then(base32hex_decode)
+            else {
+                continue;
+            };
+            let zone = canonical::ancestor(&owner, 1);
+            let chain = chain.get_or_insert_with(|| Nsec3Chain {
+                zone: zone.to_vec(),
+                salt: &nsec3.salt,
+                iterations: nsec3.iterations,
+                spans: Vec::new(),
+            });
+            if chain.zone == zone
+                && chain.salt == nsec3.salt
+                && chain.iterations == nsec3.iterations
+            {
+                chain.spans.push(Nsec3Span { owner_hash, nsec3 });
+            }
+        }
+        let chain = chain.ok_or_else(|| {
+            records
+                .iter()
+                .map(|(_, nsec3)| nsec3.hash_algorithm)
+                .find(|&algorithm| algorithm != SHA1)
+                .map_or(
+                    DenialError::NoRecords,
+                    DenialError::UnsupportedHashAlgorithm,
+                )
+        })?;
+        if chain.iterations > MAX_ITERATIONS {
+            return Err(DenialError::TooManyIterations(chain.iterations));
+        }
+        Ok(chain)
+    }
+
+    fn matching(&self, name: &[u8]) -> Option<&Nsec3Span<'a>> {
+        let hash = hash(name, self.salt, self.iterations);
+        self.spans.iter().find(|span| span.owner_hash == hash)
+    }
+
+    fn covering(&self, name: &[u8]) -> Option<&Nsec3Span<'a>> {
+        let hash = hash(name, self.salt, self.iterations);
+        let hash = &hash[..];
+        self.spans.iter().find(|span| {
+            let (owner, next) = (&span.owner_hash[..], &span.nsec3.next_hashed_owner[..]);
+            match owner < next {
+                true => owner < hash && hash < next,
+                false => owner < hash || hash < next,
+            }
+        })
+    }
+
+    fn verify(&self, qname: &[u8], qtype: DnsType) -> Result<Denial, DenialError> {
+        if let Some(span) = self.matching(qname) {
+            check_types(qname, &span.nsec3.types, qtype)?;
+            return Ok(Denial::NoData);
+        }
+
+        // The closest encloser proof (RFC 5155 §8.3): the closest ancestor with a
+        // matching NSEC3, and an NSEC3 covering the next closer name below it.
+        let labels = canonical::labels(qname).len();
+        let zone_labels = canonical::labels(&self.zone).len();
+        let (closest_encloser, next_closer) = (1..=labels.saturating_sub(zone_labels))
+            .map(|count| {
+                (
+                    canonical::ancestor(qname, count),
+                    canonical::ancestor(qname, count - 1),
+                )
+            })
+            .find(|(ancestor, _)| self.matching(ancestor).is_some())
+            .ok_or_else(|| DenialError::NoClosestEncloser {
+                name: canonical::display(qname),
+            })?;
+        let covering = self
+            .covering(next_closer)
+            .ok_or_else(|| DenialError::NotCovered {
+                name: canonical::display(next_closer),
+            })?;
+        if covering.nsec3.opt_out() {
+            return Ok(Denial::OptOut);
+        }
+
+        let wildcard = wildcard(closest_encloser);
+        if let Some(span) = self.matching(&wildcard) {
+            check_types(&wildcard, &span.nsec3.types, qtype)?;
+            return Ok(Denial::WildcardNoData);
+        }
+        match self.covering(&wildcard) {
+            Some(_) => Ok(Denial::NameError),
+            None => Err(DenialError::NotCovered {
+                name: canonical::display(&wildcard),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::dns_class::DnsClasses;
+
+    /// The NSEC3 chain of the example zone of RFC 5155 Appendix A.
+    const NSEC3_CHAIN: [(&str, &str); 11] = [
+        (
+            "0p9mhaveqvm6t7vbl5lop2u3t2rp3tom.example",
+            "1 1 12 aabbccdd 2t7b4g4vsa5smi47k61mv5bv1a22bojr NS SOA MX RRSIG DNSKEY NSEC3PARAM",
+        ),
+        (
+            "2t7b4g4vsa5smi47k61mv5bv1a22bojr.example",
+            "1 1 12 aabbccdd 2vptu5timamqttgl4luu9kg21e0aor3s A RRSIG",
+        ),
+        (
+            "2vptu5timamqttgl4luu9kg21e0aor3s.example",
+            "1 1 12 aabbccdd 35mthgpgcu1qg68fab165klnsnk3dpvl MX RRSIG",
+        ),
+        (
+            "35mthgpgcu1qg68fab165klnsnk3dpvl.example",
+            "1 1 12 aabbccdd b4um86eghhds6nea196smvmlo4ors995 NS DS RRSIG",
+        ),
+        (
+            "b4um86eghhds6nea196smvmlo4ors995.example",
+            "1 1 12 aabbccdd gjeqe526plbf1g8mklp59enfd789njgi MX RRSIG",
+        ),
+        (
+            "gjeqe526plbf1g8mklp59enfd789njgi.example",
+            "1 1 12 aabbccdd ji6neoaepv8b5o6k4ev33abha8ht9fgc A HINFO AAAA RRSIG",
+        ),
+        (
+            "ji6neoaepv8b5o6k4ev33abha8ht9fgc.example",
+            "1 1 12 aabbccdd k8udemvp1j2f7eg6jebps17vp3n8i58h",
+        ),
+        (
+            "k8udemvp1j2f7eg6jebps17vp3n8i58h.example",
+            "1 1 12 aabbccdd q04jkcevqvmu85r014c7dkba38o0ji5r",
+        ),
+        (
+            "q04jkcevqvmu85r014c7dkba38o0ji5r.example",
+            "1 1 12 aabbccdd r53bq7cc2uvmubfu5ocmm6pers9tk9en A RRSIG",
+        ),
+        (
+            "r53bq7cc2uvmubfu5ocmm6pers9tk9en.example",
+            "1 1 12 aabbccdd t644ebqk9bibcna874givr6joj62mlhv MX RRSIG",
+        ),
+        (
+            "t644ebqk9bibcna874givr6joj62mlhv.example",
+            "1 1 12 aabbccdd 0p9mhaveqvm6t7vbl5lop2u3t2rp3tom A HINFO AAAA RRSIG",
+        ),
+    ];
+
+    /// The same zone signed with NSEC, b.example being an unsigned delegation.
+    const NSEC_CHAIN: [(&str, &str); 10] = [
+        ("example", "a.example NS SOA MX RRSIG NSEC DNSKEY"),
+        ("a.example", "ai.example NS DS RRSIG NSEC"),
+        ("ai.example", "b.example A HINFO AAAA RRSIG NSEC"),
+        ("b.example", "ns1.example NS RRSIG NSEC"),
+        ("ns1.example", "ns2.example A RRSIG NSEC"),
+        ("ns2.example", "*.w.example A RRSIG NSEC"),
+        ("*.w.example", "x.w.example MX RRSIG NSEC"),
+        ("x.w.example", "x.y.w.example MX RRSIG NSEC"),
+        ("x.y.w.example", "xx.example MX RRSIG NSEC"),
+        ("xx.example", "example A HINFO AAAA RRSIG NSEC"),
+    ];
+
+    fn records(chain: &[(&str, &str)], rtype: DnsType) -> Vec<ResourceRecord> {
+        chain
+            .iter()
+            .map(|&(name, rdata)| ResourceRecord {
+                name: name.to_string(),
+                rtype,
+                rclass: DnsClasses::IN,
+                ttl: 3600,
+                data_length: 0,
+                rdata: RData::from_presentation(rtype, rdata).unwrap(),
+            })
+            .collect()
+    }
+
+    /// The NSEC3 chain with the Opt-Out flag cleared.
+    fn nsec3_without_opt_out() -> Vec<ResourceRecord> {
+        let chain: Vec<(&str, String)> = NSEC3_CHAIN
+            .iter()
+            .map(|&(name, rdata)| (name, rdata.replacen("1 1 ", "1 0 ", 1)))
+            .collect();
+        let chain: Vec<(&str, &str)> = chain
+            .iter()
+            .map(|(name, rdata)| (*name, rdata.as_str()))
+            .collect();
+        records(&chain, DnsTypes::NSEC3)
+    }
+
+    #[test]
+    fn test_nsec3_hash() {
+        // RFC 5155 Appendix A.
+        let salt = hex::decode("aabbccdd").unwrap();
+        let hashed = |name| base32hex_decode(name).unwrap();
+        assert_eq!(
+            nsec3_hash("example", &salt, 12).to_vec(),
+            hashed("0p9mhaveqvm6t7vbl5lop2u3t2rp3tom")
+        );
+        assert_eq!(
+            nsec3_hash("*.W.Example.", &salt, 12).to_vec(),
+            hashed("r53bq7cc2uvmubfu5ocmm6pers9tk9en")
+        );
+    }
+
+    #[test]
+    fn test_verify_nsec3_denial() {
+        let opt_out = records(&NSEC3_CHAIN, DnsTypes::NSEC3);
+        let signed = nsec3_without_opt_out();
+
+        // Name errors (RFC 5155 Appendix B.1), insecure when the span is Opt-Out.
+        assert_eq!(
+            verify_denial("a.c.x.w.example", DnsTypes::A, &signed),
+            Ok(Denial::NameError)
+        );
+        assert_eq!(
+            verify_denial("a.c.x.w.example", DnsTypes::A, &opt_out),
+            Ok(Denial::OptOut)
+        );
+        // No data, also at an empty non-terminal (B.2).
+        assert_eq!(
+            verify_denial("ns1.example", DnsTypes::MX, &opt_out),
+            Ok(Denial::NoData)
+        );
+        assert_eq!(
+            verify_denial("y.w.example", DnsTypes::A, &opt_out),
+            Ok(Denial::NoData)
+        );
+        // A wildcard without the type (B.5).
+        assert_eq!(
+            verify_denial("a.z.w.example", DnsTypes::AAAA, &signed),
+            Ok(Denial::WildcardNoData)
+        );
+        // The DS of an unsigned delegation in an Opt-Out span (B.3).
+        assert_eq!(
+            verify_denial("b.example", DnsTypes::DS, &opt_out),
+            Ok(Denial::OptOut)
+        );
+
+        assert_eq!(
+            verify_denial("ns1.example", DnsTypes::A, &opt_out),
+            Err(DenialError::TypeExists {
+                name: "ns1.example".to_string(),
+                rtype: DnsTypes::A
+            })
+        );
+        assert_eq!(
+            verify_denial("a.z.w.example", DnsTypes::MX, &signed),
+            Err(DenialError::TypeExists {
+                name: "*.w.example".to_string(),
+                rtype: DnsTypes::MX
+            })
+        );
+        assert_eq!(
+            verify_denial("a.example", DnsTypes::A, &opt_out),
+            Err(DenialError::Delegation {
+                name: "a.example".to_string()
+            })
+        );
+        // Only the closest encloser x.w.example and the span covering c.x.w.example.
+        assert_eq!(
+            verify_denial(
+                "a.c.x.w.example",
+                DnsTypes::A,
+                &[signed[0].clone(), signed[4].clone()]
+            ),
+            Err(DenialError::NotCovered {
+                name: "*.x.w.example".to_string()
+            })
+        );
+        assert_eq!(
+            verify_denial("a.c.x.w.example", DnsTypes::A, &signed[1..2]),
+            Err(DenialError::NoClosestEncloser {
+                name: "a.c.x.w.example".to_string()
+            })
+        );
+
+        let mut sha256 = opt_out.clone();
+        for record in &mut sha256 {
+            if let RData::NSEC3(nsec3) = &mut record.rdata {
+                nsec3.hash_algorithm = 2;
+            }
+        }
+        assert_eq!(
+            verify_denial("a.c.x.w.example", DnsTypes::A, &sha256),
+            Err(DenialError::UnsupportedHashAlgorithm(2))
+        );
+        assert_eq!(
+            verify_denial("a.c.x.w.example", DnsTypes::A, &[]),
+            Err(DenialError::NoRecords)
+        );
+
+        let mut expensive = opt_out.clone();
+        for record in &mut expensive {
+            if let RData::NSEC3(nsec3) = &mut record.rdata {
+                nsec3.iterations = 151;
+            }
+        }
+        assert_eq!(
+            verify_denial("a.c.x.w.example", DnsTypes::A, &expensive),
+            Err(DenialError::TooManyIterations(151))
+        );
+    }
+
+    #[test]
+    fn test_verify_nsec_denial() {
+        let nsecs = records(&NSEC_CHAIN, DnsTypes::NSEC);
+
+        assert_eq!(
+            verify_denial("a.c.x.w.example", DnsTypes::A, &nsecs),
+            Ok(Denial::NameError)
+        );
+        // The wildcard *.example sorts before a.example, past the apex.
+        assert_eq!(
+            verify_denial("zzz.example", DnsTypes::A, &nsecs[9..]),
+            Err(DenialError::NotCovered {
+                name: "*.example".to_string()
+            })
+        );
+        assert_eq!(
+            verify_denial(
+                "zzz.example",
+                DnsTypes::A,
+                &[nsecs[9].clone(), nsecs[0].clone()]
+            ),
+            Ok(Denial::NameError)
+        );
+        assert_eq!(
+            verify_denial("ns1.example", DnsTypes::MX, &nsecs),
+            Ok(Denial::NoData)
+        );
+        assert_eq!(
+            verify_denial("a.z.w.example", DnsTypes::AAAA, &nsecs),
+            Ok(Denial::WildcardNoData)
+        );
+        assert_eq!(
+            verify_denial("b.example", DnsTypes::DS, &nsecs),
+            Ok(Denial::NoData)
+        );
+
+        assert_eq!(
+            verify_denial("a.z.w.example", DnsTypes::MX, &nsecs),
+            Err(DenialError::TypeExists {
+                name: "*.w.example".to_string(),
+                rtype: DnsTypes::MX
+            })
+        );
+        assert_eq!(
+            verify_denial("b.example", DnsTypes::A, &nsecs),
+            Err(DenialError::Delegation {
+                name: "b.example".to_string()
+            })
+        );
+        assert_eq!(
+            verify_denial("a.z.w.example", DnsTypes::AAAA, &nsecs[8..9]),
+            Err(DenialError::NotCovered {
+                name: "*.w.example".to_string()
+            })
+        );
+        assert_eq!(
+            verify_denial("c.example", DnsTypes::A, &nsecs[4..]),
+            Err(DenialError::NotCovered {
+                name: "c.example".to_string()
+            })
+        );
+    }
+}
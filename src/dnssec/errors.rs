@@ -0,0 +1,92 @@
+use thiserror::Error;
+
+use crate::dns_records::rdata::Timestamp;
+use crate::prelude::*;
+use crate::utils::dns_types::DnsType;
+
+/// Why an RRset failed validation.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BogusReason {
+    #[error("The RRset is empty")]
+    EmptyRrset,
+    #[error("The records do not share the same owner, type and class")]
+    MixedRrset,
+    #[error("Expected a {expected} record, got {actual}")]
+    UnexpectedRecord { expected: DnsType, actual: DnsType },
+    #[error("The RRSIG covers {covered}, not {rrset}")]
+    TypeMismatch { covered: DnsType, rrset: DnsType },
+    #[error("The RRSIG is owned by {rrsig}, not by the RRset owner {rrset}")]
+    OwnerMismatch { rrsig: String, rrset: String },
+    #[error("The RRSIG class differs from the RRset class")]
+    ClassMismatch,
+    #[error("The signer {signer} is not the owner {owner} or one of its ancestors")]
+    SignerNotAncestor { signer: String, owner: String },
+    #[error("The signer {signer} does not own the DNSKEY, owned by {key_owner}")]
+    SignerMismatch { signer: String, key_owner: String },
+    #[error("The RRSIG labels field {labels} exceeds the {owner_labels} labels of the owner")]
+    LabelCount { labels: u8, owner_labels: usize },
+    #[error("The RRSIG key tag {rrsig} does not match the DNSKEY key tag {dnskey}")]
+    KeyTagMismatch { rrsig: u16, dnskey: u16 },
+    #[error("The RRSIG algorithm {rrsig} does not match the DNSKEY algorithm {dnskey}")]
+    AlgorithmMismatch { rrsig: u8, dnskey: u8 },
+    #[error("The DNSKEY is not a zone key")]
+    NotZoneKey,
+    #[error("The DNSKEY protocol is {0}, not 3")]
+    InvalidProtocol(u8),
+    #[error("The signature expired at {expiration} (now {now})")]
+    Expired {
+        expiration: Timestamp,
+        now: Timestamp,
+    },
+    #[error("The signature is not valid before {inception} (now {now})")]
+    NotYetValid {
+        inception: Timestamp,
+        now: Timestamp,
+    },
+    #[error("Unsupported DNSSEC algorithm {0}")]
+    UnsupportedAlgorithm(u8),
+    #[error("The DNSKEY public key is malformed")]
+    InvalidKey,
+    #[error("The signature is malformed")]
+    MalformedSignature,
+    #[error("The signature does not match the RRset")]
+    SignatureMismatch,
+    #[error("No RRSIG made by a trusted key covers the RRset")]
+    MissingSignature,
+}
+
+/// Why no chain of trust could be built.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ChainError {
+    #[error("No trust anchor was given")]
+    NoTrustAnchor,
+    #[error("No DNSKEY records of {zone} were captured")]
+    MissingDnskey { zone: String },
+    #[error("No DNSKEY of {zone} is designated by a trusted DS or DNSKEY record")]
+    NoMatchingKey { zone: String },
+    #[error("The {rtype} RRset of {owner} is bogus: {reason}")]
+    Bogus {
+        owner: String,
+        rtype: DnsType,
+        reason: BogusReason,
+    },
+}
+
+/// Why NSEC or NSEC3 records fail to prove a denial of existence.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DenialError {
+    #[error("No NSEC or NSEC3 records were given")]
+    NoRecords,
+    #[error("Unsupported NSEC3 hash algorithm {0}")]
+    UnsupportedHashAlgorithm(u8),
+    #[error("NSEC3 iteration count {0} is over the limit of 150")]
+    TooManyIterations(u16),
+    #[error("{name} has {rtype} records")]
+    TypeExists { name: String, rtype: DnsType },
+    #[error("The records of {name} are from the parent side of a delegation")]
+    Delegation { name: String },
+    #[error("No record covers {name}")]
+    NotCovered { name: String },
+    #[error("No closest encloser of {name} is proven to exist")]
+    NoClosestEncloser { name: String },
+}
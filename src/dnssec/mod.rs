@@ -0,0 +1,412 @@
+//! DNSSEC validation of captured records, without a validating resolver.
+//!
+//! `validate_rrset` checks an RRSIG over an RRset with a DNSKEY (RFC 4035 §5.3): the
+//! RRSIG must match the RRset and the key, be within its validity period, and its
+//! signature must verify over the canonical form of the RRset (RFC 4034 §6). The
+//! RSA/SHA-256, RSA/SHA-512, ECDSA P-256/SHA-256, ECDSA P-384/SHA-384 and Ed25519
+//! algorithms are supported.
+//!
+//! `ds_digest` and `ds_matches` relate DNSKEY records to the DS records of their parent
+//! zone, and `build_chain` follows them from a trust anchor through captured responses.
+//! `verify_denial` checks the NSEC or NSEC3 proofs of NXDOMAIN and NODATA responses, and
+//! `walk_zone` lists a zone from its NSEC records.
+
+use crate::dns_records::rdata::{Dnskey, Ds, RData, Timestamp};
+use crate::dns_records::ResourceRecord;
+use crate::prelude::*;
+use crate::utils::dns_types::DnsTypes;
+pub use chain::{build_chain, SecureZone, TrustChain};
+pub use denial::{nsec3_hash, verify_denial, Denial};
+pub use errors::{BogusReason, ChainError, DenialError};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384};
+pub use walk::{walk_zone, WalkedName, ZoneWalk};
+
+mod canonical;
+mod chain;
+pub(crate) mod crypto;
+mod denial;
+mod errors;
+mod walk;
+
+/// The DNSSEC algorithm numbers (IANA "DNS Security Algorithm Numbers").
+#[allow(non_snake_case)]
+#[allow(non_upper_case_globals)]
+pub mod Algorithms {
+    pub const RSAMD5: u8 = 1;
+    pub const DSA: u8 = 3;
+    pub const RSASHA1: u8 = 5;
+    pub const DSA_NSEC3_SHA1: u8 = 6;
+    pub const RSASHA1_NSEC3_SHA1: u8 = 7;
+    pub const RSASHA256: u8 = 8;
+    pub const RSASHA512: u8 = 10;
+    pub const ECC_GOST: u8 = 12;
+    pub const ECDSAP256SHA256: u8 = 13;
+    pub const ECDSAP384SHA384: u8 = 14;
+    pub const ED25519: u8 = 15;
+    pub const ED448: u8 = 16;
+}
+
+/// The DS digest types (IANA "Delegation Signer (DS) Resource Record (RR) Type Digest
+/// Algorithms").
+#[allow(non_snake_case)]
+#[allow(non_upper_case_globals)]
+pub mod DigestTypes {
+    pub const SHA1: u8 = 1;
+    pub const SHA256: u8 = 2;
+    pub const GOST: u8 = 3;
+    pub const SHA384: u8 = 4;
+}
+
+/// The outcome of validating an RRset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Secure,
+    Bogus(BogusReason),
+}
+
+impl Status {
+    pub fn is_secure(&self) -> bool {
+        *self == Status::Secure
+    }
+}
+
+/// Validates `rrsig` over `rrset` with `dnskey`, at `now` (seconds since the Unix epoch).
+///
+/// `rrset` holds the records of one owner, type and class, e.g. the A records of an
+/// answer section; their order and TTLs do not matter. `rrsig` and `dnskey` are RRSIG
+/// and DNSKEY records, the DNSKEY being owned by the RRSIG's signer.
+pub fn validate_rrset(
+    rrset: &[ResourceRecord],
+    rrsig: &ResourceRecord,
+    dnskey: &ResourceRecord,
+    now: u64,
+) -> Status {
+    match check_rrset(rrset, rrsig, dnskey, now) {
+        Ok(()) => Status::Secure,
+        Err(reason) => Status::Bogus(reason),
+    }
+}
+
+/// Computes the digest a DS record of `digest_type` holds for `dnskey`, owned by `owner`
+/// (RFC 4034 §5.1.4), or `None` when the digest type is not supported.
+pub fn ds_digest(owner: &str, dnskey: &Dnskey, digest_type: u8) -> Option<Vec<u8>> {
+    let mut data = canonical::name(owner);
+    dnskey.to_wire(&mut data);
+    match digest_type {
+        DigestTypes::SHA1 => Some(Sha1::digest(&data).to_vec()),
+        DigestTypes::SHA256 => Some(Sha256::digest(&data).to_vec()),
+        DigestTypes::SHA384 => Some(Sha384::digest(&data).to_vec()),
+        _ => None,
+    }
+}
+
+/// Whether `ds` designates `dnskey`, owned by `owner`: its key tag, algorithm and digest
+/// must all match.
+pub fn ds_matches(owner: &str, dnskey: &Dnskey, ds: &Ds) -> bool {
+    ds.key_tag == dnskey.key_tag()
+        && ds.algorithm == dnskey.algorithm
+        && ds_digest(owner, dnskey, ds.digest_type).is_some_and(|digest| digest == ds.digest)
+}
+
+fn check_rrset(
+    rrset: &[ResourceRecord],
+    rrsig: &ResourceRecord,
+    dnskey: &ResourceRecord,
+    now: u64,
+) -> Result<(), BogusReason> {
+    let RData::RRSIG(signature) = &rrsig.rdata else {
+        return Err(BogusReason::UnexpectedRecord {
+            expected: DnsTypes::RRSIG,
+            actual: rrsig.rtype,
+        });
+    };
+    let key = dnskey_data(dnskey)?;
+
+    let first = rrset.first().ok_or(BogusReason::EmptyRrset)?;
+    let owner = canonical::name(&first.name);
+    if rrset.iter().any(|record| {
+        record.rtype != first.rtype
+            || record.rclass != first.rclass
+            || canonical::name(&record.name) != owner
+    }) {
+        return Err(BogusReason::MixedRrset);
+    }
+
+    if signature.type_covered != first.rtype {
+        return Err(BogusReason::TypeMismatch {
+            covered: signature.type_covered,
+            rrset: first.rtype,
+        });
+    }
+    if canonical::name(&rrsig.name) != owner {
+        return Err(BogusReason::OwnerMismatch {
+            rrsig: rrsig.name.clone(),
+            rrset: first.name.clone(),
+        });
+    }
+    if rrsig.rclass != first.rclass {
+        return Err(BogusReason::ClassMismatch);
+    }
+    let signer = canonical::name(&signature.signer);
+    if !canonical::is_ancestor(&signer, &owner) {
+        return Err(BogusReason::SignerNotAncestor {
+            signer: signature.signer.clone(),
+            owner: first.name.clone(),
+        });
+    }
+    if canonical::name(&dnskey.name) != signer {
+        return Err(BogusReason::SignerMismatch {
+            signer: signature.signer.clone(),
+            key_owner: dnskey.name.clone(),
+        });
+    }
+
+    if signature.algorithm != key.algorithm {
+        return Err(BogusReason::AlgorithmMismatch {
+            rrsig: signature.algorithm,
+            dnskey: key.algorithm,
+        });
+    }
+    if signature.key_tag != key.key_tag() {
+        return Err(BogusReason::KeyTagMismatch {
+            rrsig: signature.key_tag,
+            dnskey: key.key_tag(),
+        });
+    }
+
+    // Serial number arithmetic (RFC 1982), as times wrap around in 2106.
+    let now = Timestamp(now as u32);
+    if serial_lt(signature.expiration, now) {
+        return Err(BogusReason::Expired {
+            expiration: signature.expiration,
+            now,
+        });
+    }
+    if serial_lt(now, signature.inception) {
+        return Err(BogusReason::NotYetValid {
+            inception: signature.inception,
+            now,
+        });
+    }
+
+    let data = canonical::signed_data(rrset, signature)?;
+    crypto::verify(key.algorithm, &key.public_key, &data, &signature.signature)
+}
+
+/// The data of a DNSKEY record usable to verify zone data.
+fn dnskey_data(dnskey: &ResourceRecord) -> Result<&Dnskey, BogusReason> {
+    let RData::DNSKEY(key) = &dnskey.rdata else {
+        return Err(BogusReason::UnexpectedRecord {
+            expected: DnsTypes::DNSKEY,
+            actual: dnskey.rtype,
+        });
+    };
+    if key.protocol != 3 {
+        return Err(BogusReason::InvalidProtocol(key.protocol));
+    }
+    if key.flags & Dnskey::ZONE == 0 {
+        return Err(BogusReason::NotZoneKey);
+    }
+    Ok(key)
+}
+
+pub(crate) fn serial_lt(a: Timestamp, b: Timestamp) -> bool {
+    a != b && b.0.wrapping_sub(a.0) < 1 << 31
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::dns_class::DnsClasses;
+    use crate::utils::dns_types::DnsType;
+
+    /// 2024-01-11, between the inception and expiration of the test signatures.
+    const NOW: u64 = 1_705_000_000;
+
+    /// Keys and signatures over `www.example.com. 3600 IN A 192.0.2.1` and `192.0.2.2`.
+    const VECTORS: [(&str, &str); 5] = [
+        (
+            "257 3 8 AwEAAcdXVjvXcBc35F7qzfaxgZ5VH/j/zROJ5EV894AFJcs5S0gnLOSN8hE+/D72WcQrcgzUnR4CSaYGHMQT4U4GkNcbRfAWitNtvlJeL6F2cc8ocTfUSkJKB2MKe1xRPsniRcf2i0OaevZ1yE52lXn+AhYf3Rfz6xQxOKGvlmwAqbwR",
+            "A 8 3 3600 20240201000000 20240101000000 34276 example.com X1NSHfITfDUbjjt6qlGAT4OJXtFeKCLzP3c18Alet1mbFKE6IDpnW9yDrSzX18BFKN+TeYuS3CuP/MjV0R8sFhtA3lNzEJcrkXC2OE0j6QKy0e6YSLQ4Z+lm55CB2PoQ5eJsj9h2z+LhdFtRVPZlLEQoipv3puPbCUgBd0OlW3M=",
+        ),
+        (
+            "257 3 10 AwEAAdKlzRaxw0/kQHsk/M72497QGfytmPGv7cnFSfndUG3KSgiSt2sZK6a77zEQN1G66UAwsQ0eY8+l4ysjV4vMz+6GruWKZmnZUtn52/GPN3PSS3g/+LB1XzVZs+PKVxe6OEEZj2jWhnsBnCagghycDOjowZOm0K3kZ03GoH50rL91",
+            "A 10 3 3600 20240201000000 20240101000000 62577 example.com ZpmVdSAiejmPgjSSrb9awr9KlX4cOZwhwCBvSjOH7gsTjVFJPlBnnsaW6FfGH+lHyMF4IPyT8K9VkhWr5aE9+PyVZQJQ9I6wTbnEwRk15cKxdXPha6ahHwZn+uROJ24AG+9ecCWGfQWqU0IsULiWkut1DOPyGOf3hoDHrf5qMDs=",
+        ),
+        (
+            "257 3 13 ICYPb1mX4ef+sC7zZ9ENP3PGzRY/9bYHJtzu4mYIkS8xsZpMbmPdTiJ9X8siHXmX2tqfRX92slnCskH8CJwx6w==",
+            "A 13 3 3600 20240201000000 20240101000000 30332 example.com oxytlSG4Cm2qyZrtgzJ1HRCJZayxGR2wz/xaBgvkjBGWyrERutGCOfkMayXs4oo94zpLB8OAnAEQkVnJQWmxYg==",
+        ),
+        (
+            "257 3 14 nNrYL4fF5OgKknTY48PnJvMs4F7icNXbXkpqmdDPBfn8GnRbgxz/qFpTpI75rQqUlzpo13q4fll8lTW/D8kHhyY5EHd56YclMw3C+Ov6Ml6ZMoLSZUfwRX2uogoME3/5",
+            "A 14 3 3600 20240201000000 20240101000000 32321 example.com OAKYfuh5a1yPDa/CVXol4/r0wTcT55tsXKa4xx2P0/hr3owFyUfL8RDcjZCZzKljm+oFGIepvLE7rnoC3H5dSgehg7TYWLrqwPL/oojbeH15ZuZmnBxiosn5R/0oi4ns",
+        ),
+        (
+            "257 3 15 ylUostyRWxmf1knyN9grB9eQWwd9pyS5HDIwK++mE/I=",
+            "A 15 3 3600 20240201000000 20240101000000 41050 example.com 6pn1yJTZSeI6s0ZW9z5luKAWmlM3W/bkTOFfuXi7QTHoRkAAbXBikMt3UFphQXdHwu5t4Czg4HTJoehxnbFtDQ==",
+        ),
+    ];
+
+    fn record(name: &str, rtype: DnsType, rdata: &str) -> ResourceRecord {
+        let rdata = RData::from_presentation(rtype, rdata).unwrap();
+        let mut wire = Vec::new();
+        rdata.to_wire(&mut wire);
+        ResourceRecord {
+            name: name.to_string(),
+            rtype,
+            rclass: DnsClasses::IN,
+            ttl: 300,
+            data_length: wire.len() as u16,
+            rdata,
+        }
+    }
+
+    /// The signed RRset, in another order and case than signed, with a decremented TTL.
+    fn rrset() -> Vec<ResourceRecord> {
+        vec![
+            record("WWW.Example.com", DnsTypes::A, "192.0.2.2"),
+            record("www.example.com", DnsTypes::A, "192.0.2.1"),
+        ]
+    }
+
+    #[test]
+    fn test_validate_rrset() {
+        for (dnskey, rrsig) in VECTORS {
+            let dnskey = record("example.com", DnsTypes::DNSKEY, dnskey);
+            let rrsig = record("www.example.com", DnsTypes::RRSIG, rrsig);
+            assert_eq!(
+                validate_rrset(&rrset(), &rrsig, &dnskey, NOW),
+                Status::Secure,
+                "{}",
+                rrsig.rdata
+            );
+        }
+    }
+
+    #[test]
+    fn test_ds_digest() {
+        // The example of RFC 4034 §5.4, and its SHA-256 digest from RFC 4509 §2.3.
+        let dnskey = record(
+            "dskey.example.com",
+            DnsTypes::DNSKEY,
+            "256 3 5 AQOeiiR0GOMYkDshWoSKz9XzfwJr1AYtsmx3TGkJaNXVbfi/2pHm822aJ5iI9BMzNXxeYCmZDRD99WYwYqUSdjMmmAphXdvxegXd/M5+X7OrzKBaMbCVdFLUUh6DhweJBjEVv5f2wwjM9XzcnOf+EPbtG9DMBmADjFDc2w/rljwvFw==",
+        );
+        let RData::DNSKEY(key) = &dnskey.rdata else {
+            unreachable!()
+        };
+        let digest =
+            |digest_type| ds_digest("DSKEY.example.com.", key, digest_type).map(hex::encode);
+        assert_eq!(
+            digest(DigestTypes::SHA1).unwrap(),
+            "2bb183af5f22588179a53b0a98631fad1a292118"
+        );
+        assert_eq!(
+            digest(DigestTypes::SHA256).unwrap(),
+            "d4b7d520e7bb5f0f67674a0cceb1e3e0614b93c4f9e99b8383f6a1e4469da50a"
+        );
+        assert_eq!(digest(DigestTypes::GOST), None);
+
+        let ds = |rdata| match RData::from_presentation(DnsTypes::DS, rdata).unwrap() {
+            RData::DS(ds) => ds,
+            _ => unreachable!(),
+        };
+        let sha1 = ds("60485 5 1 2BB183AF5F22588179A53B0A98631FAD1A292118");
+        assert!(ds_matches("dskey.example.com", key, &sha1));
+        assert!(!ds_matches("example.com", key, &sha1));
+        let other_tag = ds("60486 5 1 2BB183AF5F22588179A53B0A98631FAD1A292118");
+        assert!(!ds_matches("dskey.example.com", key, &other_tag));
+    }
+
+    #[test]
+    fn test_validate_wildcard_expansion() {
+        let dnskey = record("example.com", DnsTypes::DNSKEY, VECTORS[4].0);
+        let rrsig = record(
+            "host.example.com",
+            DnsTypes::RRSIG,
+            "TXT 15 2 3600 20240201000000 20240101000000 41050 example.com 9irU/PUwOJQcVIuMz/Kfolmknn1c9jpnOChsnv4tjPJnwcd9D2/p7s50p+5o6NcU5LcrVSqxe3ggkEMmPz4aAg==",
+        );
+        let txt = [record("host.example.com", DnsTypes::TXT, "\"hello\"")];
+        assert!(validate_rrset(&txt, &rrsig, &dnskey, NOW).is_secure());
+    }
+
+    #[test]
+    fn test_validate_rrset_bogus() {
+        let dnskey = record("example.com", DnsTypes::DNSKEY, VECTORS[4].0);
+        let rrsig = record("www.example.com", DnsTypes::RRSIG, VECTORS[4].1);
+        let bogus = |rrset: &[ResourceRecord], rrsig, dnskey, now| match validate_rrset(
+            rrset, rrsig, dnskey, now,
+        ) {
+            Status::Bogus(reason) => reason,
+            Status::Secure => panic!("validated a bogus RRset"),
+        };
+
+        let mut tampered = rrset();
+        tampered[0] = record("www.example.com", DnsTypes::A, "192.0.2.3");
+        assert_eq!(
+            bogus(&tampered, &rrsig, &dnskey, NOW),
+            BogusReason::SignatureMismatch
+        );
+        assert_eq!(
+            bogus(&rrset()[..1], &rrsig, &dnskey, NOW),
+            BogusReason::SignatureMismatch
+        );
+        assert_eq!(bogus(&[], &rrsig, &dnskey, NOW), BogusReason::EmptyRrset);
+
+        assert!(matches!(
+            bogus(&rrset(), &rrsig, &dnskey, 1_706_745_601),
+            BogusReason::Expired { .. }
+        ));
+        assert!(matches!(
+            bogus(&rrset(), &rrsig, &dnskey, 1_704_067_199),
+            BogusReason::NotYetValid { .. }
+        ));
+
+        let other_key = record("example.com", DnsTypes::DNSKEY, VECTORS[2].0);
+        assert_eq!(
+            bogus(&rrset(), &rrsig, &other_key, NOW),
+            BogusReason::AlgorithmMismatch {
+                rrsig: Algorithms::ED25519,
+                dnskey: Algorithms::ECDSAP256SHA256
+            }
+        );
+        let other_key = record(
+            "example.com",
+            DnsTypes::DNSKEY,
+            "257 3 15 l02Woi0iS8Aa25FQkUd9RMzZHJpBoRQwAQEX1SxZJA4=",
+        );
+        assert!(matches!(
+            bogus(&rrset(), &rrsig, &other_key, NOW),
+            BogusReason::KeyTagMismatch { .. }
+        ));
+        let foreign_key = record("example.net", DnsTypes::DNSKEY, VECTORS[4].0);
+        assert!(matches!(
+            bogus(&rrset(), &rrsig, &foreign_key, NOW),
+            BogusReason::SignerMismatch { .. }
+        ));
+
+        let aaaa = [record("www.example.com", DnsTypes::AAAA, "2001:db8::1")];
+        assert!(matches!(
+            bogus(&aaaa, &rrsig, &dnskey, NOW),
+            BogusReason::TypeMismatch { .. }
+        ));
+        let mut mixed = rrset();
+        mixed.push(record("ftp.example.com", DnsTypes::A, "192.0.2.1"));
+        assert_eq!(bogus(&mixed, &rrsig, &dnskey, NOW), BogusReason::MixedRrset);
+
+        let dsa_key = record("example.com", DnsTypes::DNSKEY, "257 3 3 AAAA");
+        let tag = match &dsa_key.rdata {
+            RData::DNSKEY(key) => key.key_tag(),
+            _ => unreachable!(),
+        };
+        let dsa = record(
+            "www.example.com",
+            DnsTypes::RRSIG,
+            &format!("A 3 3 3600 20240201000000 20240101000000 {tag} example.com AAAA"),
+        );
+        assert_eq!(
+            bogus(&rrset(), &dsa, &dsa_key, NOW),
+            BogusReason::UnsupportedAlgorithm(Algorithms::DSA)
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! NSEC zone walking: listing the names of a zone from the NSEC records collected from
+//! its responses.
+
+use super::canonical;
+use crate::dns_records::rdata::RData;
+use crate::dns_records::ResourceRecord;
+use crate::prelude::*;
+use crate::utils::dns_types::DnsType;
+use alloc::collections::{BTreeMap, BTreeSet};
+
+/// A name of a walked zone and the types present at it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalkedName {
+    pub name: String,
+    pub types: BTreeSet<DnsType>,
+}
+
+/// The names found by walking the NSEC chain of a zone.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ZoneWalk {
+    /// The owners of NSEC records, in canonical order.
+    pub names: Vec<WalkedName>,
+    /// The next names of NSEC records whose own NSEC record is missing: querying them
+    /// continues the walk.
+    pub gaps: Vec<String>,
+}
+
+impl ZoneWalk {
+    /// Whether the NSEC chain is closed, so that `names` lists the whole zone.
+    pub fn is_complete(&self) -> bool {
+        !self.names.is_empty() && self.gaps.is_empty()
+    }
+}
+
+/// Orders the NSEC records among `records`, all from one zone, into its chain of names.
+pub fn walk_zone(records: &[ResourceRecord]) -> ZoneWalk {
+    let mut chain = BTreeMap::new();
+    for record in records {
+        let RData::NSEC(nsec) = &record.rdata else {
+            continue;
+        };
+        let owner = canonical::name(&record.name);
+        chain.entry(sort_key(&owner)).or_insert((owner, nsec));
+    }
+
+    let mut walk = ZoneWalk::default();
+    for (owner, nsec) in chain.values() {
+        walk.names.push(WalkedName {
+            name: canonical::display(owner),
+            types: nsec.types.clone(),
+        });
+        let next = canonical::name(&nsec.next_domain);
+        if !chain.contains_key(&sort_key(&next)) {
+            walk.gaps.push(nsec.next_domain.clone());
+        }
+    }
+    walk
+}
+
+/// The labels of a canonical name from the rightmost one, which sort in canonical order.
+fn sort_key(name: &[u8]) -> Vec<Vec<u8>> {
+    canonical::labels(name)
+        .into_iter()
+        .rev()
+        .map(<[u8]>::to_vec)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::dns_class::DnsClasses;
+    use crate::utils::dns_types::DnsTypes;
+
+    fn nsec(name: &str, rdata: &str) -> ResourceRecord {
+        ResourceRecord {
+            name: name.to_string(),
+            rtype: DnsTypes::NSEC,
+            rclass: DnsClasses::IN,
+            ttl: 3600,
+            data_length: 0,
+            rdata: RData::from_presentation(DnsTypes::NSEC, rdata).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_walk_zone() {
+        // Collected out of order, with duplicates and differing case.
+        let mut records = vec![
+            nsec("x.w.example", "xx.example MX RRSIG NSEC"),
+            nsec("Example", "a.example NS SOA MX RRSIG NSEC DNSKEY"),
+            nsec("xx.example", "example A RRSIG NSEC"),
+            nsec("*.w.example", "x.w.example MX RRSIG NSEC"),
+            nsec("a.example", "*.w.example NS DS RRSIG NSEC"),
+            nsec("example", "a.example NS SOA MX RRSIG NSEC DNSKEY"),
+        ];
+        let walk = walk_zone(&records);
+        let names: Vec<&str> = walk.names.iter().map(|name| name.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "example",
+                "a.example",
+                "*.w.example",
+                "x.w.example",
+                "xx.example"
+            ]
+        );
+        assert!(walk.names[1].types.contains(&DnsTypes::DS));
+        assert!(walk.is_complete());
+
+        records.retain(|record| record.name != "*.w.example");
+        let walk = walk_zone(&records);
+        assert_eq!(walk.gaps, ["*.w.example"]);
+        assert!(!walk.is_complete());
+        assert!(!walk_zone(&[]).is_complete());
+    }
+}
@@ -0,0 +1,17 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DnstapError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid Frame Streams control frame: {0}")]
+    InvalidControlFrame(&'static str),
+    #[error("Frame Streams data frame of {0} bytes exceeds the maximum length")]
+    FrameTooLong(u32),
+    #[error("Unexpected Frame Streams content type: {0}")]
+    UnexpectedContentType(String),
+    #[error("Invalid protobuf encoding: {0}")]
+    InvalidProtobuf(&'static str),
+    #[error("Missing required dnstap field: {0}")]
+    MissingField(&'static str),
+}
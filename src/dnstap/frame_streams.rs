@@ -0,0 +1,346 @@
+//! Frame Streams, the framing protocol dnstap is carried over.
+//!
+//! Each data frame is a big-endian 32-bit length followed by the payload. A zero length
+//! escapes a control frame (START, STOP, READY, ACCEPT, FINISH) that carries the content
+//! type of the stream.
+
+use std::io::{self, Read, Write};
+
+use super::errors::DnstapError;
+
+const CONTROL_ACCEPT: u32 = 0x01;
+const CONTROL_START: u32 = 0x02;
+const CONTROL_STOP: u32 = 0x03;
+const CONTROL_READY: u32 = 0x04;
+const CONTROL_FINISH: u32 = 0x05;
+const CONTROL_FIELD_CONTENT_TYPE: u32 = 0x01;
+/// Maximum length of a control frame allowed by the Frame Streams specification.
+const MAX_CONTROL_FRAME_LENGTH: u32 = 512;
+/// Maximum length of a data frame accepted when not configured otherwise.
+pub const DEFAULT_MAX_FRAME_LENGTH: u32 = 1 << 20;
+
+/// A decoded control frame: its type and the content types it lists.
+struct ControlFrame {
+    control_type: u32,
+    content_types: Vec<Vec<u8>>,
+}
+
+/// Reads data frames from a Frame Streams file or unidirectional socket.
+pub struct FrameStreamReader<R> {
+    reader: R,
+    content_type: Vec<u8>,
+    max_frame_length: u32,
+    started: bool,
+    stopped: bool,
+}
+
+impl<R: Read> FrameStreamReader<R> {
+    /// Creates a reader that only accepts streams of the given content type.
+    pub fn new(reader: R, content_type: &str) -> Self {
+        Self::with_max_frame_length(reader, content_type, DEFAULT_MAX_FRAME_LENGTH)
+    }
+
+    /// Creates a reader that rejects the data frames longer than `max_frame_length`.
+    pub fn with_max_frame_length(reader: R, content_type: &str, max_frame_length: u32) -> Self {
+        FrameStreamReader {
+            reader,
+            content_type: content_type.as_bytes().to_vec(),
+            max_frame_length,
+            started: false,
+            stopped: false,
+        }
+    }
+
+    /// Returns the next data frame, or `None` once the STOP frame or the end of the input
+    /// has been reached.
+    pub fn read_frame(&mut self) -> Result<Option<Vec<u8>>, DnstapError> {
+        while !self.stopped {
+            let Some(length) = read_u32_or_eof(&mut self.reader)? else {
+                self.stopped = true;
+                break;
+            };
+            if length != 0 {
+                if !self.started {
+                    return Err(DnstapError::InvalidControlFrame("data frame before START"));
+                }
+                if length > self.max_frame_length {
+                    return Err(DnstapError::FrameTooLong(length));
+                }
+                let mut frame = vec![0; length as usize];
+                self.reader.read_exact(&mut frame)?;
+                return Ok(Some(frame));
+            }
+
+            let control = read_control_frame(&mut self.reader)?;
+            match control.control_type {
+                CONTROL_START => {
+                    if let Some(content_type) = control
+                        .content_types
+                        .iter()
+                        .find(|content_type| **content_type != self.content_type)
+                    {
+                        return Err(DnstapError::UnexpectedContentType(
+                            String::from_utf8_lossy(content_type).into_owned(),
+                        ));
+                    }
+                    self.started = true;
+                }
+                CONTROL_STOP => self.stopped = true,
+                _ => return Err(DnstapError::InvalidControlFrame("unexpected control type")),
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// Performs the receiver side of the bidirectional handshake on a socket: waits for the
+/// sender's READY frame and answers with ACCEPT if it offers `content_type`.
+///
+/// The stream can then be read with a `FrameStreamReader`; once it returns `None`,
+/// `finish_bidirectional` should be called to acknowledge the STOP frame.
+pub fn accept_bidirectional<S: Read + Write>(
+    stream: &mut S,
+    content_type: &str,
+) -> Result<(), DnstapError> {
+    if read_u32_or_eof(stream)? != Some(0) {
+        return Err(DnstapError::InvalidControlFrame("expected READY"));
+    }
+    let control = read_control_frame(stream)?;
+    if control.control_type != CONTROL_READY {
+        return Err(DnstapError::InvalidControlFrame("expected READY"));
+    }
+    if !control
+        .content_types
+        .iter()
+        .any(|offered| offered == content_type.as_bytes())
+    {
+        let offered = control
+            .content_types
+            .iter()
+            .map(|offered| String::from_utf8_lossy(offered).into_owned())
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Err(DnstapError::UnexpectedContentType(offered));
+    }
+    write_control_frame(stream, CONTROL_ACCEPT, Some(content_type))?;
+    Ok(())
+}
+
+/// Sends the FINISH frame that ends a bidirectional stream.
+pub fn finish_bidirectional<W: Write>(stream: &mut W) -> Result<(), DnstapError> {
+    write_control_frame(stream, CONTROL_FINISH, None)?;
+    Ok(())
+}
+
+/// Writes data frames as a unidirectional Frame Stream (e.g. a dnstap file).
+pub struct FrameStreamWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> FrameStreamWriter<W> {
+    /// Starts the stream by writing a START frame with `content_type`.
+    pub fn new(mut writer: W, content_type: &str) -> Result<Self, DnstapError> {
+        write_control_frame(&mut writer, CONTROL_START, Some(content_type))?;
+        Ok(FrameStreamWriter { writer })
+    }
+
+    pub fn write_frame(&mut self, frame: &[u8]) -> Result<(), DnstapError> {
+        if frame.is_empty() {
+            return Err(DnstapError::InvalidControlFrame("empty data frame"));
+        }
+        let length = u32::try_from(frame.len())
+            .map_err(|_| DnstapError::InvalidControlFrame("data frame too long"))?;
+        self.writer.write_all(&length.to_be_bytes())?;
+        self.writer.write_all(frame)?;
+        Ok(())
+    }
+
+    /// Ends the stream with a STOP frame and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, DnstapError> {
+        write_control_frame(&mut self.writer, CONTROL_STOP, None)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+fn read_u32_or_eof<R: Read>(reader: &mut R) -> Result<Option<u32>, DnstapError> {
+    let mut bytes = [0; 4];
+    let mut read = 0;
+    while read < bytes.len() {
+        match reader.read(&mut bytes[read..]) {
+            Ok(0) if read == 0 => return Ok(None),
+            Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            Ok(n) => read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(Some(u32::from_be_bytes(bytes)))
+}
+
+/// Reads a control frame, the escape sequence having already been consumed.
+fn read_control_frame<R: Read>(reader: &mut R) -> Result<ControlFrame, DnstapError> {
+    let mut length = [0; 4];
+    reader.read_exact(&mut length)?;
+    let length = u32::from_be_bytes(length);
+    if !(4..=MAX_CONTROL_FRAME_LENGTH).contains(&length) {
+        return Err(DnstapError::InvalidControlFrame("invalid length"));
+    }
+    let mut frame = vec![0; length as usize];
+    reader.read_exact(&mut frame)?;
+
+    let control_type = u32::from_be_bytes([frame[0], frame[1], frame[2], frame[3]]);
+    let mut content_types = Vec::new();
+    let mut offset = 4;
+    while offset < frame.len() {
+        if frame.len() - offset < 8 {
+            return Err(DnstapError::InvalidControlFrame("truncated field"));
+        }
+        let field_type = u32::from_be_bytes([
+            frame[offset],
+            frame[offset + 1],
+            frame[offset + 2],
+            frame[offset + 3],
+        ]);
+        let field_length = u32::from_be_bytes([
+            frame[offset + 4],
+            frame[offset + 5],
+            frame[offset + 6],
+            frame[offset + 7],
+        ]) as usize;
+        offset += 8;
+        if field_length > frame.len() - offset {
+            return Err(DnstapError::InvalidControlFrame("truncated field"));
+        }
+        if field_type == CONTROL_FIELD_CONTENT_TYPE {
+            content_types.push(frame[offset..offset + field_length].to_vec());
+        }
+        offset += field_length;
+    }
+
+    Ok(ControlFrame {
+        control_type,
+        content_types,
+    })
+}
+
+fn write_control_frame<W: Write>(
+    writer: &mut W,
+    control_type: u32,
+    content_type: Option<&str>,
+) -> io::Result<()> {
+    let mut frame = control_type.to_be_bytes().to_vec();
+    if let Some(content_type) = content_type {
+        frame.extend_from_slice(&CONTROL_FIELD_CONTENT_TYPE.to_be_bytes());
+        frame.extend_from_slice(&(content_type.len() as u32).to_be_bytes());
+        frame.extend_from_slice(content_type.as_bytes());
+    }
+    writer.write_all(&0u32.to_be_bytes())?;
+    writer.write_all(&(frame.len() as u32).to_be_bytes())?;
+    writer.write_all(&frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_stream_round_trip() {
+        let mut writer = FrameStreamWriter::new(Vec::new(), "test").unwrap();
+        writer.write_frame(b"first").unwrap();
+        writer.write_frame(b"second").unwrap();
+        let bytes = writer.finish().unwrap();
+        assert_eq!(&bytes[..8], &[0, 0, 0, 0, 0, 0, 0, 16]);
+
+        let mut reader = FrameStreamReader::new(bytes.as_slice(), "test");
+        assert_eq!(reader.read_frame().unwrap(), Some(b"first".to_vec()));
+        assert_eq!(reader.read_frame().unwrap(), Some(b"second".to_vec()));
+        assert_eq!(reader.read_frame().unwrap(), None);
+        assert_eq!(reader.read_frame().unwrap(), None);
+    }
+
+    #[test]
+    fn test_frame_stream_frame_too_long() {
+        let mut writer = FrameStreamWriter::new(Vec::new(), "test").unwrap();
+        writer.write_frame(b"first").unwrap();
+        let mut bytes = writer.finish().unwrap();
+        bytes.truncate(bytes.len() - 12);
+        bytes.extend_from_slice(&u32::MAX.to_be_bytes());
+
+        let mut reader = FrameStreamReader::new(bytes.as_slice(), "test");
+        assert_eq!(reader.read_frame().unwrap(), Some(b"first".to_vec()));
+        assert!(matches!(
+            reader.read_frame(),
+            Err(DnstapError::FrameTooLong(u32::MAX))
+        ));
+
+        let mut reader = FrameStreamReader::with_max_frame_length(bytes.as_slice(), "test", 4);
+        assert!(matches!(
+            reader.read_frame(),
+            Err(DnstapError::FrameTooLong(5))
+        ));
+    }
+
+    #[test]
+    fn test_frame_stream_wrong_content_type() {
+        let bytes = FrameStreamWriter::new(Vec::new(), "other")
+            .unwrap()
+            .finish()
+            .unwrap();
+        let mut reader = FrameStreamReader::new(bytes.as_slice(), "test");
+        assert!(matches!(
+            reader.read_frame(),
+            Err(DnstapError::UnexpectedContentType(content_type)) if content_type == "other"
+        ));
+    }
+
+    /// An in-memory socket: reads from `input`, writes to `output`.
+    struct Duplex {
+        input: io::Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+
+    impl Read for Duplex {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for Duplex {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_accept_bidirectional() {
+        let mut ready = Vec::new();
+        write_control_frame(&mut ready, CONTROL_READY, Some("test")).unwrap();
+
+        let mut duplex = Duplex {
+            input: io::Cursor::new(ready.clone()),
+            output: Vec::new(),
+        };
+        accept_bidirectional(&mut duplex, "test").unwrap();
+        let mut accept = Vec::new();
+        write_control_frame(&mut accept, CONTROL_ACCEPT, Some("test")).unwrap();
+        assert_eq!(duplex.output, accept);
+
+        let mut duplex = Duplex {
+            input: io::Cursor::new(ready),
+            output: Vec::new(),
+        };
+        assert!(matches!(
+            accept_bidirectional(&mut duplex, "other"),
+            Err(DnstapError::UnexpectedContentType(offered)) if offered == "test"
+        ));
+    }
+}
@@ -0,0 +1,496 @@
+//! Reading and writing dnstap (<https://dnstap.info>) logs.
+//!
+//! Resolvers such as Unbound, BIND or Knot log the DNS messages they send and receive as
+//! dnstap protobuf messages carried over Frame Streams. `DnstapReader` decodes such a
+//! stream, and the embedded wire-format messages can be parsed with
+//! `Message::query_packet` and `Message::response_packet`, exactly like captured payloads.
+//! `DnstapWriter` goes the other way, e.g. to log packets seen on the wire.
+
+use std::fmt;
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use crate::errors::DnsPacketError;
+use crate::DnsPacket;
+pub use errors::DnstapError;
+pub use frame_streams::{
+    accept_bidirectional, finish_bidirectional, FrameStreamReader, FrameStreamWriter,
+    DEFAULT_MAX_FRAME_LENGTH,
+};
+use protobuf::{
+    write_bytes_field, write_fixed32_field, write_varint_field, ProtobufReader, WireValue,
+};
+
+mod errors;
+mod frame_streams;
+mod protobuf;
+
+/// The Frame Streams content type of dnstap streams.
+pub const CONTENT_TYPE: &str = "protobuf:dnstap.Dnstap";
+
+/// The only `Dnstap.Type` defined by the schema.
+const DNSTAP_TYPE_MESSAGE: u64 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MessageType(pub u32);
+
+#[allow(non_snake_case)]
+#[allow(non_upper_case_globals)]
+pub mod MessageTypes {
+    use super::MessageType;
+
+    pub const AUTH_QUERY: MessageType = MessageType(1);
+    pub const AUTH_RESPONSE: MessageType = MessageType(2);
+    pub const RESOLVER_QUERY: MessageType = MessageType(3);
+    pub const RESOLVER_RESPONSE: MessageType = MessageType(4);
+    pub const CLIENT_QUERY: MessageType = MessageType(5);
+    pub const CLIENT_RESPONSE: MessageType = MessageType(6);
+    pub const FORWARDER_QUERY: MessageType = MessageType(7);
+    pub const FORWARDER_RESPONSE: MessageType = MessageType(8);
+    pub const STUB_QUERY: MessageType = MessageType(9);
+    pub const STUB_RESPONSE: MessageType = MessageType(10);
+    pub const TOOL_QUERY: MessageType = MessageType(11);
+    pub const TOOL_RESPONSE: MessageType = MessageType(12);
+    pub const UPDATE_QUERY: MessageType = MessageType(13);
+    pub const UPDATE_RESPONSE: MessageType = MessageType(14);
+}
+
+impl MessageType {
+    /// Whether this type logs a query (odd values) rather than a response.
+    pub fn is_query(&self) -> bool {
+        self.0 % 2 == 1
+    }
+}
+
+impl fmt::Display for MessageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                MessageTypes::AUTH_QUERY => "AUTH_QUERY",               // 1
+                MessageTypes::AUTH_RESPONSE => "AUTH_RESPONSE",         // 2
+                MessageTypes::RESOLVER_QUERY => "RESOLVER_QUERY",       // 3
+                MessageTypes::RESOLVER_RESPONSE => "RESOLVER_RESPONSE", // 4
+                MessageTypes::CLIENT_QUERY => "CLIENT_QUERY",           // 5
+                MessageTypes::CLIENT_RESPONSE => "CLIENT_RESPONSE",     // 6
+                MessageTypes::FORWARDER_QUERY => "FORWARDER_QUERY",     // 7
+                MessageTypes::FORWARDER_RESPONSE => "FORWARDER_RESPONSE", // 8
+                MessageTypes::STUB_QUERY => "STUB_QUERY",               // 9
+                MessageTypes::STUB_RESPONSE => "STUB_RESPONSE",         // 10
+                MessageTypes::TOOL_QUERY => "TOOL_QUERY",               // 11
+                MessageTypes::TOOL_RESPONSE => "TOOL_RESPONSE",         // 12
+                MessageTypes::UPDATE_QUERY => "UPDATE_QUERY",           // 13
+                MessageTypes::UPDATE_RESPONSE => "UPDATE_RESPONSE",     // 14
+                _ => "unknown",
+            }
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SocketProtocol(pub u32);
+
+#[allow(non_snake_case)]
+#[allow(non_upper_case_globals)]
+pub mod SocketProtocols {
+    use super::SocketProtocol;
+
+    pub const UDP: SocketProtocol = SocketProtocol(1);
+    pub const TCP: SocketProtocol = SocketProtocol(2);
+    pub const DOT: SocketProtocol = SocketProtocol(3);
+    pub const DOH: SocketProtocol = SocketProtocol(4);
+    pub const DNSCRYPT_UDP: SocketProtocol = SocketProtocol(5);
+    pub const DNSCRYPT_TCP: SocketProtocol = SocketProtocol(6);
+    pub const DOQ: SocketProtocol = SocketProtocol(7);
+}
+
+impl fmt::Display for SocketProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                SocketProtocols::UDP => "UDP",                  // 1
+                SocketProtocols::TCP => "TCP",                  // 2
+                SocketProtocols::DOT => "DOT",                  // 3
+                SocketProtocols::DOH => "DOH",                  // 4
+                SocketProtocols::DNSCRYPT_UDP => "DNSCryptUDP", // 5
+                SocketProtocols::DNSCRYPT_TCP => "DNSCryptTCP", // 6
+                SocketProtocols::DOQ => "DOQ",                  // 7
+                _ => "unknown",
+            }
+        )
+    }
+}
+
+/// A dnstap frame: an identification of the logging server and the logged message.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Dnstap {
+    pub identity: Option<Vec<u8>>,
+    pub version: Option<Vec<u8>>,
+    pub extra: Option<Vec<u8>>,
+    pub message: Option<Message>,
+}
+
+impl Dnstap {
+    pub fn decode(bytes: &[u8]) -> Result<Self, DnstapError> {
+        let mut dnstap = Dnstap::default();
+        let mut reader = ProtobufReader::new(bytes);
+        let mut has_type = false;
+        while let Some((field, value)) = reader.next_field()? {
+            match field {
+                1 => dnstap.identity = Some(value.as_bytes()?.to_vec()),
+                2 => dnstap.version = Some(value.as_bytes()?.to_vec()),
+                3 => dnstap.extra = Some(value.as_bytes()?.to_vec()),
+                14 => dnstap.message = Some(Message::decode(value.as_bytes()?)?),
+                15 => has_type = true,
+                _ => {}
+            }
+        }
+        if !has_type {
+            return Err(DnstapError::MissingField("Dnstap.type"));
+        }
+        Ok(dnstap)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for (field, value) in [(1, &self.identity), (2, &self.version), (3, &self.extra)] {
+            if let Some(value) = value {
+                write_bytes_field(&mut buf, field, value);
+            }
+        }
+        if let Some(message) = &self.message {
+            write_bytes_field(&mut buf, 14, &message.encode());
+        }
+        write_varint_field(&mut buf, 15, DNSTAP_TYPE_MESSAGE);
+        buf
+    }
+}
+
+/// A logged DNS message with its transport metadata.
+///
+/// Times are durations since the Unix epoch. Query types carry `query_message` and
+/// `query_time`, response types `response_message` and `response_time` (and usually the
+/// original query too).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub message_type: MessageType,
+    pub socket_protocol: Option<SocketProtocol>,
+    pub query_address: Option<IpAddr>,
+    pub response_address: Option<IpAddr>,
+    pub query_port: Option<u16>,
+    pub response_port: Option<u16>,
+    pub query_time: Option<Duration>,
+    pub query_message: Option<Vec<u8>>,
+    pub query_zone: Option<Vec<u8>>,
+    pub response_time: Option<Duration>,
+    pub response_message: Option<Vec<u8>>,
+}
+
+impl Message {
+    pub fn new(message_type: MessageType) -> Self {
+        Message {
+            message_type,
+            socket_protocol: None,
+            query_address: None,
+            response_address: None,
+            query_port: None,
+            response_port: None,
+            query_time: None,
+            query_message: None,
+            query_zone: None,
+            response_time: None,
+            response_message: None,
+        }
+    }
+
+    /// Builds a message from a DNS payload captured on the wire between `query_peer` (the
+    /// side that sent the query) and `response_peer`, seen at `time`.
+    pub fn from_captured(
+        message_type: MessageType,
+        payload: &[u8],
+        query_peer: SocketAddr,
+        response_peer: SocketAddr,
+        socket_protocol: SocketProtocol,
+        time: Duration,
+    ) -> Self {
+        let mut message = Message::new(message_type);
+        message.socket_protocol = Some(socket_protocol);
+        message.query_address = Some(query_peer.ip());
+        message.query_port = Some(query_peer.port());
+        message.response_address = Some(response_peer.ip());
+        message.response_port = Some(response_peer.port());
+        if message_type.is_query() {
+            message.query_time = Some(time);
+            message.query_message = Some(payload.to_vec());
+        } else {
+            message.response_time = Some(time);
+            message.response_message = Some(payload.to_vec());
+        }
+        message
+    }
+
+    /// Parses the logged query, if there is one.
+    pub fn query_packet(&self) -> Option<Result<DnsPacket, DnsPacketError>> {
+        self.query_message.as_deref().map(DnsPacket::try_from)
+    }
+
+    /// Parses the logged response, if there is one.
+    pub fn response_packet(&self) -> Option<Result<DnsPacket, DnsPacketError>> {
+        self.response_message.as_deref().map(DnsPacket::try_from)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DnstapError> {
+        let mut message = Message::new(MessageType(0));
+        let mut has_type = false;
+        let (mut query_seconds, mut query_nanos) = (None, 0);
+        let (mut response_seconds, mut response_nanos) = (None, 0);
+        let mut reader = ProtobufReader::new(bytes);
+        while let Some((field, value)) = reader.next_field()? {
+            match field {
+                1 => {
+                    message.message_type = MessageType(as_u32(&value)?);
+                    has_type = true;
+                }
+                3 => message.socket_protocol = Some(SocketProtocol(as_u32(&value)?)),
+                4 => message.query_address = Some(as_address(&value)?),
+                5 => message.response_address = Some(as_address(&value)?),
+                6 => message.query_port = Some(as_port(&value)?),
+                7 => message.response_port = Some(as_port(&value)?),
+                8 => query_seconds = Some(value.as_varint()?),
+                9 => query_nanos = value.as_fixed32()?,
+                10 => message.query_message = Some(value.as_bytes()?.to_vec()),
+                11 => message.query_zone = Some(value.as_bytes()?.to_vec()),
+                12 => response_seconds = Some(value.as_varint()?),
+                13 => response_nanos = value.as_fixed32()?,
+                14 => message.response_message = Some(value.as_bytes()?.to_vec()),
+                // Socket family (2) is implied by the addresses; policy and HTTP protocol are not decoded.
+                _ => {}
+            }
+        }
+        if !has_type {
+            return Err(DnstapError::MissingField("Message.type"));
+        }
+        message.query_time = as_time(query_seconds, query_nanos)?;
+        message.response_time = as_time(response_seconds, response_nanos)?;
+        Ok(message)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_varint_field(&mut buf, 1, self.message_type.0 as u64);
+        if let Some(address) = self.query_address.or(self.response_address) {
+            write_varint_field(&mut buf, 2, if address.is_ipv4() { 1 } else { 2 });
+        }
+        if let Some(protocol) = self.socket_protocol {
+            write_varint_field(&mut buf, 3, protocol.0 as u64);
+        }
+        for (field, address) in [(4, self.query_address), (5, self.response_address)] {
+            match address {
+                Some(IpAddr::V4(address)) => write_bytes_field(&mut buf, field, &address.octets()),
+                Some(IpAddr::V6(address)) => write_bytes_field(&mut buf, field, &address.octets()),
+                None => {}
+            }
+        }
+        for (field, port) in [(6, self.query_port), (7, self.response_port)] {
+            if let Some(port) = port {
+                write_varint_field(&mut buf, field, port as u64);
+            }
+        }
+        if let Some(time) = self.query_time {
+            write_varint_field(&mut buf, 8, time.as_secs());
+            write_fixed32_field(&mut buf, 9, time.subsec_nanos());
+        }
+        if let Some(query) = &self.query_message {
+            write_bytes_field(&mut buf, 10, query);
+        }
+        if let Some(zone) = &self.query_zone {
+            write_bytes_field(&mut buf, 11, zone);
+        }
+        if let Some(time) = self.response_time {
+            write_varint_field(&mut buf, 12, time.as_secs());
+            write_fixed32_field(&mut buf, 13, time.subsec_nanos());
+        }
+        if let Some(response) = &self.response_message {
+            write_bytes_field(&mut buf, 14, response);
+        }
+        buf
+    }
+}
+
+fn as_u32(value: &WireValue) -> Result<u32, DnstapError> {
+    u32::try_from(value.as_varint()?)
+        .map_err(|_| DnstapError::InvalidProtobuf("value out of range"))
+}
+
+fn as_port(value: &WireValue) -> Result<u16, DnstapError> {
+    u16::try_from(value.as_varint()?).map_err(|_| DnstapError::InvalidProtobuf("port out of range"))
+}
+
+fn as_time(seconds: Option<u64>, nanos: u32) -> Result<Option<Duration>, DnstapError> {
+    if nanos >= 1_000_000_000 {
+        return Err(DnstapError::InvalidProtobuf("nanoseconds out of range"));
+    }
+    Ok(seconds.map(|seconds| Duration::new(seconds, nanos)))
+}
+
+fn as_address(value: &WireValue) -> Result<IpAddr, DnstapError> {
+    let bytes = value.as_bytes()?;
+    if let Ok(octets) = <[u8; 4]>::try_from(bytes) {
+        Ok(IpAddr::V4(Ipv4Addr::from(octets)))
+    } else if let Ok(octets) = <[u8; 16]>::try_from(bytes) {
+        Ok(IpAddr::V6(Ipv6Addr::from(octets)))
+    } else {
+        Err(DnstapError::InvalidProtobuf("invalid address length"))
+    }
+}
+
+/// Iterates over the dnstap frames of a Frame Streams file or unidirectional socket.
+pub struct DnstapReader<R> {
+    frames: FrameStreamReader<R>,
+}
+
+impl<R: Read> DnstapReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_max_frame_length(reader, DEFAULT_MAX_FRAME_LENGTH)
+    }
+
+    /// Creates a reader that rejects the frames longer than `max_frame_length`.
+    pub fn with_max_frame_length(reader: R, max_frame_length: u32) -> Self {
+        DnstapReader {
+            frames: FrameStreamReader::with_max_frame_length(
+                reader,
+                CONTENT_TYPE,
+                max_frame_length,
+            ),
+        }
+    }
+}
+
+impl<R: Read> Iterator for DnstapReader<R> {
+    type Item = Result<Dnstap, DnstapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.frames.read_frame() {
+            Ok(Some(frame)) => Some(Dnstap::decode(&frame)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Writes dnstap frames as a Frame Streams file.
+pub struct DnstapWriter<W: Write> {
+    frames: FrameStreamWriter<W>,
+}
+
+impl<W: Write> DnstapWriter<W> {
+    pub fn new(writer: W) -> Result<Self, DnstapError> {
+        Ok(DnstapWriter {
+            frames: FrameStreamWriter::new(writer, CONTENT_TYPE)?,
+        })
+    }
+
+    pub fn write(&mut self, dnstap: &Dnstap) -> Result<(), DnstapError> {
+        self.frames.write_frame(&dnstap.encode())
+    }
+
+    /// Ends the stream and returns the underlying writer.
+    pub fn finish(self) -> Result<W, DnstapError> {
+        self.frames.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUERY: &str = "abcd01000001000000000000076578616d706c6503636f6d0000010001";
+    const RESPONSE: &str = "abcd81800001000100000000076578616d706c6503636f6d0000010001c00c000100010000012c00045db8d822";
+
+    #[test]
+    fn test_dnstap_file_round_trip() {
+        let client: SocketAddr = "192.0.2.10:53000".parse().unwrap();
+        let server: SocketAddr = "[2001:db8::53]:53".parse().unwrap();
+        let query = hex::decode(QUERY).unwrap();
+        let response = hex::decode(RESPONSE).unwrap();
+
+        let frames = [
+            (MessageTypes::CLIENT_QUERY, &query),
+            (MessageTypes::CLIENT_RESPONSE, &response),
+        ]
+        .map(|(message_type, payload)| Dnstap {
+            identity: Some(b"resolver1".to_vec()),
+            version: None,
+            extra: None,
+            message: Some(Message::from_captured(
+                message_type,
+                payload,
+                client,
+                server,
+                SocketProtocols::UDP,
+                Duration::new(1_700_000_000, 123_456_789),
+            )),
+        });
+
+        let mut writer = DnstapWriter::new(Vec::new()).unwrap();
+        for frame in &frames {
+            writer.write(frame).unwrap();
+        }
+        let bytes = writer.finish().unwrap();
+
+        let decoded = DnstapReader::new(bytes.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, frames);
+
+        let query_message = decoded[0].message.as_ref().unwrap();
+        assert_eq!(query_message.query_port, Some(53000));
+        assert!(query_message.response_packet().is_none());
+        let packet = query_message.query_packet().unwrap().unwrap();
+        assert_eq!(packet.queries.queries[0].name, "example.com");
+
+        let response_message = decoded[1].message.as_ref().unwrap();
+        let packet = response_message.response_packet().unwrap().unwrap();
+        assert_eq!(
+            packet.answers.unwrap()[0].rdata.to_string(),
+            "93.184.216.34"
+        );
+    }
+
+    #[test]
+    fn test_dnstap_decode_hand_encoded_message() {
+        // Message encoded by hand rather than with `Message::encode`: type RESOLVER_QUERY,
+        // family INET, protocol UDP, addresses, ports, query time and an unknown field 16
+        // (HTTP protocol).
+        let mut bytes = hex::decode("080310011801220498c60001").unwrap();
+        bytes.extend(hex::decode("2a04c0000201").unwrap());
+        bytes.extend(hex::decode("30b9a20338354065").unwrap());
+        bytes.extend(hex::decode("4d00000000").unwrap());
+        bytes.extend(hex::decode("8001").unwrap());
+        bytes.push(0x02);
+        let message = Message::decode(&bytes).unwrap();
+        assert_eq!(message.message_type, MessageTypes::RESOLVER_QUERY);
+        assert_eq!(message.socket_protocol, Some(SocketProtocols::UDP));
+        assert_eq!(message.query_address, Some("152.198.0.1".parse().unwrap()));
+        assert_eq!(message.response_address, Some("192.0.2.1".parse().unwrap()));
+        assert_eq!(message.query_port, Some(53561));
+        assert_eq!(message.response_port, Some(53));
+        assert_eq!(message.query_time, Some(Duration::from_secs(101)));
+    }
+
+    #[test]
+    fn test_dnstap_missing_type() {
+        assert!(matches!(
+            Dnstap::decode(&[]),
+            Err(DnstapError::MissingField("Dnstap.type"))
+        ));
+        assert!(matches!(
+            Message::decode(&[0x30, 0x35]),
+            Err(DnstapError::MissingField("Message.type"))
+        ));
+    }
+}
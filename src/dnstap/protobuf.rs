@@ -0,0 +1,158 @@
+//! Just enough of the protobuf wire format to read and write dnstap messages.
+
+use super::errors::DnstapError;
+
+/// A decoded field value, by wire type.
+pub(crate) enum WireValue<'a> {
+    Varint(u64),
+    Fixed64,
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+impl<'a> WireValue<'a> {
+    pub(crate) fn as_varint(&self) -> Result<u64, DnstapError> {
+        match self {
+            WireValue::Varint(value) => Ok(*value),
+            _ => Err(DnstapError::InvalidProtobuf("expected a varint field")),
+        }
+    }
+
+    pub(crate) fn as_fixed32(&self) -> Result<u32, DnstapError> {
+        match self {
+            WireValue::Fixed32(value) => Ok(*value),
+            _ => Err(DnstapError::InvalidProtobuf("expected a fixed32 field")),
+        }
+    }
+
+    pub(crate) fn as_bytes(&self) -> Result<&'a [u8], DnstapError> {
+        match self {
+            WireValue::Bytes(bytes) => Ok(bytes),
+            _ => Err(DnstapError::InvalidProtobuf(
+                "expected a length-delimited field",
+            )),
+        }
+    }
+}
+
+/// Iterates over the `(field number, value)` pairs of an encoded message.
+pub(crate) struct ProtobufReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ProtobufReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        ProtobufReader { bytes, offset: 0 }
+    }
+
+    pub(crate) fn next_field(&mut self) -> Result<Option<(u32, WireValue<'a>)>, DnstapError> {
+        if self.offset == self.bytes.len() {
+            return Ok(None);
+        }
+        let key = self.read_varint()?;
+        let field = u32::try_from(key >> 3)
+            .map_err(|_| DnstapError::InvalidProtobuf("field number out of range"))?;
+        let value = match key & 0b111 {
+            0 => WireValue::Varint(self.read_varint()?),
+            1 => {
+                self.read_bytes(8)?;
+                WireValue::Fixed64
+            }
+            2 => {
+                let len = usize::try_from(self.read_varint()?)
+                    .map_err(|_| DnstapError::InvalidProtobuf("length out of range"))?;
+                WireValue::Bytes(self.read_bytes(len)?)
+            }
+            5 => WireValue::Fixed32(u32::from_le_bytes(self.read_array()?)),
+            _ => return Err(DnstapError::InvalidProtobuf("unsupported wire type")),
+        };
+        Ok(Some((field, value)))
+    }
+
+    fn read_varint(&mut self) -> Result<u64, DnstapError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_bytes(1)?[0];
+            value |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DnstapError::InvalidProtobuf("varint too long"))
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], DnstapError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.read_bytes(N)?);
+        Ok(array)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], DnstapError> {
+        if len > self.bytes.len() - self.offset {
+            return Err(DnstapError::InvalidProtobuf("truncated message"));
+        }
+        self.offset += len;
+        Ok(&self.bytes[self.offset - len..self.offset])
+    }
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn write_key(buf: &mut Vec<u8>, field: u32, wire_type: u8) {
+    write_varint(buf, ((field as u64) << 3) | wire_type as u64);
+}
+
+pub(crate) fn write_varint_field(buf: &mut Vec<u8>, field: u32, value: u64) {
+    write_key(buf, field, 0);
+    write_varint(buf, value);
+}
+
+pub(crate) fn write_bytes_field(buf: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    write_key(buf, field, 2);
+    write_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+pub(crate) fn write_fixed32_field(buf: &mut Vec<u8>, field: u32, value: u32) {
+    write_key(buf, field, 5);
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protobuf_round_trip() {
+        let mut buf = Vec::new();
+        write_varint_field(&mut buf, 1, 300);
+        write_bytes_field(&mut buf, 14, b"abc");
+        write_fixed32_field(&mut buf, 9, 0xdeadbeef);
+        assert_eq!(&buf[..3], &[0x08, 0xac, 0x02]);
+
+        let mut reader = ProtobufReader::new(&buf);
+        let (field, value) = reader.next_field().unwrap().unwrap();
+        assert_eq!((field, value.as_varint().unwrap()), (1, 300));
+        let (field, value) = reader.next_field().unwrap().unwrap();
+        assert_eq!((field, value.as_bytes().unwrap()), (14, &b"abc"[..]));
+        let (field, value) = reader.next_field().unwrap().unwrap();
+        assert_eq!((field, value.as_fixed32().unwrap()), (9, 0xdeadbeef));
+        assert!(reader.next_field().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_protobuf_truncated() {
+        let mut reader = ProtobufReader::new(&[0x72, 0x05, b'a']);
+        assert!(matches!(
+            reader.next_field(),
+            Err(DnstapError::InvalidProtobuf("truncated message"))
+        ));
+    }
+}
@@ -1,14 +1,131 @@
+use core::fmt;
+
 use thiserror::Error;
 
 use crate::dns_header::errors::DnsHeaderError;
 use crate::dns_queries::errors::DnsQueryParseError;
+use crate::dns_records::errors::DnsRecordParseError;
+#[cfg(feature = "alloc")]
+use crate::prelude::*;
+#[cfg(feature = "alloc")]
+use crate::utils::hexdump::hexdump;
 
+/// An error met while parsing a message, with where it happened.
+///
+/// `offset` is the absolute position in the message of the byte that could not be
+/// parsed: the first missing byte when the message is too short, the start of the
+/// offending field otherwise.
 #[derive(Debug, Error)]
-pub enum DnsPacketError {
+#[error("{kind} ({location}, offset {offset})")]
+pub struct DnsPacketError {
+    pub kind: DnsErrorKind,
+    pub location: Location,
+    pub offset: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum DnsErrorKind {
     #[error("Insufficient data: expected at least {expected} bytes, but got {actual}")]
     InsufficientData { expected: usize, actual: usize },
     #[error("DNS header parsing error: {0}")]
     HeaderError(#[from] DnsHeaderError),
     #[error("DNS Query parsing error: {0}")]
     QueryError(#[from] DnsQueryParseError),
+    #[error("DNS Record parsing error: {0}")]
+    RecordError(#[from] DnsRecordParseError),
+    #[error("Trailing data: the message ends after {consumed} of {length} bytes")]
+    TrailingData { consumed: usize, length: usize },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Section {
+    Header,
+    Question,
+    Answer,
+    Authority,
+    Additional,
+    /// After the last declared record.
+    Trailing,
+}
+
+/// The section of the message, and the entry within it for the question and record
+/// sections (0-based).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Location {
+    pub section: Section,
+    pub index: Option<usize>,
+}
+
+impl DnsPacketError {
+    /// Builds an error for an entry starting at `start`; when `kind` knows the exact
+    /// failing byte, the error points at it instead.
+    pub(crate) fn new(
+        kind: impl Into<DnsErrorKind>,
+        section: Section,
+        index: Option<usize>,
+        start: usize,
+    ) -> Self {
+        let kind = kind.into();
+        let offset = kind.offset().unwrap_or(start);
+        Self {
+            kind,
+            location: Location { section, index },
+            offset,
+        }
+    }
+
+    pub(crate) fn trailing_data(consumed: usize, length: usize) -> Self {
+        let kind = DnsErrorKind::TrailingData { consumed, length };
+        Self::new(kind, Section::Trailing, None, consumed)
+    }
+
+    /// Renders a hexdump of `bytes`, the message the error comes from, pointing at the
+    /// failing byte; meant for bug reports.
+    #[cfg(feature = "alloc")]
+    pub fn diagnose(&self, bytes: &[u8]) -> String {
+        format!(
+            "{}\n{}",
+            self,
+            hexdump(bytes, self.offset, &self.location.to_string())
+        )
+    }
+}
+
+impl DnsErrorKind {
+    /// The absolute offset of the failing byte, when the underlying error tells it.
+    fn offset(&self) -> Option<usize> {
+        match self {
+            DnsErrorKind::InsufficientData { actual, .. } => Some(*actual),
+            DnsErrorKind::HeaderError(error) => Some(error.offset()),
+            DnsErrorKind::QueryError(error) => error.offset(),
+            DnsErrorKind::RecordError(error) => error.offset(),
+            DnsErrorKind::TrailingData { consumed, .. } => Some(*consumed),
+        }
+    }
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Section::Header => "header",
+                Section::Question => "question",
+                Section::Answer => "answer",
+                Section::Authority => "authority",
+                Section::Additional => "additional",
+                Section::Trailing => "trailing data",
+            }
+        )
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "{} {}", self.section, index),
+            None => write!(f, "{}", self.section),
+        }
+    }
 }
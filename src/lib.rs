@@ -1,61 +1,182 @@
+//! Parsing and detection of DNS messages.
+//!
+//! The default `std` feature can be turned off for `#![no_std]` targets. With the `alloc`
+//! feature, the owned `DnsPacket` and everything built on it remain available; without
+//! it, the header, its flags and the borrowed `DnsPacketRef` parse without allocating.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "cdns")]
+pub mod cdns;
+#[cfg(feature = "alloc")]
+mod detection;
+#[cfg(feature = "alloc")]
 mod dns_additional;
+#[cfg(feature = "alloc")]
 mod dns_answers;
+#[cfg(feature = "alloc")]
 mod dns_authoritative;
 mod dns_header;
 mod dns_queries;
+mod dns_records;
+#[cfg(feature = "dnssec")]
+pub mod dnssec;
+#[cfg(feature = "dnstap")]
+pub mod dnstap;
+mod errors;
+mod packet_ref;
+mod prefilter;
+#[cfg(feature = "alloc")]
+mod prelude;
+#[cfg(feature = "alloc")]
+pub mod protocols;
+#[cfg(feature = "alloc")]
+mod recovery;
+#[cfg(feature = "rfc8427")]
+pub mod rfc8427;
+#[cfg(feature = "tsig")]
+pub mod tsig;
 pub mod utils;
+pub mod validation;
 
-use dns_additional::AdditionalRecord;
-use dns_answers::Answer;
-use dns_authoritative::AuthoritativeNameServer;
-use dns_header::DnsHeader;
-use dns_queries::DnsQueries;
-use errors::DnsPacketError;
-use std::fmt;
-mod errors;
+#[cfg(feature = "alloc")]
+use core::fmt;
+#[cfg(feature = "alloc")]
+pub use detection::{detect, Detection, Heuristic, DNS_THRESHOLD};
+#[cfg(feature = "alloc")]
+pub use dns_additional::AdditionalRecord;
+#[cfg(feature = "alloc")]
+pub use dns_answers::Answer;
+#[cfg(feature = "alloc")]
+pub use dns_authoritative::AuthoritativeNameServer;
+pub use dns_header::DnsHeader;
+#[cfg(feature = "alloc")]
+pub use dns_queries::{DnsQueries, DnsQuery};
+#[cfg(feature = "alloc")]
+use dns_records::parse_records;
+#[cfg(feature = "alloc")]
+pub use dns_records::{rdata, ResourceRecord};
+pub use dns_records::{Labels, NameRef};
+pub use errors::{DnsErrorKind, DnsPacketError, Location, Section};
+pub use packet_ref::{DnsPacketRef, QuestionRef, Questions, RecordRef, Records};
+pub use prefilter::is_probably_dns;
+#[cfg(feature = "alloc")]
+use prelude::*;
+#[cfg(feature = "alloc")]
+pub use recovery::PartialPacket;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "alloc")]
+use validation::{ParseOptions, Rule, Warning};
 
-#[derive(Debug)]
+/// A parsed DNS packet.
+///
+/// With the `serde` feature, the packet serializes to JSON as the header, the list of
+/// queries and the three record sections (`null` when empty). Types and classes are
+/// written as mnemonics (`"AAAA"`, `"IN"`, or `"TYPE65280"` for unknown ones) and record
+/// data as typed objects, see [`rdata::RData`].
+///
+/// `warnings` lists the rules broken by the packet that `DnsPacket::parse_with` was told
+/// to tolerate; it is always empty after `try_from`.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DnsPacket {
     pub header: DnsHeader,
     pub queries: DnsQueries,
     pub answers: Option<Vec<Answer>>, // List of answer records
     pub authorities: Option<Vec<AuthoritativeNameServer>>, // List of authority records
     pub additionals: Option<Vec<AdditionalRecord>>, // List of additional records
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub warnings: Vec<Warning>,
 }
 
+#[cfg(feature = "alloc")]
 impl TryFrom<&[u8]> for DnsPacket {
     type Error = DnsPacketError;
 
     fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse_with(bytes, &ParseOptions::default())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl DnsPacket {
+    /// Parses a packet, with `options` deciding which validation rules are errors and
+    /// which are only reported in `warnings`.
+    pub fn parse_with(bytes: &[u8], options: &ParseOptions) -> Result<Self, DnsPacketError> {
+        let (mut packet, consumed) = Self::parse_prefix(bytes, options)?;
+        if consumed < bytes.len() {
+            let error = DnsPacketError::trailing_data(consumed, bytes.len());
+            options.check(Rule::TrailingData, error, &mut packet.warnings)?;
+        }
+        Ok(packet)
+    }
+
+    /// Iterates over the records of the answer, authority and additional sections.
+    pub fn records(&self) -> impl Iterator<Item = &ResourceRecord> {
+        [&self.answers, &self.authorities, &self.additionals]
+            .into_iter()
+            .flatten()
+            .flatten()
+    }
+
+    /// Parses the message at the start of `bytes`, ignoring what follows it, and returns
+    /// it with the number of bytes it occupies.
+    pub fn parse_prefix(
+        bytes: &[u8],
+        options: &ParseOptions,
+    ) -> Result<(Self, usize), DnsPacketError> {
         check_dns_minimum_size(bytes)?;
 
-        let header = DnsHeader::try_from(bytes)?;
-        let queries = DnsQueries::from_bytes(&bytes[12..], header.counts[0])?;
-        let answers = None;
-        let authorities = None;
-        let additionals = None;
+        let mut warnings = Vec::new();
+        let header = DnsHeader::parse_with(bytes, options, &mut warnings)
+            .map_err(|error| DnsPacketError::new(error, Section::Header, None, 0))?;
+        let mut offset = 12;
+        let mut queries = Vec::with_capacity(header.counts[0] as usize);
+        for index in 0..header.counts[0] as usize {
+            let start = offset;
+            let query = DnsQuery::from_bytes(bytes, &mut offset).map_err(|error| {
+                DnsPacketError::new(error, Section::Question, Some(index), start)
+            })?;
+            queries.push(query);
+        }
+        let queries = DnsQueries { queries };
+        let answers = parse_records(bytes, &mut offset, header.counts[1], Section::Answer)?;
+        let authorities = parse_records(bytes, &mut offset, header.counts[2], Section::Authority)?;
+        let additionals = parse_records(bytes, &mut offset, header.counts[3], Section::Additional)?;
 
-        Ok(DnsPacket {
+        let packet = DnsPacket {
             header,
             queries,
             answers,
             authorities,
             additionals,
-        })
+            warnings,
+        };
+        Ok((packet, offset))
     }
 }
 
 fn check_dns_minimum_size(bytes: &[u8]) -> Result<(), DnsPacketError> {
     const DNS_MINIMUM_SIZE: usize = 12; // Taille minimale pour un en-tête DNS
     if bytes.len() < DNS_MINIMUM_SIZE {
-        return Err(DnsPacketError::InsufficientData {
+        let error = DnsErrorKind::InsufficientData {
             expected: DNS_MINIMUM_SIZE,
             actual: bytes.len(),
-        });
+        };
+        return Err(DnsPacketError::new(error, Section::Header, None, 0));
     }
     Ok(())
 }
 
+#[cfg(feature = "alloc")]
 impl fmt::Display for DnsPacket {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -69,7 +190,12 @@ impl fmt::Display for DnsPacket {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "alloc")]
+    use crate::rdata::RData;
+    #[cfg(feature = "alloc")]
+    use crate::utils::dns_types::DnsTypes;
 
+    #[cfg(feature = "alloc")]
     #[test]
     fn test_dns_packet_parsing() {
         // Example DNS packet data
@@ -84,11 +210,22 @@ mod tests {
                 assert_eq!(packet.header.counts[1], 15);
                 assert_eq!(packet.header.counts[2], 6);
                 assert_eq!(packet.header.counts[3], 2);
+
+                let answers = packet.answers.expect("answers should be parsed");
+                assert_eq!(answers.len(), 15);
+                assert_eq!(answers[0].name, "us.pool.ntp.org");
+                assert_eq!(answers[0].rdata.to_string(), "67.129.68.9");
+                let authorities = packet.authorities.expect("authorities should be parsed");
+                assert_eq!(authorities.len(), 6);
+                assert_eq!(authorities[0].rdata.to_string(), "ns1.mailworx.net");
+                let additionals = packet.additionals.expect("additionals should be parsed");
+                assert_eq!(additionals.len(), 2);
             }
             Err(e) => panic!("Error parsing DNS packet: {}", e),
         }
     }
 
+    #[cfg(feature = "alloc")]
     #[test]
     fn test_dns_packet_parsing_return_error() {
         // Example non-DNS packet data
@@ -104,6 +241,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "alloc")]
     #[test]
     fn test_ssl_packet_parsing_return_error() {
         // Example ssl packet data
@@ -113,13 +251,14 @@ mod tests {
             Ok(_) => panic!("Expected error, but parsing succeeded"),
             Err(e) => assert!(
                 e.to_string()
-                    .contains("required 1 more bytes at offset 0, but only 0 bytes available"),
+                    .contains("required 1 more bytes at offset 12, but only 0 bytes available"),
                 "Unexpected error: {}",
                 e
             ),
         }
     }
 
+    #[cfg(feature = "alloc")]
     #[test]
     fn test_rtcp_packet_parsing_return_error() {
         // Payload RTCP en hexadécimal
@@ -137,17 +276,167 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_dns_packet_parsing_truncated_answer_return_error() {
+        // Response for example.com whose A record is cut in the middle of its data
+        let data = hex::decode("abcd81800001000100000000076578616d706c6503636f6d0000010001c00c000100010000012c00045db8").expect("Invalid hex string");
+
+        match DnsPacket::try_from(data.as_slice()) {
+            Ok(_) => panic!("Expected error, but parsing succeeded"),
+            Err(e) => assert!(
+                matches!(e.kind, DnsErrorKind::RecordError(_)),
+                "Unexpected error: {}",
+                e
+            ),
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_dns_packet_parsing_keeps_malformed_rrsig() {
+        // Response for example.com with its A record and an RRSIG cut after 5 bytes
+        let data = hex::decode("abcd81800001000200000000076578616d706c6503636f6d0000010001c00c000100010000012c00045db8d822c00c002e00010000012c000500010d0200").expect("Invalid hex string");
+
+        let packet = DnsPacket::try_from(data.as_slice()).unwrap();
+        let answers = packet.answers.unwrap();
+        assert_eq!(answers[0].rdata.to_string(), "93.184.216.34");
+        assert_eq!(answers[1].rtype, DnsTypes::RRSIG);
+        assert_eq!(
+            answers[1].rdata,
+            RData::Unknown(vec![0x00, 0x01, 0x0d, 0x02, 0x00])
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_dns_packet_serde() {
+        let data = hex::decode("abcd81800001000100000000076578616d706c6503636f6d0000010001c00c000100010000012c00045db8d822").expect("Invalid hex string");
+        let packet = DnsPacket::try_from(data.as_slice()).unwrap();
+
+        let json = serde_json::to_value(&packet).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "header": { "transaction_id": 0xabcd, "flags": 0x8180, "counts": [1, 1, 0, 0] },
+                "queries": [{ "name": "example.com", "qtype": "A", "qclass": "IN" }],
+                "answers": [{
+                    "name": "example.com",
+                    "rtype": "A",
+                    "rclass": "IN",
+                    "ttl": 300,
+                    "data_length": 4,
+                    "rdata": { "A": "93.184.216.34" }
+                }],
+                "authorities": null,
+                "additionals": null
+            })
+        );
+
+        let decoded: DnsPacket = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.queries, packet.queries);
+        assert_eq!(decoded.answers, packet.answers);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_dns_packet_parse_with_profiles() {
+        use validation::{Rule, RuleAction, ValidationProfile};
+
+        // NXDOMAIN from a recursive resolver: AA=0 and AD=1.
+        let data =
+            hex::decode("abcd81a30001000000000000076578616d706c6503636f6d0000010001").unwrap();
+        assert!(DnsPacket::try_from(data.as_slice()).is_err());
+
+        let packet =
+            DnsPacket::parse_with(&data, &ParseOptions::new(ValidationProfile::Rfc)).unwrap();
+        assert!(packet.warnings.is_empty());
+
+        let options = ParseOptions::new(ValidationProfile::Rfc)
+            .with_rule(Rule::AaInNameError, RuleAction::Warn);
+        let packet = DnsPacket::parse_with(&data, &options).unwrap();
+        assert_eq!(packet.warnings.len(), 1);
+        assert_eq!(packet.warnings[0].rule, Rule::AaInNameError);
+
+        // A query with RA set and the reserved Z bit set.
+        let data =
+            hex::decode("abcd01c00001000000000000076578616d706c6503636f6d0000010001").unwrap();
+        assert!(DnsPacket::parse_with(&data, &ParseOptions::new(ValidationProfile::Rfc)).is_err());
+        let packet =
+            DnsPacket::parse_with(&data, &ParseOptions::new(ValidationProfile::Lenient)).unwrap();
+        let rules = packet.warnings.iter().map(|w| w.rule).collect::<Vec<_>>();
+        assert_eq!(rules, [Rule::ReservedZBit]);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_dns_packet_trailing_data() {
+        let mut data = hex::decode("abcd81800001000100000000076578616d706c6503636f6d0000010001c00c000100010000012c00045db8d822").unwrap();
+        let length = data.len();
+        data.extend_from_slice(b"\x00garbage");
+
+        assert!(matches!(
+            DnsPacket::try_from(data.as_slice()),
+            Err(DnsPacketError { kind: DnsErrorKind::TrailingData { consumed, length: 53 }, .. }) if consumed == length
+        ));
+
+        let (packet, consumed) = DnsPacket::parse_prefix(&data, &ParseOptions::default()).unwrap();
+        assert_eq!(consumed, length);
+        assert_eq!(packet.answers.unwrap().len(), 1);
+
+        let options = ParseOptions::new(validation::ValidationProfile::Lenient);
+        let packet = DnsPacket::parse_with(&data, &options).unwrap();
+        assert_eq!(packet.warnings[0].rule, Rule::TrailingData);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_error_location_and_diagnose() {
+        // A response whose second answer stops in the middle of its data.
+        let data = hex::decode(
+            "abcd81800001000200000000076578616d706c6503636f6d0000010001\
+             c00c000100010000012c00045db8d822c00c000100010000012c00045db8",
+        )
+        .unwrap();
+        let error = DnsPacket::try_from(data.as_slice()).unwrap_err();
+        assert!(matches!(error.kind, DnsErrorKind::RecordError(_)));
+        assert_eq!(
+            error.location,
+            Location {
+                section: Section::Answer,
+                index: Some(1)
+            }
+        );
+        assert_eq!(error.offset, data.len());
+
+        let diagnosis = error.diagnose(&data);
+        assert!(diagnosis.starts_with(&error.to_string()));
+        assert!(diagnosis.contains("0030  01 00 01 00 00 01 2c 00  04 5d b8"));
+        assert!(diagnosis.ends_with("^^ answer 1\n"));
+
+        // The Z bit is in the flags, at offset 2.
+        let mut data = data;
+        data[3] |= 0x40;
+        let error = DnsPacket::try_from(data.as_slice()).unwrap_err();
+        assert_eq!(error.location.section, Section::Header);
+        assert_eq!(error.offset, 2);
+    }
+
     #[test]
     fn test_check_dns_minimum_size_insufficient_data() {
         let data = vec![0; 10]; // Seulement 10 octets, donc insuffisant pour un paquet DNS
         let result = check_dns_minimum_size(&data);
         assert!(result.is_err());
-        if let Err(DnsPacketError::InsufficientData { expected, actual }) = result {
+        if let Err(DnsPacketError {
+            kind: DnsErrorKind::InsufficientData { expected, actual },
+            ..
+        }) = result
+        {
             assert_eq!(expected, 12);
             assert_eq!(actual, 10);
         } else {
             panic!(
-                "Expected DnsPacketError::InsufficientData, but got {:?}",
+                "Expected DnsErrorKind::InsufficientData, but got {:?}",
                 result
             );
         }
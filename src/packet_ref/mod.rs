@@ -0,0 +1,365 @@
+//! A borrowed view of a message, for parsing without allocating.
+//!
+//! `DnsPacketRef::parse` checks the header rules and the structure of every question and
+//! record (names, fixed fields and data lengths) once, without copying anything. The
+//! questions and records are then read lazily from the buffer, and names are only
+//! decoded when displayed or compared. Record data is kept as raw bytes: `to_record` and
+//! `to_owned` decode it into the owned types, with the `alloc` feature.
+//!
+//! Nothing is allocated, even for the rules downgraded to warnings, which are kept as a
+//! `RuleSet`; so the view is available without the `alloc` feature.
+
+use crate::dns_queries::errors::DnsQueryParseError;
+use crate::dns_records::errors::DnsRecordParseError;
+use crate::dns_records::{check_record_size, NameRef};
+use crate::errors::{DnsPacketError, Section};
+#[cfg(feature = "alloc")]
+use crate::prelude::*;
+#[cfg(feature = "alloc")]
+use crate::rdata::RData;
+use crate::utils::{dns_class::DnsClass, dns_types::DnsType};
+use crate::validation::{ParseOptions, Rule, RuleSet};
+use crate::{check_dns_minimum_size, DnsHeader};
+#[cfg(feature = "alloc")]
+use crate::{DnsPacket, DnsQuery, ResourceRecord};
+
+/// A validated message borrowed from its buffer.
+#[derive(Clone, Debug)]
+pub struct DnsPacketRef<'a> {
+    bytes: &'a [u8],
+    pub header: DnsHeader,
+    /// The offsets of the question, answer, authority and additional sections.
+    sections: [usize; 4],
+    length: usize,
+    #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+    options: ParseOptions,
+    /// The rules broken by the message that `options` downgraded to warnings.
+    pub warnings: RuleSet,
+}
+
+impl<'a> TryFrom<&'a [u8]> for DnsPacketRef<'a> {
+    type Error = DnsPacketError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::parse_with(bytes, &ParseOptions::default())
+    }
+}
+
+impl<'a> DnsPacketRef<'a> {
+    /// Validates the message in `bytes`, with `options` deciding which rules are errors,
+    /// as `DnsPacket::parse_with` does.
+    pub fn parse_with(bytes: &'a [u8], options: &ParseOptions) -> Result<Self, DnsPacketError> {
+        check_dns_minimum_size(bytes)?;
+
+        let mut warnings = RuleSet::default();
+        let header = DnsHeader::parse_with(bytes, options, &mut warnings)
+            .map_err(|error| DnsPacketError::new(error, Section::Header, None, 0))?;
+
+        let mut sections = [12; 4];
+        let mut offset = 12;
+        for index in 0..header.counts[0] as usize {
+            let start = offset;
+            offset = skip_question(bytes, offset).map_err(|error| {
+                DnsPacketError::new(error, Section::Question, Some(index), start)
+            })?;
+        }
+        let records = [Section::Answer, Section::Authority, Section::Additional];
+        for (i, section) in records.into_iter().enumerate() {
+            sections[i + 1] = offset;
+            for index in 0..header.counts[i + 1] as usize {
+                let start = offset;
+                offset = skip_record(bytes, offset)
+                    .map_err(|error| DnsPacketError::new(error, section, Some(index), start))?;
+            }
+        }
+
+        if offset < bytes.len() {
+            let error = DnsPacketError::trailing_data(offset, bytes.len());
+            options.check(Rule::TrailingData, error, &mut warnings)?;
+        }
+        Ok(DnsPacketRef {
+            bytes,
+            header,
+            sections,
+            length: offset,
+            options: *options,
+            warnings,
+        })
+    }
+
+    /// The bytes of the message, without any trailing data.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        &self.bytes[..self.length]
+    }
+
+    pub fn questions(&self) -> Questions<'a> {
+        Questions {
+            bytes: self.bytes,
+            offset: self.sections[0],
+            remaining: self.header.counts[0],
+        }
+    }
+
+    pub fn answers(&self) -> Records<'a> {
+        self.section(1)
+    }
+
+    pub fn authorities(&self) -> Records<'a> {
+        self.section(2)
+    }
+
+    pub fn additionals(&self) -> Records<'a> {
+        self.section(3)
+    }
+
+    /// Iterates over the records of the answer, authority and additional sections.
+    pub fn records(&self) -> Records<'a> {
+        Records {
+            bytes: self.bytes,
+            offset: self.sections[1],
+            remaining: self.header.counts[1..]
+                .iter()
+                .map(|&count| count as usize)
+                .sum(),
+        }
+    }
+
+    fn section(&self, index: usize) -> Records<'a> {
+        Records {
+            bytes: self.bytes,
+            offset: self.sections[index],
+            remaining: self.header.counts[index] as usize,
+        }
+    }
+
+    /// Decodes the message into an owned `DnsPacket`, with the options it was parsed
+    /// with; fails if some record data cannot be decoded.
+    #[cfg(feature = "alloc")]
+    pub fn to_owned(&self) -> Result<DnsPacket, DnsPacketError> {
+        DnsPacket::parse_with(self.bytes, &self.options)
+    }
+}
+
+/// A question borrowed from a message.
+#[derive(Clone, Copy, Debug)]
+pub struct QuestionRef<'a> {
+    pub name: NameRef<'a>,
+    pub qtype: DnsType,
+    pub qclass: DnsClass,
+}
+
+#[cfg(feature = "alloc")]
+impl QuestionRef<'_> {
+    pub fn to_query(&self) -> DnsQuery {
+        DnsQuery {
+            name: self.name.to_string(),
+            qtype: self.qtype,
+            qclass: self.qclass,
+        }
+    }
+}
+
+/// A resource record borrowed from a message, with its data undecoded.
+#[derive(Clone, Copy, Debug)]
+pub struct RecordRef<'a> {
+    pub name: NameRef<'a>,
+    pub rtype: DnsType,
+    pub rclass: DnsClass,
+    pub ttl: u32,
+    pub rdata: &'a [u8],
+    #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+    bytes: &'a [u8],
+    #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+    rdata_offset: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl RecordRef<'_> {
+    /// Decodes the record, with its data, into an owned `ResourceRecord`.
+    pub fn to_record(&self) -> Result<ResourceRecord, DnsRecordParseError> {
+        let end = self.rdata_offset + self.rdata.len();
+        Ok(ResourceRecord {
+            name: self.name.to_string(),
+            rtype: self.rtype,
+            rclass: self.rclass,
+            ttl: self.ttl,
+            data_length: self.rdata.len() as u16,
+            rdata: RData::from_bytes(&self.bytes[..end], self.rdata_offset, self.rtype)?,
+        })
+    }
+}
+
+/// The questions of a `DnsPacketRef`.
+#[derive(Clone, Debug)]
+pub struct Questions<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    remaining: u16,
+}
+
+impl<'a> Iterator for Questions<'a> {
+    type Item = QuestionRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.remaining = self.remaining.checked_sub(1)?;
+        let (name, offset) = NameRef::validated(self.bytes, self.offset)?;
+        let fixed = self.bytes.get(offset..offset + 4)?;
+        self.offset = offset + 4;
+        Some(QuestionRef {
+            name,
+            qtype: DnsType::new(u16::from_be_bytes([fixed[0], fixed[1]])),
+            qclass: DnsClass::new(u16::from_be_bytes([fixed[2], fixed[3]])),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining as usize, Some(self.remaining as usize))
+    }
+}
+
+/// The records of one or more sections of a `DnsPacketRef`.
+#[derive(Clone, Debug)]
+pub struct Records<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    remaining: usize,
+}
+
+#[cfg(feature = "tsig")]
+impl Records<'_> {
+    /// The offset of the next record in the message.
+    pub(crate) fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = RecordRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.remaining = self.remaining.checked_sub(1)?;
+        let (name, offset) = NameRef::validated(self.bytes, self.offset)?;
+        let fixed = self.bytes.get(offset..offset + 10)?;
+        let rdata_offset = offset + 10;
+        let length = u16::from_be_bytes([fixed[8], fixed[9]]) as usize;
+        let rdata = self.bytes.get(rdata_offset..rdata_offset + length)?;
+        self.offset = rdata_offset + length;
+        Some(RecordRef {
+            name,
+            rtype: DnsType::new(u16::from_be_bytes([fixed[0], fixed[1]])),
+            rclass: DnsClass::new(u16::from_be_bytes([fixed[2], fixed[3]])),
+            ttl: u32::from_be_bytes([fixed[4], fixed[5], fixed[6], fixed[7]]),
+            rdata,
+            bytes: self.bytes,
+            rdata_offset,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+fn skip_question(bytes: &[u8], offset: usize) -> Result<usize, DnsQueryParseError> {
+    let (_, offset) = NameRef::parse(bytes, offset)?;
+    if offset + 4 > bytes.len() {
+        return Err(DnsQueryParseError::InsufficientData {
+            required: 4,
+            offset,
+            available: bytes.len() - offset,
+        });
+    }
+    Ok(offset + 4)
+}
+
+fn skip_record(bytes: &[u8], offset: usize) -> Result<usize, DnsRecordParseError> {
+    let (_, offset) = NameRef::parse(bytes, offset)?;
+    check_record_size(bytes, offset, 10)?;
+    let length = u16::from_be_bytes([bytes[offset + 8], bytes[offset + 9]]) as usize;
+    check_record_size(bytes, offset + 10, length)?;
+    Ok(offset + 10 + length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::dns_types::DnsTypes;
+    #[cfg(feature = "alloc")]
+    use crate::utils::pcap::read_udp_datagrams;
+    use crate::validation::ValidationProfile;
+    use crate::DnsErrorKind;
+
+    // Response for example.com A with two answers, the owner names compressed.
+    const RESPONSE: &str = "abcd81800001000200000000076578616d706c6503636f6d0000010001\
+                            c00c000100010000012c00045db8d822\
+                            03777777c00c000100010000012c00045db8d823";
+
+    #[test]
+    fn test_packet_ref_iterators() {
+        let bytes = hex::decode(RESPONSE).unwrap();
+        let packet = DnsPacketRef::try_from(bytes.as_slice()).unwrap();
+
+        let question = packet.questions().next().unwrap();
+        assert!(question.name.eq_ignore_ascii_case("EXAMPLE.com."));
+        assert!(!question.name.eq_ignore_ascii_case("example.co"));
+        assert!(!question.name.eq_ignore_ascii_case("www.example.com"));
+        assert_eq!(question.qtype, DnsTypes::A);
+
+        let answers: Vec<RecordRef> = packet.answers().collect();
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers[1].name.to_string(), "www.example.com");
+        let labels: Vec<&[u8]> = answers[1].name.labels().collect();
+        assert_eq!(labels, [&b"www"[..], b"example", b"com"]);
+        assert_eq!(answers[1].ttl, 300);
+        assert_eq!(answers[1].rdata, [0x5d, 0xb8, 0xd8, 0x23]);
+        assert_eq!(packet.records().count(), 2);
+        assert_eq!(packet.additionals().count(), 0);
+
+        #[cfg(feature = "alloc")]
+        assert_eq!(
+            packet.to_owned().unwrap(),
+            DnsPacket::try_from(bytes.as_slice()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_packet_ref_errors() {
+        let bytes = hex::decode(RESPONSE).unwrap();
+        let truncated = &bytes[..bytes.len() - 1];
+        let error = DnsPacketRef::try_from(truncated).unwrap_err();
+        assert_eq!(error.location.section, Section::Answer);
+        #[cfg(feature = "alloc")]
+        {
+            let owned_error = DnsPacket::try_from(truncated).unwrap_err();
+            assert_eq!(error.location, owned_error.location);
+            assert_eq!(error.offset, owned_error.offset);
+        }
+
+        let mut padded = bytes.clone();
+        padded.push(0);
+        let error = DnsPacketRef::try_from(padded.as_slice()).unwrap_err();
+        assert!(matches!(error.kind, DnsErrorKind::TrailingData { .. }));
+        let options = ParseOptions::new(ValidationProfile::Lenient);
+        let packet = DnsPacketRef::parse_with(&padded, &options).unwrap();
+        assert_eq!(packet.as_bytes(), bytes);
+        assert!(packet.warnings.contains(Rule::TrailingData));
+        #[cfg(feature = "alloc")]
+        assert_eq!(packet.to_owned().unwrap().warnings.len(), 1);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_packet_ref_matches_owned_parser() {
+        for datagram in read_udp_datagrams("pcap_exemples/dns.cap") {
+            let payload = datagram.payload.as_slice();
+            match (
+                DnsPacketRef::try_from(payload),
+                DnsPacket::try_from(payload),
+            ) {
+                (Ok(packet), Ok(owned)) => assert_eq!(packet.to_owned().unwrap(), owned),
+                (Err(error), Err(owned)) => assert_eq!(error.to_string(), owned.to_string()),
+                (packet, owned) => panic!("{:?} != {:?}", packet, owned),
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! A cheap test of the first bytes of a payload, to discard most non-DNS traffic before
+//! parsing it.
+
+use crate::dns_header::dns_flags::verify_reserved_flags;
+
+/// The size of the smallest question: the root name, the type and the class.
+const MIN_QUESTION_SIZE: usize = 5;
+/// The size of the smallest record: the root name, the type, the class, the TTL and the
+/// data length.
+const MIN_RECORD_SIZE: usize = 11;
+
+/// Tells whether `bytes` may be a DNS message, looking only at the header and the first
+/// question name, without allocating.
+///
+/// The counts must fit in the payload, the Z bit must be clear and the opcode assigned,
+/// and the first name must be made of valid labels. The other flags are not checked, so
+/// the AD bit in a response or an NXDOMAIN without AA pass as they do with the lenient
+/// profile. It never rejects a message that `DnsPacket::try_from` accepts, but accepts
+/// many that it rejects: it is meant to skip the full parse for most non-DNS payloads.
+pub fn is_probably_dns(bytes: &[u8]) -> bool {
+    let Some(header) = bytes.get(..12) else {
+        return false;
+    };
+    let count = |i: usize| u16::from_be_bytes([header[i], header[i + 1]]) as usize;
+    let questions = count(4);
+    let records = count(6) + count(8) + count(10);
+    let minimum_size = 12 + questions * MIN_QUESTION_SIZE + records * MIN_RECORD_SIZE;
+
+    (questions > 0 || records == 0)
+        && minimum_size <= bytes.len()
+        && verify_reserved_flags(u16::from_be_bytes([header[2], header[3]])).is_ok()
+        && (questions == 0 || first_name_is_valid(bytes))
+}
+
+/// Whether the first question name is made of labels within `bytes`, and is followed by
+/// room for the type and class.
+fn first_name_is_valid(bytes: &[u8]) -> bool {
+    let mut offset = 12;
+    while let Some(&len) = bytes.get(offset) {
+        match len {
+            0 => return offset + 5 <= bytes.len(),
+            // A pointer from the first name can only point back into the header.
+            0xC0.. => {
+                return bytes
+                    .get(offset + 1)
+                    .is_some_and(|&low| len == 0xC0 && low < 12)
+                    && offset + 6 <= bytes.len()
+            }
+            // The 0x40 and 0x80 label types are reserved.
+            0x40.. => return false,
+            _ => offset += 1 + len as usize,
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::pcap::read_udp_datagrams;
+    use crate::validation::{ParseOptions, ValidationProfile};
+    use crate::DnsPacketRef;
+
+    const QUERY: &str = "abcd01000001000000000000076578616d706c6503636f6d0000010001";
+    const RESPONSE: &str = "abcd81800001000100000000076578616d706c6503636f6d0000010001\
+                            c00c000100010000012c00045db8d822";
+    // The non-DNS payloads of the `lib.rs` tests.
+    const SSL: &str = "8746a7014094af07a47e9b7f";
+    const RTCP: &str = "89cc00076f4c712d44434e53515445524d5f50494e473a3035343a3031360000";
+
+    fn assert_never_rejects_valid(payload: &[u8]) {
+        if DnsPacketRef::try_from(payload).is_ok() {
+            assert!(
+                is_probably_dns(payload),
+                "rejected {}",
+                hex::encode(payload)
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_probably_dns() {
+        for message in [QUERY, RESPONSE] {
+            assert!(is_probably_dns(&hex::decode(message).unwrap()));
+        }
+        for payload in [SSL, RTCP, ""] {
+            assert!(!is_probably_dns(&hex::decode(payload).unwrap()));
+        }
+
+        let mut query = hex::decode(QUERY).unwrap();
+        // A label running past the end of the payload.
+        query[12] = 0x3f;
+        assert!(!is_probably_dns(&query));
+        // An extended label type.
+        query[12] = 0x47;
+        assert!(!is_probably_dns(&query));
+
+        // The reserved Z bit and an unassigned opcode.
+        let mut query = hex::decode(QUERY).unwrap();
+        query[3] = 0x40;
+        assert!(!is_probably_dns(&query));
+        query[3] = 0x00;
+        query[2] = 0x31;
+        assert!(!is_probably_dns(&query));
+    }
+
+    #[test]
+    fn test_accepts_lenient_flags() {
+        let options = ParseOptions::new(ValidationProfile::Lenient);
+        let mut response = hex::decode(RESPONSE).unwrap();
+        // AD in a response, then NXDOMAIN without AA.
+        for flags in [[0x81, 0xa0], [0x81, 0x83]] {
+            response[2..4].copy_from_slice(&flags);
+            assert!(DnsPacketRef::try_from(response.as_slice()).is_err());
+            assert!(DnsPacketRef::parse_with(&response, &options).is_ok());
+            assert!(is_probably_dns(&response));
+        }
+    }
+
+    #[test]
+    fn test_never_rejects_captured_messages() {
+        for path in [
+            "pcap_exemples/dns.cap",
+            "pcap_exemples/dns-remoteshell.pcap",
+            "pcap_exemples/NTP_sync.pcap",
+        ] {
+            for datagram in read_udp_datagrams(path) {
+                assert_never_rejects_valid(&datagram.payload);
+                let is_dns = datagram.source.port() == 53 || datagram.destination.port() == 53;
+                assert_eq!(is_probably_dns(&datagram.payload), is_dns, "{}", path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_never_rejects_mutated_messages() {
+        for datagram in read_udp_datagrams("pcap_exemples/dns.cap") {
+            let mut payload = datagram.payload;
+            for i in 0..payload.len() {
+                let original = payload[i];
+                for mask in [0x01, 0x40, 0x80, 0xff] {
+                    payload[i] = original ^ mask;
+                    assert_never_rejects_valid(&payload);
+                }
+                payload[i] = original;
+                assert_never_rejects_valid(&payload[..i]);
+            }
+        }
+    }
+}
@@ -0,0 +1,5 @@
+//! The `alloc` items that `std` puts in the prelude, for `no_std` builds.
+
+pub(crate) use alloc::string::{String, ToString};
+pub(crate) use alloc::vec::Vec;
+pub(crate) use alloc::{format, vec};
@@ -0,0 +1,25 @@
+use thiserror::Error;
+
+use crate::dns_records::errors::DnsRecordParseError;
+use crate::errors::DnsPacketError;
+use crate::prelude::*;
+
+#[derive(Debug, Error)]
+pub enum NbnsError {
+    #[error("Insufficient data: expected at least 12 bytes, but got {0}")]
+    PacketTooShort(usize),
+    #[error("Invalid NetBIOS name: {0}")]
+    InvalidName(String),
+    #[error("NBNS record parsing error: {0}")]
+    RecordError(#[from] DnsRecordParseError),
+    #[error("Trailing data: the message ends after {consumed} of {length} bytes")]
+    TrailingData { consumed: usize, length: usize },
+}
+
+#[derive(Debug, Error)]
+pub enum ProtocolError {
+    #[error("DNS parsing error: {0}")]
+    Dns(#[from] DnsPacketError),
+    #[error("NBNS parsing error: {0}")]
+    Nbns(#[from] NbnsError),
+}
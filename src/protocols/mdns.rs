@@ -0,0 +1,228 @@
+use core::net::IpAddr;
+
+use crate::dns_records::rdata::RData;
+use crate::prelude::*;
+use crate::ResourceRecord;
+
+/// A DNS-SD (RFC 6763) service instance, e.g. `Office Printer._ipp._tcp.local`.
+///
+/// TXT values are decoded as UTF-8, lossily; a key without `=` has no value.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ServiceInstance {
+    /// The instance name, unescaped (`Office Printer`).
+    pub name: String,
+    /// The service type (`_ipp._tcp`).
+    pub service_type: String,
+    pub domain: String,
+    /// The target host and port of the SRV record.
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub txt: Vec<(String, Option<String>)>,
+    /// The A and AAAA addresses of `host`.
+    pub addresses: Vec<IpAddr>,
+}
+
+impl ServiceInstance {
+    /// The full name the instance's SRV and TXT records are owned by.
+    pub fn full_name(&self) -> String {
+        let name = self.name.replace('\\', "\\\\").replace('.', "\\.");
+        format!("{}.{}.{}", name, self.service_type, self.domain)
+    }
+}
+
+/// Splits a name into its labels, keeping the escapes.
+fn labels(name: &str) -> Vec<&str> {
+    let mut labels = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in name.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '.' => {
+                labels.push(&name[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    labels.push(&name[start..]);
+    labels
+}
+
+fn unescape(label: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = label.chars();
+    while let Some(c) = chars.next() {
+        unescaped.extend(if c == '\\' { chars.next() } else { Some(c) });
+    }
+    unescaped
+}
+
+/// Splits `<instance>.<_service>.<_tcp|_udp>.<domain>` into an empty instance.
+fn parse_instance_name(name: &str) -> Option<ServiceInstance> {
+    match labels(name).as_slice() {
+        [instance, service, protocol, domain @ ..]
+            if service.starts_with('_')
+                && ["_tcp", "_udp"].contains(&protocol.to_ascii_lowercase().as_str())
+                && !domain.is_empty() =>
+        {
+            Some(ServiceInstance {
+                name: unescape(instance),
+                service_type: format!("{}.{}", service, protocol),
+                domain: domain.join("."),
+                ..ServiceInstance::default()
+            })
+        }
+        _ => None,
+    }
+}
+
+fn same_name(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+/// Assembles the service instances described by `records`: the targets of PTR records and
+/// the owners of SRV and TXT records, completed with their SRV, TXT, A and AAAA records.
+///
+/// The records can come from any number of messages, e.g. all the mDNS announcements
+/// observed on a link.
+pub fn service_instances<'a>(
+    records: impl IntoIterator<Item = &'a ResourceRecord>,
+) -> Vec<ServiceInstance> {
+    let records = records.into_iter().collect::<Vec<_>>();
+    let mut instances: Vec<(String, ServiceInstance)> = Vec::new();
+    for record in &records {
+        let name = match &record.rdata {
+            RData::PTR(target) => target,
+            RData::SRV(_) | RData::TXT(_) => &record.name,
+            _ => continue,
+        };
+        if instances.iter().any(|(known, _)| same_name(known, name)) {
+            continue;
+        }
+        if let Some(instance) = parse_instance_name(name) {
+            instances.push((name.clone(), instance));
+        }
+    }
+
+    for (name, instance) in &mut instances {
+        for record in records
+            .iter()
+            .filter(|record| same_name(&record.name, name))
+        {
+            match &record.rdata {
+                RData::SRV(srv) if instance.host.is_none() => {
+                    instance.host = Some(srv.target.clone());
+                    instance.port = Some(srv.port);
+                }
+                RData::TXT(txt) if instance.txt.is_empty() => {
+                    for string in &txt.strings {
+                        let entry = String::from_utf8_lossy(&string.0);
+                        let (key, value) = match entry.split_once('=') {
+                            Some((key, value)) => (key, Some(value.to_string())),
+                            None => (entry.as_ref(), None),
+                        };
+                        // Empty keys are invalid, and only the first occurrence of a key counts.
+                        if !key.is_empty() && !instance.txt.iter().any(|(k, _)| k == key) {
+                            instance.txt.push((key.to_string(), value));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        let Some(host) = &instance.host else {
+            continue;
+        };
+        for record in records
+            .iter()
+            .filter(|record| same_name(&record.name, host))
+        {
+            let address = match record.rdata {
+                RData::A(address) => IpAddr::V4(address),
+                RData::AAAA(address) => IpAddr::V6(address),
+                _ => continue,
+            };
+            if !instance.addresses.contains(&address) {
+                instance.addresses.push(address);
+            }
+        }
+    }
+    instances
+        .into_iter()
+        .map(|(_, instance)| instance)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{dns_class::DnsClass, dns_types::DnsType, dns_types::DnsTypes};
+
+    fn record(name: &str, rtype: DnsType, rdata: &str) -> ResourceRecord {
+        ResourceRecord {
+            name: name.to_string(),
+            rtype,
+            rclass: DnsClass(0x8001),
+            ttl: 120,
+            data_length: 0,
+            rdata: RData::from_presentation(rtype, rdata).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_service_instances() {
+        let instance = "Office\\.Printer._ipp._tcp.local";
+        let records = [
+            record(
+                "_services._dns-sd._udp.local",
+                DnsTypes::PTR,
+                "_ipp._tcp.local",
+            ),
+            record("_ipp._tcp.local", DnsTypes::PTR, instance),
+            record(instance, DnsTypes::SRV, "0 0 631 printer.local"),
+            record(
+                instance,
+                DnsTypes::TXT,
+                "\"txtvers=1\" \"rp=ipp/print\" \"Color\" \"rp=ignored\" \"=bad\"",
+            ),
+            record("printer.local", DnsTypes::A, "192.168.1.20"),
+            record("PRINTER.local", DnsTypes::AAAA, "fe80::1"),
+            record("other.local", DnsTypes::A, "192.168.1.21"),
+        ];
+        assert!(records[2].cache_flush());
+
+        let instances = service_instances(&records);
+        assert_eq!(
+            instances,
+            [ServiceInstance {
+                name: "Office.Printer".to_string(),
+                service_type: "_ipp._tcp".to_string(),
+                domain: "local".to_string(),
+                host: Some("printer.local".to_string()),
+                port: Some(631),
+                txt: vec![
+                    ("txtvers".to_string(), Some("1".to_string())),
+                    ("rp".to_string(), Some("ipp/print".to_string())),
+                    ("Color".to_string(), None),
+                ],
+                addresses: vec!["192.168.1.20".parse().unwrap(), "fe80::1".parse().unwrap()],
+            }]
+        );
+        assert_eq!(instances[0].full_name(), instance);
+    }
+
+    #[test]
+    fn test_service_instance_without_srv() {
+        let records = [record(
+            "_hap._tcp.local",
+            DnsTypes::PTR,
+            "Bridge._hap._tcp.local",
+        )];
+        let instances = service_instances(&records);
+        assert_eq!(instances[0].name, "Bridge");
+        assert_eq!(instances[0].host, None);
+        assert!(service_instances(&[record("a.local", DnsTypes::A, "192.0.2.1")]).is_empty());
+    }
+}
@@ -0,0 +1,263 @@
+//! Telling DNS apart from the protocols that reuse its message format.
+//!
+//! Multicast DNS (RFC 6762), LLMNR (RFC 4795) and the NetBIOS Name Service (RFC 1002)
+//! share the DNS header layout, but give some fields other meanings: mDNS announces
+//! records without questions and uses the top bit of the class, LLMNR redefines AA and RD
+//! as its C (conflict) and T (tentative) bits, and NBNS has its own opcodes and name
+//! encoding. `classify` tells which protocol a payload belongs to, and `parse` decodes it
+//! with that protocol's rules.
+//!
+//! For mDNS, `DnsQuery::unicast_response` and `ResourceRecord::cache_flush` read the top
+//! bit of the class, and `service_instances` assembles DNS-SD service instances from the
+//! announced records.
+
+use core::fmt;
+
+use crate::dns_records::parse_name;
+use crate::prelude::*;
+use crate::validation::{ParseOptions, Rule, RuleAction, ValidationProfile};
+use crate::{detect, DnsPacket};
+pub use errors::{NbnsError, ProtocolError};
+pub use mdns::{service_instances, ServiceInstance};
+use nbns::is_netbios_name;
+pub use nbns::{
+    NbnsOpcode, NbnsOpcodes, NbnsPacket, NbnsQuestion, NbnsRecord, NbnsType, NbnsTypes, NetbiosName,
+};
+
+mod errors;
+mod mdns;
+mod nbns;
+
+pub const DNS_PORT: u16 = 53;
+pub const MDNS_PORT: u16 = 5353;
+pub const LLMNR_PORT: u16 = 5355;
+pub const NBNS_PORT: u16 = 137;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    Dns,
+    Mdns,
+    Llmnr,
+    Nbns,
+}
+
+impl Protocol {
+    /// The protocol whose well-known port is `port`.
+    pub fn from_port(port: u16) -> Option<Self> {
+        match port {
+            DNS_PORT => Some(Protocol::Dns),
+            MDNS_PORT => Some(Protocol::Mdns),
+            LLMNR_PORT => Some(Protocol::Llmnr),
+            NBNS_PORT => Some(Protocol::Nbns),
+            _ => None,
+        }
+    }
+
+    /// The parse options matching the protocol's use of the DNS header, for the protocols
+    /// parsed as a `DnsPacket`.
+    pub fn parse_options(&self) -> ParseOptions {
+        let options = ParseOptions::new(ValidationProfile::Rfc);
+        match self {
+            // Announcements and goodbyes carry answers without questions.
+            Protocol::Mdns => options.with_rule(Rule::RecordsWithoutQuestion, RuleAction::Ignore),
+            // AA is the conflict bit, and the AD and CD positions are reserved.
+            Protocol::Llmnr => options
+                .with_rule(Rule::AaTcInStatusResponse, RuleAction::Ignore)
+                .with_rule(Rule::AaInServerFailure, RuleAction::Ignore)
+                .with_rule(Rule::AaInRefused, RuleAction::Ignore)
+                .with_rule(Rule::AdCdBits, RuleAction::Error),
+            Protocol::Dns | Protocol::Nbns => options,
+        }
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Protocol::Dns => "DNS",
+                Protocol::Mdns => "mDNS",
+                Protocol::Llmnr => "LLMNR",
+                Protocol::Nbns => "NBNS",
+            }
+        )
+    }
+}
+
+/// A message parsed according to its protocol.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProtocolPacket {
+    Dns(DnsPacket),
+    Mdns(DnsPacket),
+    Llmnr(DnsPacket),
+    Nbns(NbnsPacket),
+}
+
+impl ProtocolPacket {
+    pub fn protocol(&self) -> Protocol {
+        match self {
+            ProtocolPacket::Dns(_) => Protocol::Dns,
+            ProtocolPacket::Mdns(_) => Protocol::Mdns,
+            ProtocolPacket::Llmnr(_) => Protocol::Llmnr,
+            ProtocolPacket::Nbns(_) => Protocol::Nbns,
+        }
+    }
+}
+
+/// Parses `payload` as a message of `protocol`.
+pub fn parse(payload: &[u8], protocol: Protocol) -> Result<ProtocolPacket, ProtocolError> {
+    let parse_dns = || DnsPacket::parse_with(payload, &protocol.parse_options());
+    Ok(match protocol {
+        Protocol::Dns => ProtocolPacket::Dns(parse_dns()?),
+        Protocol::Mdns => ProtocolPacket::Mdns(parse_dns()?),
+        Protocol::Llmnr => ProtocolPacket::Llmnr(parse_dns()?),
+        Protocol::Nbns => ProtocolPacket::Nbns(NbnsPacket::try_from(payload)?),
+    })
+}
+
+/// Tells which protocol `payload` belongs to, or `None` if it is none of them.
+///
+/// `ports` are the (source, destination) UDP ports when known; a well-known port on either
+/// side decides the candidate protocol. Otherwise the content decides: NetBIOS-encoded
+/// names mean NBNS, and `.local` names or a class with the top bit set mean mDNS. LLMNR
+/// cannot be told apart from unicast DNS by content, so it is only recognized by port.
+/// The payload must then be valid for the candidate protocol.
+pub fn classify(payload: &[u8], ports: Option<(u16, u16)>) -> Option<Protocol> {
+    let candidate = ports
+        .and_then(|(source, destination)| {
+            Protocol::from_port(destination).or_else(|| Protocol::from_port(source))
+        })
+        .or_else(|| content_hint(payload))
+        .unwrap_or(Protocol::Dns);
+    let valid = match candidate {
+        Protocol::Dns => detect(payload).is_dns(),
+        _ => parse(payload, candidate).is_ok(),
+    };
+    valid.then_some(candidate)
+}
+
+fn content_hint(payload: &[u8]) -> Option<Protocol> {
+    if is_netbios_name(payload, 12) {
+        return Some(Protocol::Nbns);
+    }
+    // The first name is the first question's, or the first record's in announcements.
+    let (name, end) = parse_name(payload, 12).ok()?;
+    let class = payload.get(end + 2..end + 4)?;
+    let name = name.to_ascii_lowercase();
+    let local = ["local", "254.169.in-addr.arpa", "8.e.f.ip6.arpa"]
+        .iter()
+        .any(|domain| name == *domain || name.ends_with(&format!(".{}", domain)));
+    let top_bit_class = class == [0x80, 0x01];
+    (local || top_bit_class).then_some(Protocol::Mdns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DNS_QUERY: &str = "abcd01000001000000000000076578616d706c6503636f6d0000010001";
+    // Query for _http._tcp.local PTR, with the unicast-response bit set.
+    const MDNS_QUERY: &str = "000000000001000000000000055f68747470045f746370056c6f63616c00000c8001";
+    // Announcement of printer.local A 192.168.1.20, with the cache-flush bit set.
+    const MDNS_ANNOUNCEMENT: &str =
+        "000084000000000100000000077072696e746572056c6f63616c0000018001000000780004c0a80114";
+    // Broadcast name query for WORKGROUP<1D>.
+    const NBNS_QUERY: &str = "80010110000100000000000020464845504643454c45484643455046464641434143414341434143414341424e0000200001";
+    // Positive answer for WORKGROUP<1D> at 192.168.1.10.
+    const NBNS_RESPONSE: &str = "80018500000000010000000020464845504643454c45484643455046464641434143414341434143414341424e0000200001000493e000060000c0a8010a";
+
+    fn bytes(hex: &str) -> Vec<u8> {
+        hex::decode(hex).unwrap()
+    }
+
+    #[test]
+    fn test_classify_by_port() {
+        let query = bytes(DNS_QUERY);
+        assert_eq!(classify(&query, Some((53000, 53))), Some(Protocol::Dns));
+        assert_eq!(classify(&query, Some((5355, 53000))), Some(Protocol::Llmnr));
+        assert_eq!(classify(&query, Some((5353, 5353))), Some(Protocol::Mdns));
+        assert_eq!(classify(&query, None), Some(Protocol::Dns));
+        assert_eq!(classify(b"not a DNS message", Some((53000, 53))), None);
+    }
+
+    #[test]
+    fn test_classify_mdns() {
+        for message in [MDNS_QUERY, MDNS_ANNOUNCEMENT] {
+            assert_eq!(classify(&bytes(message), None), Some(Protocol::Mdns));
+        }
+        // Answers without a question are rejected by the DNS rules.
+        let announcement = bytes(MDNS_ANNOUNCEMENT);
+        assert!(DnsPacket::try_from(announcement.as_slice()).is_err());
+
+        let packet = match parse(&announcement, Protocol::Mdns).unwrap() {
+            ProtocolPacket::Mdns(packet) => packet,
+            other => panic!("unexpected {:?}", other),
+        };
+        let answer = packet.records().next().unwrap();
+        assert_eq!(answer.name, "printer.local");
+        assert!(answer.cache_flush());
+        assert_eq!(answer.rclass.split_mdns_flag().0.to_string(), "IN");
+
+        let query = DnsPacket::parse_with(&bytes(MDNS_QUERY), &Protocol::Mdns.parse_options());
+        assert!(query.unwrap().queries.queries[0].unicast_response());
+    }
+
+    #[test]
+    fn test_classify_llmnr_conflict() {
+        // LLMNR response with the C bit set in a REFUSED-like rcode position.
+        let mut payload = bytes(DNS_QUERY);
+        payload[2..4].copy_from_slice(&[0x84, 0x05]);
+        assert!(DnsPacket::try_from(payload.as_slice()).is_err());
+        assert_eq!(
+            classify(&payload, Some((5355, 50000))),
+            Some(Protocol::Llmnr)
+        );
+
+        // AD is reserved in LLMNR.
+        payload[2..4].copy_from_slice(&[0x80, 0x20]);
+        assert_eq!(classify(&payload, Some((5355, 50000))), None);
+    }
+
+    #[test]
+    fn test_classify_nbns() {
+        let query = bytes(NBNS_QUERY);
+        assert_eq!(classify(&query, None), Some(Protocol::Nbns));
+        assert_eq!(classify(&query, Some((137, 137))), Some(Protocol::Nbns));
+
+        let packet = NbnsPacket::try_from(query.as_slice()).unwrap();
+        assert!(packet.is_broadcast());
+        assert_eq!(packet.opcode(), NbnsOpcodes::QUERY);
+        assert_eq!(packet.questions[0].name.to_string(), "WORKGROUP<1D>");
+        assert_eq!(packet.questions[0].qtype, NbnsTypes::NB);
+
+        let response = match parse(&bytes(NBNS_RESPONSE), Protocol::Nbns).unwrap() {
+            ProtocolPacket::Nbns(packet) => packet,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert!(response.is_response());
+        assert_eq!(
+            response.answers[0].addresses(),
+            [(0, "192.168.1.10".parse().unwrap())]
+        );
+
+        let mut wire = Vec::new();
+        response.answers[0].name.to_wire(&mut wire);
+        assert_eq!(wire, query[12..46]);
+    }
+
+    #[test]
+    fn test_netbios_name_decode() {
+        assert_eq!(
+            NetbiosName::decode("CKAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA").unwrap(),
+            NetbiosName {
+                name: "*".to_string(),
+                suffix: 0,
+                scope: String::new()
+            }
+        );
+        assert!(NetbiosName::decode("FHEPFCELEHFCEPFF").is_err());
+        assert!(NetbiosName::decode("ZZEPFCELEHFCEPFFFACACACACACACABN").is_err());
+    }
+}
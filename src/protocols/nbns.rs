@@ -0,0 +1,281 @@
+use core::fmt;
+use core::net::Ipv4Addr;
+
+use super::errors::NbnsError;
+use crate::dns_records::{parse_name, read_bytes, read_u16, read_u32};
+use crate::prelude::*;
+
+/// Length of an encoded NetBIOS name: 16 bytes, each split into two letters.
+const ENCODED_NAME_LENGTH: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NbnsOpcode(pub u8);
+
+#[allow(non_snake_case)]
+#[allow(non_upper_case_globals)]
+pub mod NbnsOpcodes {
+    use super::NbnsOpcode;
+
+    pub const QUERY: NbnsOpcode = NbnsOpcode(0);
+    pub const REGISTRATION: NbnsOpcode = NbnsOpcode(5);
+    pub const RELEASE: NbnsOpcode = NbnsOpcode(6);
+    pub const WACK: NbnsOpcode = NbnsOpcode(7);
+    pub const REFRESH: NbnsOpcode = NbnsOpcode(8);
+    pub const MULTI_HOMED_REGISTRATION: NbnsOpcode = NbnsOpcode(15);
+}
+
+impl fmt::Display for NbnsOpcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                NbnsOpcodes::QUERY => "QUERY",               // 0
+                NbnsOpcodes::REGISTRATION => "REGISTRATION", // 5
+                NbnsOpcodes::RELEASE => "RELEASE",           // 6
+                NbnsOpcodes::WACK => "WACK",                 // 7
+                NbnsOpcodes::REFRESH => "REFRESH",           // 8
+                NbnsOpcodes::MULTI_HOMED_REGISTRATION => "MULTI-HOMED REGISTRATION", // 15
+                _ => "unknown",
+            }
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NbnsType(pub u16);
+
+#[allow(non_snake_case)]
+#[allow(non_upper_case_globals)]
+pub mod NbnsTypes {
+    use super::NbnsType;
+
+    pub const A: NbnsType = NbnsType(0x0001);
+    pub const NS: NbnsType = NbnsType(0x0002);
+    pub const NULL: NbnsType = NbnsType(0x000A);
+    pub const NB: NbnsType = NbnsType(0x0020);
+    pub const NBSTAT: NbnsType = NbnsType(0x0021);
+}
+
+impl fmt::Display for NbnsType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                NbnsTypes::A => "A",           // 0x0001
+                NbnsTypes::NS => "NS",         // 0x0002
+                NbnsTypes::NULL => "NULL",     // 0x000A
+                NbnsTypes::NB => "NB",         // 0x0020
+                NbnsTypes::NBSTAT => "NBSTAT", // 0x0021
+                _ => "unknown",
+            }
+        )
+    }
+}
+
+/// A NetBIOS name (RFC 1001): up to 15 characters, a suffix byte telling the service
+/// (e.g. `0x20` for file servers, `0x1D` for master browsers) and an optional scope.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetbiosName {
+    pub name: String,
+    pub suffix: u8,
+    pub scope: String,
+}
+
+impl NetbiosName {
+    /// Decodes a name in the "first-level" encoding, where each half-byte is a letter
+    /// from `A` to `P`, followed by the scope labels.
+    pub fn decode(encoded: &str) -> Result<Self, NbnsError> {
+        let (label, scope) = encoded.split_once('.').unwrap_or((encoded, ""));
+        let invalid = || NbnsError::InvalidName(encoded.to_string());
+        if label.len() != ENCODED_NAME_LENGTH {
+            return Err(invalid());
+        }
+        let bytes = label
+            .as_bytes()
+            .chunks(2)
+            .map(|pair| match pair {
+                [high @ b'A'..=b'P', low @ b'A'..=b'P'] => Ok((high - b'A') << 4 | (low - b'A')),
+                _ => Err(invalid()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let name = String::from_utf8_lossy(&bytes[..15])
+            .trim_end_matches([' ', '\0'])
+            .to_string();
+        Ok(NetbiosName {
+            name,
+            suffix: bytes[15],
+            scope: scope.to_string(),
+        })
+    }
+
+    /// Appends the name in wire format: the encoded label, then the scope labels.
+    pub fn to_wire(&self, buf: &mut Vec<u8>) {
+        let mut bytes = self.name.as_bytes().to_vec();
+        // The wildcard name `*` is padded with zeros, other names with spaces.
+        bytes.resize(15, if self.name == "*" { 0 } else { b' ' });
+        bytes.push(self.suffix);
+        buf.push(ENCODED_NAME_LENGTH as u8);
+        for byte in bytes {
+            buf.extend_from_slice(&[b'A' + (byte >> 4), b'A' + (byte & 0x0F)]);
+        }
+        for label in self.scope.split('.').filter(|label| !label.is_empty()) {
+            buf.push(label.len() as u8);
+            buf.extend_from_slice(label.as_bytes());
+        }
+        buf.push(0);
+    }
+}
+
+impl fmt::Display for NetbiosName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}<{:02X}>", self.name, self.suffix)?;
+        if !self.scope.is_empty() {
+            write!(f, ".{}", self.scope)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether the name at `offset` is an encoded NetBIOS name.
+pub(crate) fn is_netbios_name(bytes: &[u8], offset: usize) -> bool {
+    bytes.get(offset) == Some(&(ENCODED_NAME_LENGTH as u8))
+        && bytes
+            .get(offset + 1..offset + 1 + ENCODED_NAME_LENGTH)
+            .is_some_and(|label| label.iter().all(|byte| (b'A'..=b'P').contains(byte)))
+}
+
+fn parse_netbios_name(bytes: &[u8], offset: &mut usize) -> Result<NetbiosName, NbnsError> {
+    let (encoded, end) = parse_name(bytes, *offset)?;
+    *offset = end;
+    NetbiosName::decode(&encoded)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NbnsQuestion {
+    pub name: NetbiosName,
+    pub qtype: NbnsType,
+    pub qclass: u16,
+}
+
+/// An NBNS resource record; the data is kept raw, see `NbnsRecord::addresses` for NB records.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NbnsRecord {
+    pub name: NetbiosName,
+    pub rtype: NbnsType,
+    pub rclass: u16,
+    pub ttl: u32,
+    pub rdata: Vec<u8>,
+}
+
+impl NbnsRecord {
+    /// The (NB_FLAGS, address) entries of an NB record, empty for other types.
+    pub fn addresses(&self) -> Vec<(u16, Ipv4Addr)> {
+        if self.rtype != NbnsTypes::NB {
+            return Vec::new();
+        }
+        self.rdata
+            .chunks_exact(6)
+            .map(|entry| {
+                let flags = u16::from_be_bytes([entry[0], entry[1]]);
+                (flags, Ipv4Addr::new(entry[2], entry[3], entry[4], entry[5]))
+            })
+            .collect()
+    }
+}
+
+/// A NetBIOS Name Service (RFC 1002) message.
+///
+/// NBNS reuses the DNS header layout, but the flags hold NetBIOS opcodes (registration,
+/// release...) and a broadcast bit, and names use the NetBIOS encoding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NbnsPacket {
+    pub transaction_id: u16,
+    pub flags: u16,
+    pub questions: Vec<NbnsQuestion>,
+    pub answers: Vec<NbnsRecord>,
+    pub authorities: Vec<NbnsRecord>,
+    pub additionals: Vec<NbnsRecord>,
+}
+
+impl NbnsPacket {
+    pub fn is_response(&self) -> bool {
+        self.flags & 0x8000 != 0
+    }
+
+    pub fn opcode(&self) -> NbnsOpcode {
+        NbnsOpcode(((self.flags >> 11) & 0x0F) as u8)
+    }
+
+    /// Whether the message was broadcast rather than sent to a name server.
+    pub fn is_broadcast(&self) -> bool {
+        self.flags & 0x0010 != 0
+    }
+
+    pub fn rcode(&self) -> u8 {
+        (self.flags & 0x0F) as u8
+    }
+}
+
+impl TryFrom<&[u8]> for NbnsPacket {
+    type Error = NbnsError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() < 12 {
+            return Err(NbnsError::PacketTooShort(bytes.len()));
+        }
+        let mut offset = 0;
+        let transaction_id = read_u16(bytes, &mut offset)?;
+        let flags = read_u16(bytes, &mut offset)?;
+        let mut counts = [0; 4];
+        for count in &mut counts {
+            *count = read_u16(bytes, &mut offset)?;
+        }
+
+        let mut questions = Vec::with_capacity(counts[0] as usize);
+        for _ in 0..counts[0] {
+            questions.push(NbnsQuestion {
+                name: parse_netbios_name(bytes, &mut offset)?,
+                qtype: NbnsType(read_u16(bytes, &mut offset)?),
+                qclass: read_u16(bytes, &mut offset)?,
+            });
+        }
+        let mut sections = counts[1..].iter().map(|&count| {
+            (0..count)
+                .map(|_| {
+                    let name = parse_netbios_name(bytes, &mut offset)?;
+                    let rtype = NbnsType(read_u16(bytes, &mut offset)?);
+                    let rclass = read_u16(bytes, &mut offset)?;
+                    let ttl = read_u32(bytes, &mut offset)?;
+                    let length = read_u16(bytes, &mut offset)? as usize;
+                    Ok(NbnsRecord {
+                        name,
+                        rtype,
+                        rclass,
+                        ttl,
+                        rdata: read_bytes(bytes, &mut offset, length)?.to_vec(),
+                    })
+                })
+                .collect::<Result<Vec<_>, NbnsError>>()
+        });
+        let answers = sections.next().unwrap()?;
+        let authorities = sections.next().unwrap()?;
+        let additionals = sections.next().unwrap()?;
+
+        if offset < bytes.len() {
+            return Err(NbnsError::TrailingData {
+                consumed: offset,
+                length: bytes.len(),
+            });
+        }
+        Ok(NbnsPacket {
+            transaction_id,
+            flags,
+            questions,
+            answers,
+            authorities,
+            additionals,
+        })
+    }
+}
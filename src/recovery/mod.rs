@@ -0,0 +1,234 @@
+//! Recovering the intact parts of a malformed message.
+//!
+//! `DnsPacket::parse_with` stops at the first error and returns nothing else.
+//! `DnsPacket::parse_recovering` keeps going as far as the structure allows: rule
+//! violations in the header are recorded, a record whose data cannot be decoded is kept
+//! with its raw data, and parsing only stops when the position of the next entry is
+//! unknown (e.g. a broken name or a truncated record).
+
+use crate::dns_records::errors::DnsRecordParseError;
+use crate::errors::{DnsErrorKind, DnsPacketError, Section};
+use crate::prelude::*;
+use crate::validation::{ParseOptions, Rule, Warning};
+use crate::{check_dns_minimum_size, DnsHeader, DnsPacket, DnsQueries, DnsQuery, ResourceRecord};
+
+/// What `DnsPacket::parse_recovering` could decode, with the errors met on the way.
+#[derive(Debug, Default)]
+pub struct PartialPacket {
+    /// `None` when the message is shorter than a header.
+    pub header: Option<DnsHeader>,
+    pub queries: Vec<DnsQuery>,
+    pub answers: Vec<ResourceRecord>,
+    pub authorities: Vec<ResourceRecord>,
+    pub additionals: Vec<ResourceRecord>,
+    pub warnings: Vec<Warning>,
+    pub errors: Vec<DnsPacketError>,
+    /// The offset where parsing stopped: the end of the message if all the declared
+    /// entries were read.
+    pub offset: usize,
+}
+
+impl PartialPacket {
+    /// Whether the message was fully decoded without errors.
+    pub fn is_complete(&self) -> bool {
+        self.header.is_some() && self.errors.is_empty()
+    }
+
+    /// Converts a complete parse into a packet, or returns the first error.
+    pub fn into_packet(mut self) -> Result<DnsPacket, DnsPacketError> {
+        if !self.errors.is_empty() {
+            return Err(self.errors.remove(0));
+        }
+        let header = self.header.ok_or_else(|| {
+            let error = DnsErrorKind::InsufficientData {
+                expected: 12,
+                actual: self.offset,
+            };
+            DnsPacketError::new(error, Section::Header, None, 0)
+        })?;
+        let section = |records: Vec<ResourceRecord>| (!records.is_empty()).then_some(records);
+        Ok(DnsPacket {
+            header,
+            queries: DnsQueries {
+                queries: self.queries,
+            },
+            answers: section(self.answers),
+            authorities: section(self.authorities),
+            additionals: section(self.additionals),
+            warnings: self.warnings,
+        })
+    }
+}
+
+impl DnsPacket {
+    /// Parses as much of `bytes` as possible, collecting the errors instead of stopping
+    /// at the first one.
+    pub fn parse_recovering(bytes: &[u8], options: &ParseOptions) -> PartialPacket {
+        let mut partial = PartialPacket::default();
+        if let Err(error) = check_dns_minimum_size(bytes) {
+            partial.errors.push(error);
+            return partial;
+        }
+
+        let header = match DnsHeader::parse_with(bytes, options, &mut partial.warnings) {
+            Ok(header) => header,
+            Err(error) => {
+                let error = DnsPacketError::new(error, Section::Header, None, 0);
+                partial.errors.push(error);
+                DnsHeader::unchecked(bytes)
+            }
+        };
+        let counts = header.counts;
+        partial.header = Some(header);
+        partial.offset = 12;
+
+        for index in 0..counts[0] as usize {
+            let start = partial.offset;
+            match DnsQuery::from_bytes(bytes, &mut partial.offset) {
+                Ok(query) => partial.queries.push(query),
+                Err(error) => {
+                    let error = DnsPacketError::new(error, Section::Question, Some(index), start);
+                    partial.errors.push(error);
+                    partial.offset = start;
+                    return partial;
+                }
+            }
+        }
+
+        let sections = [Section::Answer, Section::Authority, Section::Additional];
+        for (section, count) in sections.into_iter().zip(&counts[1..]) {
+            for index in 0..*count as usize {
+                let start = partial.offset;
+                let located = |error| DnsPacketError::new(error, section, Some(index), start);
+                match parse_record(bytes, &mut partial.offset) {
+                    Ok((record, error)) => {
+                        partial.errors.extend(error.map(located));
+                        match section {
+                            Section::Answer => partial.answers.push(record),
+                            Section::Authority => partial.authorities.push(record),
+                            _ => partial.additionals.push(record),
+                        }
+                    }
+                    Err(error) => {
+                        partial.errors.push(located(error));
+                        return partial;
+                    }
+                }
+            }
+        }
+
+        if partial.offset < bytes.len() {
+            let error = DnsPacketError::trailing_data(partial.offset, bytes.len());
+            if let Err(error) = options.check(Rule::TrailingData, error, &mut partial.warnings) {
+                partial.errors.push(error);
+            }
+        }
+        partial
+    }
+}
+
+/// Parses a record; if only its data is invalid, returns it with the raw data and the error.
+/// On failure, `offset` is left at the start of the record.
+fn parse_record(
+    bytes: &[u8],
+    offset: &mut usize,
+) -> Result<(ResourceRecord, Option<DnsRecordParseError>), DnsRecordParseError> {
+    let start = *offset;
+    match ResourceRecord::from_bytes(bytes, offset) {
+        Ok(record) => Ok((record, None)),
+        Err(error) => {
+            *offset = start;
+            match ResourceRecord::parse(bytes, offset, false) {
+                Ok(record) => Ok((record, Some(error))),
+                Err(_) => {
+                    *offset = start;
+                    Err(error)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "abcd81800001000200000000";
+    const QUESTION: &str = "076578616d706c6503636f6d0000010001";
+    const ANSWER: &str = "c00c000100010000012c00045db8d822";
+    // An A record with three bytes of address.
+    const SHORT_ANSWER: &str = "c00c000100010000012c00035db8d8";
+
+    fn parse(hex: &str) -> (Vec<u8>, PartialPacket) {
+        let bytes = hex::decode(hex).unwrap();
+        let partial = DnsPacket::parse_recovering(&bytes, &ParseOptions::default());
+        (bytes, partial)
+    }
+
+    #[test]
+    fn test_complete_message() {
+        let (bytes, partial) = parse(&format!("{}{}{}{}", HEADER, QUESTION, ANSWER, ANSWER));
+        assert!(partial.is_complete());
+        assert_eq!(partial.offset, bytes.len());
+        assert_eq!(
+            partial.into_packet().unwrap(),
+            DnsPacket::try_from(bytes.as_slice()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_header_violation_is_recorded() {
+        // Z bit set.
+        let hex = format!("abcd81c00001000100000000{}{}", QUESTION, ANSWER);
+        let (bytes, partial) = parse(&hex);
+        assert!(matches!(
+            partial.errors[..],
+            [DnsPacketError {
+                kind: DnsErrorKind::HeaderError(_),
+                ..
+            }]
+        ));
+        assert_eq!(partial.header.unwrap().flags, 0x81c0);
+        assert_eq!(partial.queries[0].name, "example.com");
+        assert_eq!(partial.answers.len(), 1);
+        assert_eq!(partial.offset, bytes.len());
+    }
+
+    #[test]
+    fn test_invalid_rdata_is_kept_raw() {
+        let (bytes, partial) = parse(&format!("{}{}{}{}", HEADER, QUESTION, SHORT_ANSWER, ANSWER));
+        assert!(matches!(
+            partial.errors[..],
+            [DnsPacketError {
+                kind: DnsErrorKind::RecordError(_),
+                ..
+            }]
+        ));
+        assert_eq!(
+            partial.answers[0].rdata,
+            crate::rdata::RData::Unknown(vec![0x5d, 0xb8, 0xd8])
+        );
+        assert_eq!(partial.answers[1].rdata.to_string(), "93.184.216.34");
+        assert_eq!(partial.offset, bytes.len());
+        assert!(partial.into_packet().is_err());
+    }
+
+    #[test]
+    fn test_truncated_message_stops() {
+        let hex = format!("{}{}{}{}", HEADER, QUESTION, ANSWER, &ANSWER[..20]);
+        let (_, partial) = parse(&hex);
+        assert_eq!(partial.answers.len(), 1);
+        assert!(matches!(
+            partial.errors[..],
+            [DnsPacketError {
+                kind: DnsErrorKind::RecordError(_),
+                ..
+            }]
+        ));
+        assert_eq!(partial.offset, 12 + 17 + 16);
+
+        let (_, partial) = parse("abcd0100");
+        assert!(partial.header.is_none());
+        assert_eq!(partial.offset, 0);
+    }
+}
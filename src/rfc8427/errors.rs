@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+use crate::dns_records::errors::DnsRecordParseError;
+use crate::errors::DnsPacketError;
+
+#[derive(Debug, Error)]
+pub enum Rfc8427Error {
+    #[error("Expected a JSON object for {0}")]
+    NotAnObject(&'static str),
+    #[error("Missing member: {0}")]
+    MissingMember(String),
+    #[error("Invalid value for member {0}")]
+    InvalidMember(String),
+    #[error("Invalid name {0}: labels are limited to 63 bytes and names to 255")]
+    InvalidName(String),
+    #[error("Invalid hex string: {0}")]
+    InvalidHex(#[from] hex::FromHexError),
+    #[error("Invalid message octets: {0}")]
+    PacketError(#[from] DnsPacketError),
+    #[error("Invalid record data: {0}")]
+    RecordError(#[from] DnsRecordParseError),
+}
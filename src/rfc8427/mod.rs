@@ -0,0 +1,483 @@
+//! Conversion between `DnsPacket` and the JSON representation of DNS messages defined in
+//! RFC 8427 ("Representing DNS Messages in JSON").
+//!
+//! The exporter writes the header fields (`ID`, `QR`, `Opcode`, ..., `ARCOUNT`), the
+//! sections (`questionRRs`, `answerRRs`, `authorityRRs`, `additionalRRs`) and, for each
+//! record, both `RDATAHEX` and the `rdata<TYPE>` member in presentation format when the
+//! type has one. The importer accepts the same members, or simply `messageOctetsHEX`.
+//! Names are in presentation format, where the root is `.`.
+
+use serde_json::{json, Map, Value};
+
+use crate::dns_header::DnsHeader;
+use crate::dns_queries::{DnsQueries, DnsQuery};
+use crate::dns_records::rdata::{name_from_token, presentation_name, RData};
+use crate::dns_records::ResourceRecord;
+use crate::utils::{dns_class::DnsClass, dns_types::DnsType};
+use crate::DnsPacket;
+pub use errors::Rfc8427Error;
+
+mod errors;
+
+/// Header flag bits, as (member name, bit position in the flags field).
+const FLAG_BITS: [(&str, u16); 7] = [
+    ("QR", 15),
+    ("AA", 10),
+    ("TC", 9),
+    ("RD", 8),
+    ("RA", 7),
+    ("AD", 5),
+    ("CD", 4),
+];
+
+const COUNT_MEMBERS: [&str; 4] = ["QDCOUNT", "ANCOUNT", "NSCOUNT", "ARCOUNT"];
+
+const SECTION_MEMBERS: [&str; 3] = ["answerRRs", "authorityRRs", "additionalRRs"];
+
+/// Converts `packet` into an RFC 8427 message object.
+///
+/// When the raw `message` the packet was parsed from is given, it is included as
+/// `messageOctetsHEX`.
+pub fn to_rfc8427(packet: &DnsPacket, message: Option<&[u8]>) -> Value {
+    let mut object = Map::new();
+    let flags = packet.header.flags;
+
+    object.insert("ID".into(), json!(packet.header.transaction_id));
+    for (member, bit) in FLAG_BITS {
+        object.insert(member.into(), json!((flags >> bit) & 1 == 1));
+    }
+    object.insert("Opcode".into(), json!((flags >> 11) & 0b1111));
+    object.insert("RCODE".into(), json!(flags & 0b1111));
+    for (member, count) in COUNT_MEMBERS.iter().zip(packet.header.counts) {
+        object.insert((*member).into(), json!(count));
+    }
+
+    if let [query] = packet.queries.queries.as_slice() {
+        object.insert("QNAME".into(), json!(presentation_name(&query.name)));
+        insert_type_and_class(&mut object, "Q", query.qtype, query.qclass);
+    }
+    let questions = packet
+        .queries
+        .queries
+        .iter()
+        .map(|query| {
+            let mut question = Map::new();
+            question.insert("NAME".into(), json!(presentation_name(&query.name)));
+            insert_type_and_class(&mut question, "", query.qtype, query.qclass);
+            Value::Object(question)
+        })
+        .collect();
+    object.insert("questionRRs".into(), Value::Array(questions));
+
+    let sections = [&packet.answers, &packet.authorities, &packet.additionals];
+    for (member, records) in SECTION_MEMBERS.iter().zip(sections) {
+        if let Some(records) = records {
+            let records = records.iter().map(record_to_rfc8427).collect();
+            object.insert((*member).into(), Value::Array(records));
+        }
+    }
+
+    if let Some(message) = message {
+        object.insert("messageOctetsHEX".into(), json!(hex::encode_upper(message)));
+    }
+    Value::Object(object)
+}
+
+fn record_to_rfc8427(record: &ResourceRecord) -> Value {
+    let mut object = Map::new();
+    object.insert("NAME".into(), json!(presentation_name(&record.name)));
+    insert_type_and_class(&mut object, "", record.rtype, record.rclass);
+    object.insert("TTL".into(), json!(record.ttl));
+    object.insert("RDLENGTH".into(), json!(record.data_length));
+
+    let mut rdata = Vec::new();
+    record.rdata.to_wire(&mut rdata);
+    object.insert("RDATAHEX".into(), json!(hex::encode_upper(rdata)));
+    if let (Some(mnemonic), true) = (record.rtype.mnemonic(), has_presentation(&record.rdata)) {
+        object.insert(
+            format!("rdata{}", mnemonic),
+            json!(record.rdata.to_string()),
+        );
+    }
+    Value::Object(object)
+}
+
+/// Whether `rdata` has a presentation format that `RData::from_presentation` can read back.
+fn has_presentation(rdata: &RData) -> bool {
+    !matches!(rdata, RData::OPT(_) | RData::Unknown(_))
+}
+
+fn insert_type_and_class(
+    object: &mut Map<String, Value>,
+    prefix: &str,
+    dns_type: DnsType,
+    dns_class: DnsClass,
+) {
+    object.insert(format!("{}TYPE", prefix), json!(dns_type.0));
+    if let Some(mnemonic) = dns_type.mnemonic() {
+        object.insert(format!("{}TYPEname", prefix), json!(mnemonic));
+    }
+    object.insert(format!("{}CLASS", prefix), json!(dns_class.0));
+    if let Some(mnemonic) = dns_class.mnemonic() {
+        object.insert(format!("{}CLASSname", prefix), json!(mnemonic));
+    }
+}
+
+/// Builds a `DnsPacket` from an RFC 8427 message object.
+///
+/// If `messageOctetsHEX` is present, the octets are parsed as a regular DNS message and
+/// the other members are ignored. Otherwise the packet is assembled from the header
+/// members (missing flags default to 0, missing counts to the section lengths), from
+/// `questionRRs` or `QNAME`/`QTYPE`/`QCLASS`, and from the record sections, whose data
+/// is read from `RDATAHEX` or else from the `rdata<TYPE>` member.
+pub fn from_rfc8427(value: &Value) -> Result<DnsPacket, Rfc8427Error> {
+    let object = value
+        .as_object()
+        .ok_or(Rfc8427Error::NotAnObject("message"))?;
+
+    if let Some(octets) = object.get("messageOctetsHEX") {
+        let octets = octets
+            .as_str()
+            .ok_or_else(|| Rfc8427Error::InvalidMember("messageOctetsHEX".into()))?;
+        let bytes = hex::decode(octets)?;
+        return Ok(DnsPacket::try_from(bytes.as_slice())?);
+    }
+
+    let transaction_id = get_u16(object, "ID")?.unwrap_or(0);
+    let mut flags = 0;
+    for (member, bit) in FLAG_BITS {
+        match object.get(member) {
+            None => {}
+            Some(Value::Bool(set)) => flags |= (*set as u16) << bit,
+            Some(_) => return Err(Rfc8427Error::InvalidMember(member.into())),
+        }
+    }
+    flags |= (get_u16(object, "Opcode")?.unwrap_or(0) & 0b1111) << 11;
+    flags |= get_u16(object, "RCODE")?.unwrap_or(0) & 0b1111;
+
+    let queries = match object.get("questionRRs") {
+        Some(questions) => get_array(questions, "questionRRs")?
+            .iter()
+            .map(query_from_rfc8427)
+            .collect::<Result<_, _>>()?,
+        None if object.contains_key("QNAME") => vec![DnsQuery {
+            name: get_name(object, "QNAME")?,
+            qtype: get_type(object, "Q")?,
+            qclass: get_class(object, "Q")?,
+        }],
+        None => Vec::new(),
+    };
+
+    let mut sections = Vec::with_capacity(SECTION_MEMBERS.len());
+    for member in SECTION_MEMBERS {
+        let records = match object.get(member) {
+            Some(records) => Some(
+                get_array(records, member)?
+                    .iter()
+                    .map(record_from_rfc8427)
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            None => None,
+        };
+        sections.push(records);
+    }
+
+    let lengths = [
+        queries.len(),
+        sections[0].as_ref().map_or(0, Vec::len),
+        sections[1].as_ref().map_or(0, Vec::len),
+        sections[2].as_ref().map_or(0, Vec::len),
+    ];
+    let mut counts = [0; 4];
+    for ((count, member), length) in counts.iter_mut().zip(COUNT_MEMBERS).zip(lengths) {
+        *count = get_u16(object, member)?.unwrap_or(length as u16);
+    }
+
+    let mut sections = sections.into_iter();
+    Ok(DnsPacket {
+        header: DnsHeader {
+            transaction_id,
+            flags,
+            counts,
+        },
+        queries: DnsQueries { queries },
+        answers: sections.next().flatten(),
+        authorities: sections.next().flatten(),
+        additionals: sections.next().flatten(),
+        warnings: Vec::new(),
+    })
+}
+
+fn query_from_rfc8427(value: &Value) -> Result<DnsQuery, Rfc8427Error> {
+    let object = value
+        .as_object()
+        .ok_or(Rfc8427Error::NotAnObject("questionRRs"))?;
+    Ok(DnsQuery {
+        name: get_name(object, "NAME")?,
+        qtype: get_type(object, "")?,
+        qclass: get_class(object, "")?,
+    })
+}
+
+fn record_from_rfc8427(value: &Value) -> Result<ResourceRecord, Rfc8427Error> {
+    let object = value
+        .as_object()
+        .ok_or(Rfc8427Error::NotAnObject("resource record"))?;
+    let rtype = get_type(object, "")?;
+
+    let rdata = if let Some(rdata) = object.get("RDATAHEX") {
+        let rdata = rdata
+            .as_str()
+            .ok_or_else(|| Rfc8427Error::InvalidMember("RDATAHEX".into()))?;
+        let rdata = hex::decode(rdata)?;
+        RData::from_bytes(&rdata, 0, rtype)?
+    } else {
+        let member = format!("rdata{}", rtype.mnemonic().unwrap_or_default());
+        RData::from_presentation(rtype, get_str(object, &member)?)?
+    };
+    let mut wire = Vec::new();
+    rdata.to_wire(&mut wire);
+
+    Ok(ResourceRecord {
+        name: get_name(object, "NAME")?,
+        rtype,
+        rclass: get_class(object, "")?,
+        ttl: get_u32(object, "TTL")?.unwrap_or(0),
+        data_length: get_u16(object, "RDLENGTH")?.unwrap_or(wire.len() as u16),
+        rdata,
+    })
+}
+
+fn get_array<'a>(value: &'a Value, member: &str) -> Result<&'a Vec<Value>, Rfc8427Error> {
+    value
+        .as_array()
+        .ok_or_else(|| Rfc8427Error::InvalidMember(member.into()))
+}
+
+fn get_str<'a>(object: &'a Map<String, Value>, member: &str) -> Result<&'a str, Rfc8427Error> {
+    object
+        .get(member)
+        .ok_or_else(|| Rfc8427Error::MissingMember(member.into()))?
+        .as_str()
+        .ok_or_else(|| Rfc8427Error::InvalidMember(member.into()))
+}
+
+/// Reads a name, rejecting the ones that do not fit in wire format: labels of more than
+/// 63 bytes, or more than 255 bytes in all (RFC 1035 §2.3.4).
+fn get_name(object: &Map<String, Value>, member: &str) -> Result<String, Rfc8427Error> {
+    let name = get_str(object, member)?;
+    // The wire format starts with the length of the first label and ends with the root.
+    let mut length = 1;
+    let mut label = 0;
+    let mut bytes = name.bytes();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'.' => {
+                length += label + (label > 0) as usize;
+                label = 0;
+            }
+            b'\\' => label += bytes.next().map_or(0, |_| 1),
+            _ => label += 1,
+        }
+        if label > 63 {
+            return Err(Rfc8427Error::InvalidName(name.into()));
+        }
+    }
+    length += label + (label > 0) as usize;
+    if length > 255 {
+        return Err(Rfc8427Error::InvalidName(name.into()));
+    }
+    Ok(name_from_token(name))
+}
+
+fn get_u32(object: &Map<String, Value>, member: &str) -> Result<Option<u32>, Rfc8427Error> {
+    object
+        .get(member)
+        .map(|value| {
+            value
+                .as_u64()
+                .and_then(|value| u32::try_from(value).ok())
+                .ok_or_else(|| Rfc8427Error::InvalidMember(member.into()))
+        })
+        .transpose()
+}
+
+fn get_u16(object: &Map<String, Value>, member: &str) -> Result<Option<u16>, Rfc8427Error> {
+    get_u32(object, member)?
+        .map(|value| u16::try_from(value).map_err(|_| Rfc8427Error::InvalidMember(member.into())))
+        .transpose()
+}
+
+/// Reads `<prefix>TYPE`, falling back to the `<prefix>TYPEname` mnemonic.
+fn get_type(object: &Map<String, Value>, prefix: &str) -> Result<DnsType, Rfc8427Error> {
+    let member = format!("{}TYPE", prefix);
+    if let Some(value) = get_u16(object, &member)? {
+        return Ok(DnsType(value));
+    }
+    let member = format!("{}TYPEname", prefix);
+    get_str(object, &member)?
+        .parse()
+        .map_err(|_| Rfc8427Error::InvalidMember(member))
+}
+
+/// Reads `<prefix>CLASS`, falling back to the `<prefix>CLASSname` mnemonic, then to IN.
+fn get_class(object: &Map<String, Value>, prefix: &str) -> Result<DnsClass, Rfc8427Error> {
+    let member = format!("{}CLASS", prefix);
+    if let Some(value) = get_u16(object, &member)? {
+        return Ok(DnsClass(value));
+    }
+    let member = format!("{}CLASSname", prefix);
+    match object.get(&member) {
+        Some(_) => get_str(object, &member)?
+            .parse()
+            .map_err(|_| Rfc8427Error::InvalidMember(member)),
+        None => Ok(DnsClass(1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::dns_types::DnsTypes;
+
+    const RESPONSE: &str = "abcd81800001000100000000076578616d706c6503636f6d0000010001c00c000100010000012c00045db8d822";
+
+    #[test]
+    fn test_to_rfc8427() {
+        let data = hex::decode(RESPONSE).unwrap();
+        let packet = DnsPacket::try_from(data.as_slice()).unwrap();
+
+        let value = to_rfc8427(&packet, Some(&data));
+        assert_eq!(value["ID"], 0xabcd);
+        assert_eq!(value["QR"], true);
+        assert_eq!(value["RD"], true);
+        assert_eq!(value["RA"], true);
+        assert_eq!(value["AA"], false);
+        assert_eq!(value["Opcode"], 0);
+        assert_eq!(value["RCODE"], 0);
+        assert_eq!(value["QDCOUNT"], 1);
+        assert_eq!(value["ANCOUNT"], 1);
+        assert_eq!(value["QNAME"], "example.com");
+        assert_eq!(value["QTYPEname"], "A");
+        assert_eq!(
+            value["questionRRs"],
+            json!([{ "NAME": "example.com", "TYPE": 1, "TYPEname": "A", "CLASS": 1, "CLASSname": "IN" }])
+        );
+        assert_eq!(
+            value["answerRRs"],
+            json!([{
+                "NAME": "example.com",
+                "TYPE": 1,
+                "TYPEname": "A",
+                "CLASS": 1,
+                "CLASSname": "IN",
+                "TTL": 300,
+                "RDLENGTH": 4,
+                "RDATAHEX": "5DB8D822",
+                "rdataA": "93.184.216.34"
+            }])
+        );
+        assert!(value.get("authorityRRs").is_none());
+        assert_eq!(value["messageOctetsHEX"], RESPONSE.to_uppercase());
+    }
+
+    #[test]
+    fn test_from_rfc8427_round_trip() {
+        let data = hex::decode(RESPONSE).unwrap();
+        let packet = DnsPacket::try_from(data.as_slice()).unwrap();
+
+        let imported = from_rfc8427(&to_rfc8427(&packet, None)).unwrap();
+        assert_eq!(imported.header.transaction_id, packet.header.transaction_id);
+        assert_eq!(imported.header.flags, packet.header.flags);
+        assert_eq!(imported.header.counts, packet.header.counts);
+        assert_eq!(imported.queries, packet.queries);
+        assert_eq!(imported.answers, packet.answers);
+        assert_eq!(imported.authorities, None);
+    }
+
+    #[test]
+    fn test_from_rfc8427_message_octets() {
+        let value = json!({ "messageOctetsHEX": RESPONSE, "ID": 1 });
+        let packet = from_rfc8427(&value).unwrap();
+        assert_eq!(packet.header.transaction_id, 0xabcd);
+        assert_eq!(packet.answers.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_from_rfc8427_presentation_rdata() {
+        let value = json!({
+            "ID": 7,
+            "QR": true,
+            "AA": true,
+            "QNAME": "example.com",
+            "QTYPEname": "MX",
+            "answerRRs": [{
+                "NAME": "example.com",
+                "TYPEname": "MX",
+                "TTL": 60,
+                "rdataMX": "10 mail.example.com"
+            }]
+        });
+        let packet = from_rfc8427(&value).unwrap();
+        assert_eq!(packet.header.flags, 0x8400);
+        assert_eq!(packet.header.counts, [1, 1, 0, 0]);
+        assert_eq!(packet.queries.queries[0].qtype, DnsTypes::MX);
+        let answer = &packet.answers.unwrap()[0];
+        assert_eq!(answer.rdata.to_string(), "10 mail.example.com");
+        assert_eq!(answer.data_length, 20);
+    }
+
+    #[test]
+    fn test_from_rfc8427_errors() {
+        assert!(matches!(
+            from_rfc8427(&json!([])),
+            Err(Rfc8427Error::NotAnObject("message"))
+        ));
+        assert!(matches!(
+            from_rfc8427(&json!({ "QR": 1 })),
+            Err(Rfc8427Error::InvalidMember(member)) if member == "QR"
+        ));
+        assert!(matches!(
+            from_rfc8427(&json!({ "answerRRs": [{ "NAME": "a", "TYPE": 1 }] })),
+            Err(Rfc8427Error::MissingMember(member)) if member == "rdataA"
+        ));
+
+        let label = "a".repeat(64);
+        assert!(matches!(
+            from_rfc8427(&json!({ "QNAME": format!("{label}.com") })),
+            Err(Rfc8427Error::InvalidName(_))
+        ));
+        let name = vec!["a".repeat(63); 4].join(".");
+        assert!(matches!(
+            from_rfc8427(&json!({ "questionRRs": [{ "NAME": name, "TYPE": 1 }] })),
+            Err(Rfc8427Error::InvalidName(_))
+        ));
+        let name = [
+            "a".repeat(63),
+            "a".repeat(63),
+            "a".repeat(63),
+            "a".repeat(61),
+        ]
+        .join(".");
+        assert!(from_rfc8427(&json!({ "QNAME": name, "QTYPE": 1 })).is_ok());
+    }
+
+    #[test]
+    fn test_rfc8427_root_name_round_trip() {
+        // A priming query for the root NS records, and its answer.
+        let data = hex::decode(
+            "00018180000100010000000000000200010000020001\
+            00000e100008016104726f6f7400",
+        )
+        .unwrap();
+        let packet = DnsPacket::try_from(data.as_slice()).unwrap();
+        let value = to_rfc8427(&packet, None);
+        assert_eq!(value["QNAME"], ".");
+        assert_eq!(value["answerRRs"][0]["NAME"], ".");
+
+        let imported = from_rfc8427(&value).unwrap();
+        assert_eq!(imported.queries, packet.queries);
+        assert_eq!(imported.answers, packet.answers);
+        assert_eq!(imported.queries.queries[0].name, "");
+    }
+}
@@ -0,0 +1,58 @@
+use thiserror::Error;
+
+use crate::dns_records::rdata::Tsig;
+#[cfg(feature = "dnssec")]
+use crate::dnssec::BogusReason;
+use crate::errors::DnsPacketError;
+use crate::prelude::*;
+
+/// Why a message could not be signed, or its transaction signature did not verify.
+#[derive(Debug, Error)]
+pub enum TsigError {
+    #[error("Malformed message: {0}")]
+    Malformed(DnsPacketError),
+    #[error("The message has no transaction signature")]
+    Unsigned,
+    #[error("The message already has a transaction signature")]
+    AlreadySigned,
+    #[error("The transaction signature is not the last additional record")]
+    NotLast,
+    #[error("Unsupported TSIG algorithm {0}")]
+    UnsupportedAlgorithm(String),
+    #[error("The message is signed with the key {name} ({algorithm}), not the given key")]
+    KeyMismatch { name: String, algorithm: String },
+    #[error("Invalid MAC size {0}")]
+    BadTruncation(usize),
+    #[error("The MAC does not match the message")]
+    BadSignature,
+    #[error("The message was signed at {time_signed}, more than {fudge} seconds from {now}")]
+    BadTime {
+        time_signed: u64,
+        fudge: u16,
+        now: u64,
+    },
+    #[error("The signer answered with the TSIG error {0}")]
+    Rejected(u16),
+    #[cfg(feature = "dnssec")]
+    #[error("The SIG(0) signature is bogus: {0}")]
+    Bogus(BogusReason),
+}
+
+impl TsigError {
+    /// The RCODE a server answers a request failing verification with (RFC 8945 §5.2):
+    /// FORMERR, or one of the extended RCODEs of `Tsig`.
+    pub fn rcode(&self) -> Option<u16> {
+        match self {
+            TsigError::Malformed(_) | TsigError::NotLast => Some(1),
+            TsigError::UnsupportedAlgorithm(_) | TsigError::KeyMismatch { .. } => {
+                Some(Tsig::BADKEY)
+            }
+            TsigError::BadTruncation(_) => Some(Tsig::BADTRUNC),
+            TsigError::BadSignature => Some(Tsig::BADSIG),
+            TsigError::BadTime { .. } => Some(Tsig::BADTIME),
+            #[cfg(feature = "dnssec")]
+            TsigError::Bogus(_) => Some(Tsig::BADSIG),
+            TsigError::Unsigned | TsigError::AlreadySigned | TsigError::Rejected(_) => None,
+        }
+    }
+}
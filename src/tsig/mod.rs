@@ -0,0 +1,456 @@
+//! Transaction signatures: TSIG (RFC 8945) and, with the `dnssec` feature, SIG(0)
+//! (RFC 2931).
+//!
+//! `sign` appends a TSIG record to a message with a key shared by both ends of the
+//! transaction, and `verify` checks the TSIG record ending a message. The MAC algorithms
+//! HMAC-SHA1, HMAC-SHA256, HMAC-SHA384 and HMAC-SHA512 are supported; GSS-TSIG records
+//! are recognized by `Tsig::is_gss`, but their MAC needs the GSS-API context. The MAC of a
+//! response also covers the MAC of its request, and each message of a zone transfer the
+//! MAC of the previous one: `Context` tells which.
+//!
+//! `verify_sig0` checks a SIG(0) record, made with the private key of a KEY record.
+
+use hmac::digest::KeyInit;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Sha256, Sha384, Sha512};
+
+pub use crate::dns_records::rdata::TsigAlgorithms;
+use crate::dns_records::rdata::{RData, Tsig};
+use crate::dns_records::{write_name, ResourceRecord};
+use crate::errors::{DnsPacketError, Section};
+use crate::prelude::*;
+use crate::utils::dns_class::DnsClasses;
+use crate::utils::dns_types::{DnsType, DnsTypes};
+use crate::validation::{ParseOptions, Rule, RuleAction, ValidationProfile};
+use crate::DnsPacketRef;
+pub use errors::TsigError;
+#[cfg(feature = "dnssec")]
+pub use sig0::verify_sig0;
+
+mod errors;
+#[cfg(feature = "dnssec")]
+mod sig0;
+
+/// A secret key shared by the two ends of a transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TsigKey {
+    /// The name of the key, which owns the TSIG records.
+    pub name: String,
+    /// The MAC algorithm, one of the HMAC ones of `TsigAlgorithms`.
+    pub algorithm: String,
+    pub secret: Vec<u8>,
+}
+
+/// The place of a message in its transaction, which decides what its MAC covers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Context<'a> {
+    /// A request, or any message not answering a signed one.
+    Request,
+    /// The response to a request whose MAC was `request_mac`.
+    Response { request_mac: &'a [u8] },
+    /// A message after the first one of a multi-message response, such as a zone
+    /// transfer, following a message whose MAC was `previous_mac`. Of the TSIG fields,
+    /// only the time signed and the fudge are covered (RFC 8945 §5.3.1).
+    Continuation { previous_mac: &'a [u8] },
+}
+
+/// Signs `message` with `key`: appends a TSIG record and counts it in ARCOUNT.
+///
+/// `time_signed` is in seconds since the Unix epoch, and `fudge` the seconds of clock
+/// skew the receiver should accept. Returns the data of the appended record, whose MAC
+/// the next message of the transaction refers to.
+pub fn sign(
+    message: &mut Vec<u8>,
+    key: &TsigKey,
+    time_signed: u64,
+    fudge: u16,
+    context: Context<'_>,
+) -> Result<Tsig, TsigError> {
+    let packet = parse(message)?;
+    if packet
+        .additionals()
+        .any(|record| record.rtype == DnsTypes::TSIG)
+    {
+        return Err(TsigError::AlreadySigned);
+    }
+    let original_id = packet.header.transaction_id;
+
+    let mut tsig = Tsig {
+        algorithm: key.algorithm.clone(),
+        time_signed,
+        fudge,
+        mac: Vec::new(),
+        original_id,
+        error: 0,
+        other: Vec::new(),
+    };
+    tsig.mac = mac(key, &signed_data(message, &key.name, &tsig, context))?;
+
+    write_name(&key.name, message);
+    message.extend_from_slice(&DnsTypes::TSIG.0.to_be_bytes());
+    message.extend_from_slice(&DnsClasses::ANY.0.to_be_bytes());
+    message.extend_from_slice(&0u32.to_be_bytes());
+    let mut rdata = Vec::new();
+    tsig.to_wire(&mut rdata);
+    message.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+    message.extend_from_slice(&rdata);
+    add_to_arcount(message, 1);
+    Ok(tsig)
+}
+
+/// Verifies the TSIG record ending `message` with `key`, at `now` (seconds since the
+/// Unix epoch), and returns it.
+///
+/// The record must be the last additional record, its MAC must match, possibly
+/// truncated to half the hash size (RFC 8945 §5.2.2.1), and its time signed must be
+/// within its fudge of `now`. A signed error response is reported as
+/// `TsigError::Rejected`, after its MAC is checked.
+pub fn verify(
+    message: &[u8],
+    key: &TsigKey,
+    now: u64,
+    context: Context<'_>,
+) -> Result<Tsig, TsigError> {
+    let (start, record) = find_signature(message, DnsTypes::TSIG, |_| true)?;
+    let RData::TSIG(tsig) = record.rdata else {
+        return Err(TsigError::Unsigned);
+    };
+    if !same_name(&record.name, &key.name) || !same_name(&tsig.algorithm, &key.algorithm) {
+        return Err(TsigError::KeyMismatch {
+            name: record.name,
+            algorithm: tsig.algorithm,
+        });
+    }
+    // The answers to requests with an unknown key or a bad MAC are not signed.
+    if matches!(tsig.error, Tsig::BADKEY | Tsig::BADSIG) {
+        return Err(TsigError::Rejected(tsig.error));
+    }
+
+    // The MAC covers the message as signed: with its original ID and without the TSIG.
+    let mut unsigned = message[..start].to_vec();
+    unsigned[..2].copy_from_slice(&tsig.original_id.to_be_bytes());
+    add_to_arcount(&mut unsigned, -1);
+    let expected = mac(key, &signed_data(&unsigned, &key.name, &tsig, context))?;
+
+    let size = tsig.mac.len();
+    if size > expected.len() || size < (expected.len() / 2).max(10) {
+        return Err(TsigError::BadTruncation(size));
+    }
+    let difference = expected
+        .iter()
+        .zip(&tsig.mac)
+        .fold(0, |difference, (a, b)| difference | (a ^ b));
+    if difference != 0 {
+        return Err(TsigError::BadSignature);
+    }
+    if now.abs_diff(tsig.time_signed) > tsig.fudge as u64 {
+        return Err(TsigError::BadTime {
+            time_signed: tsig.time_signed,
+            fudge: tsig.fudge,
+            now,
+        });
+    }
+    if tsig.error != 0 {
+        return Err(TsigError::Rejected(tsig.error));
+    }
+    Ok(tsig)
+}
+
+/// The data a TSIG MAC is computed over (RFC 8945 §4.3): the MAC the message refers
+/// to, the message without its TSIG record, and the TSIG variables.
+fn signed_data(unsigned: &[u8], key_name: &str, tsig: &Tsig, context: Context<'_>) -> Vec<u8> {
+    let mut data = Vec::new();
+    if let Context::Response {
+        request_mac: previous,
+    }
+    | Context::Continuation {
+        previous_mac: previous,
+    } = context
+    {
+        data.extend_from_slice(&(previous.len() as u16).to_be_bytes());
+        data.extend_from_slice(previous);
+    }
+    data.extend_from_slice(unsigned);
+    let timers = |data: &mut Vec<u8>| {
+        data.extend_from_slice(&tsig.time_signed.to_be_bytes()[2..]);
+        data.extend_from_slice(&tsig.fudge.to_be_bytes());
+    };
+    if let Context::Continuation { .. } = context {
+        timers(&mut data);
+        return data;
+    }
+    write_name(&key_name.to_ascii_lowercase(), &mut data);
+    data.extend_from_slice(&DnsClasses::ANY.0.to_be_bytes());
+    data.extend_from_slice(&0u32.to_be_bytes());
+    write_name(&tsig.algorithm.to_ascii_lowercase(), &mut data);
+    timers(&mut data);
+    data.extend_from_slice(&tsig.error.to_be_bytes());
+    data.extend_from_slice(&(tsig.other.len() as u16).to_be_bytes());
+    data.extend_from_slice(&tsig.other);
+    data
+}
+
+fn mac(key: &TsigKey, data: &[u8]) -> Result<Vec<u8>, TsigError> {
+    let secret = &key.secret;
+    let algorithm = key.algorithm.trim_end_matches('.').to_ascii_lowercase();
+    Ok(match algorithm.as_str() {
+        TsigAlgorithms::HMAC_SHA1 => hmac::<Hmac<Sha1>>(secret, data),
+        TsigAlgorithms::HMAC_SHA256 => hmac::<Hmac<Sha256>>(secret, data),
+        TsigAlgorithms::HMAC_SHA384 => hmac::<Hmac<Sha384>>(secret, data),
+        TsigAlgorithms::HMAC_SHA512 => hmac::<Hmac<Sha512>>(secret, data),
+        _ => return Err(TsigError::UnsupportedAlgorithm(key.algorithm.clone())),
+    })
+}
+
+fn hmac<M: Mac + KeyInit>(secret: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = <M as Mac>::new_from_slice(secret).expect("HMAC takes keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Finds the transaction signature of `message`: the first record of type `rtype` whose
+/// data `is_signature` accepts. It must be the last additional record; returns it with
+/// the offset where it starts.
+fn find_signature(
+    message: &[u8],
+    rtype: DnsType,
+    is_signature: fn(&RData) -> bool,
+) -> Result<(usize, ResourceRecord), TsigError> {
+    let packet = parse(message)?;
+    let [_, answers, authorities, additionals] = packet.header.counts.map(|count| count as usize);
+    let mut records = packet.records();
+    for index in 0..answers + authorities + additionals {
+        let start = records.offset();
+        let record = records.next().ok_or(TsigError::Unsigned)?;
+        if record.rtype != rtype {
+            continue;
+        }
+        let (section, section_index) = match index {
+            index if index < answers => (Section::Answer, index),
+            index if index < answers + authorities => (Section::Authority, index - answers),
+            index => (Section::Additional, index - answers - authorities),
+        };
+        let record = record.to_record().map_err(|error| {
+            TsigError::Malformed(DnsPacketError::new(
+                error,
+                section,
+                Some(section_index),
+                start,
+            ))
+        })?;
+        if !is_signature(&record.rdata) {
+            continue;
+        }
+        if section != Section::Additional || section_index + 1 != additionals {
+            return Err(TsigError::NotLast);
+        }
+        return Ok((start, record));
+    }
+    Err(TsigError::Unsigned)
+}
+
+/// Parses a message to sign or verify. The header rules are ignored: the messages after
+/// the first one of a zone transfer have no question, and update responses use RCODEs
+/// such as NOTAUTH. The message must still end with its last record.
+fn parse(message: &[u8]) -> Result<DnsPacketRef<'_>, TsigError> {
+    let options = ParseOptions::new(ValidationProfile::Lenient)
+        .with_rule(Rule::TrailingData, RuleAction::Error);
+    DnsPacketRef::parse_with(message, &options).map_err(TsigError::Malformed)
+}
+
+/// Adds `delta` to the ARCOUNT of `message`, to count a signature record in or out.
+fn add_to_arcount(message: &mut [u8], delta: i32) {
+    let count = u16::from_be_bytes([message[10], message[11]]) as i32 + delta;
+    message[10..12].copy_from_slice(&(count as u16).to_be_bytes());
+}
+
+fn same_name(a: &str, b: &str) -> bool {
+    a.trim_end_matches('.')
+        .eq_ignore_ascii_case(b.trim_end_matches('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A query for `example.com A`, with the ID 0x1234.
+    const QUERY: &str = "123401000001000000000000076578616d706c6503636f6d0000010001";
+    /// The query signed with `key()` at 1700000000, with a fudge of 300 seconds.
+    const SIGNED_QUERY: &str = "123401000001000000000001076578616d706c6503636f6d0000010001\
+        036b6579076578616d706c650000fa00ff00000000003d0b686d61632d7368613235360000006553f1\
+        00012c00201017653474332ff8b92781e97eb26944398748f8bc936751d9a49278d1d8662c12340000\
+        0000";
+
+    fn key() -> TsigKey {
+        TsigKey {
+            name: "key.example".to_string(),
+            algorithm: TsigAlgorithms::HMAC_SHA256.to_string(),
+            secret: (0..32).collect(),
+        }
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let mut message = hex::decode(QUERY).unwrap();
+        let tsig = sign(&mut message, &key(), 1_700_000_000, 300, Context::Request).unwrap();
+        assert_eq!(message, hex::decode(SIGNED_QUERY).unwrap());
+        assert_eq!(tsig.original_id, 0x1234);
+
+        let verified = verify(&message, &key(), 1_700_000_100, Context::Request).unwrap();
+        assert_eq!(verified, tsig);
+        // A forwarder may change the ID: the original one is signed.
+        message[..2].copy_from_slice(&[0xab, 0xcd]);
+        assert!(verify(&message, &key(), 1_700_000_100, Context::Request).is_ok());
+        assert!(matches!(
+            sign(&mut message, &key(), 1_700_000_000, 300, Context::Request),
+            Err(TsigError::AlreadySigned)
+        ));
+    }
+
+    #[test]
+    fn test_response_covers_request_mac() {
+        let mut request = hex::decode(QUERY).unwrap();
+        let request_tsig =
+            sign(&mut request, &key(), 1_700_000_000, 300, Context::Request).unwrap();
+        let mut response = hex::decode(QUERY).unwrap();
+        response[2] |= 0x80;
+        let context = Context::Response {
+            request_mac: &request_tsig.mac,
+        };
+        let response_tsig = sign(&mut response, &key(), 1_700_000_001, 300, context).unwrap();
+        assert!(verify(&response, &key(), 1_700_000_001, context).is_ok());
+        assert!(matches!(
+            verify(&response, &key(), 1_700_000_001, Context::Request),
+            Err(TsigError::BadSignature)
+        ));
+
+        let mut next = hex::decode(QUERY).unwrap();
+        next[2] |= 0x80;
+        let context = Context::Continuation {
+            previous_mac: &response_tsig.mac,
+        };
+        sign(&mut next, &key(), 1_700_000_002, 300, context).unwrap();
+        assert!(verify(&next, &key(), 1_700_000_002, context).is_ok());
+    }
+
+    #[test]
+    fn test_zone_transfer_and_update_messages() {
+        // The second message of a zone transfer, without a question: one A record.
+        let mut next = hex::decode(
+            "123484000000000100000000076578616d706c6503636f6d00000100010000012c0004c0000201",
+        )
+        .unwrap();
+        let previous_mac = [0xab; 32];
+        let context = Context::Continuation {
+            previous_mac: &previous_mac,
+        };
+        sign(&mut next, &key(), 1_700_000_000, 300, context).unwrap();
+        assert!(verify(&next, &key(), 1_700_000_000, context).is_ok());
+
+        // A NOTAUTH (9) response to an UPDATE for the zone example.com.
+        let mut response =
+            hex::decode("1234a8090001000000000000076578616d706c6503636f6d0000060001").unwrap();
+        let context = Context::Response {
+            request_mac: &previous_mac,
+        };
+        sign(&mut response, &key(), 1_700_000_000, 300, context).unwrap();
+        assert!(verify(&response, &key(), 1_700_000_000, context).is_ok());
+
+        // Trailing data is not covered by the MAC.
+        response.push(0);
+        assert!(matches!(
+            verify(&response, &key(), 1_700_000_000, context),
+            Err(TsigError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_failures() {
+        let message = hex::decode(SIGNED_QUERY).unwrap();
+        let now = 1_700_000_000;
+        assert!(matches!(
+            verify(&hex::decode(QUERY).unwrap(), &key(), now, Context::Request),
+            Err(TsigError::Unsigned)
+        ));
+        let other = TsigKey {
+            name: "other.example".to_string(),
+            ..key()
+        };
+        assert!(matches!(
+            verify(&message, &other, now, Context::Request),
+            Err(TsigError::KeyMismatch { .. })
+        ));
+        let wrong_secret = TsigKey {
+            secret: vec![0; 32],
+            ..key()
+        };
+        let error = verify(&message, &wrong_secret, now, Context::Request).unwrap_err();
+        assert!(matches!(error, TsigError::BadSignature));
+        assert_eq!(error.rcode(), Some(Tsig::BADSIG));
+        let error = verify(&message, &key(), now + 301, Context::Request).unwrap_err();
+        assert!(matches!(error, TsigError::BadTime { .. }));
+        assert_eq!(error.rcode(), Some(Tsig::BADTIME));
+
+        // A MAC truncated below half the hash size.
+        let mut truncated = hex::decode(QUERY).unwrap();
+        let mut tsig = verify(&message, &key(), now, Context::Request).unwrap();
+        tsig.mac.truncate(15);
+        write_name("key.example", &mut truncated);
+        truncated.extend_from_slice(&[0, 250, 0, 255, 0, 0, 0, 0]);
+        let mut rdata = Vec::new();
+        tsig.to_wire(&mut rdata);
+        truncated.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        truncated.extend_from_slice(&rdata);
+        add_to_arcount(&mut truncated, 1);
+        assert!(matches!(
+            verify(&truncated, &key(), now, Context::Request),
+            Err(TsigError::BadTruncation(15))
+        ));
+    }
+
+    #[test]
+    fn test_tsig_must_be_last() {
+        let mut message = hex::decode(SIGNED_QUERY).unwrap();
+        // An OPT record after the TSIG record.
+        message.extend_from_slice(&[0, 0, 41, 4, 0xd0, 0, 0, 0, 0, 0, 0]);
+        add_to_arcount(&mut message, 1);
+        let error = verify(&message, &key(), 1_700_000_000, Context::Request).unwrap_err();
+        assert!(matches!(error, TsigError::NotLast));
+        assert_eq!(error.rcode(), Some(1));
+    }
+
+    #[cfg(feature = "dnssec")]
+    #[test]
+    fn test_verify_sig0() {
+        use crate::dns_records::rdata::Dnskey;
+        use crate::dnssec::BogusReason;
+
+        const SIGNED: &str = "123401000001000000000001076578616d706c6503636f6d0000010001\
+            00001800ff00000000005f00000f00000000006553f22c6553efd42e09076578616d706c6503636f6d\
+            00e1d5c50a53b13303870bc41ba5dc6c7be9bd2e5a8a346b3a0b91bb8e0a294031ebc6a688e4f2cc7e\
+            10b35f2f424360db5c7e09b10d0b0bcd9bf1104fa5530509";
+        let key = Dnskey {
+            flags: 0x0200,
+            protocol: 3,
+            algorithm: 15,
+            public_key: hex::decode(
+                "29acbae141bccaf0b22e1a94d34d0bc7361e526d0bfe12c89794bc9322966dd7",
+            )
+            .unwrap(),
+        };
+        let mut message = hex::decode(SIGNED).unwrap();
+        let sig = verify_sig0(&message, &key, 1_700_000_000, None).unwrap();
+        assert_eq!(sig.key_tag, 11785);
+        assert_eq!(sig.signer, "example.com");
+        assert!(matches!(
+            verify_sig0(&message, &key, 1_700_000_301, None),
+            Err(TsigError::Bogus(BogusReason::Expired { .. }))
+        ));
+        message[3] ^= 1;
+        assert!(matches!(
+            verify_sig0(&message, &key, 1_700_000_000, None),
+            Err(TsigError::Bogus(_))
+        ));
+    }
+}
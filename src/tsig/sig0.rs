@@ -0,0 +1,69 @@
+//! SIG(0) transaction signatures (RFC 2931), made with the private key of a KEY record.
+
+use super::{add_to_arcount, find_signature, TsigError};
+use crate::dns_records::rdata::{Dnskey, RData, Rrsig, Timestamp};
+use crate::dnssec::{crypto, serial_lt, BogusReason};
+use crate::prelude::*;
+use crate::utils::dns_types::{DnsType, DnsTypes};
+
+/// Verifies the SIG(0) record ending `message` with `key`, the data of the signer's KEY
+/// record, at `now` (seconds since the Unix epoch), and returns it.
+///
+/// The signature of a response also covers its request, which must then be given as
+/// `request`, signature included.
+pub fn verify_sig0(
+    message: &[u8],
+    key: &Dnskey,
+    now: u64,
+    request: Option<&[u8]>,
+) -> Result<Rrsig, TsigError> {
+    let (start, record) = find_signature(
+        message,
+        DnsTypes::SIG,
+        |rdata| matches!(rdata, RData::SIG(sig) if sig.type_covered == DnsType(0)),
+    )?;
+    let RData::SIG(sig) = record.rdata else {
+        return Err(TsigError::Unsigned);
+    };
+    if sig.algorithm != key.algorithm {
+        return Err(TsigError::Bogus(BogusReason::AlgorithmMismatch {
+            rrsig: sig.algorithm,
+            dnskey: key.algorithm,
+        }));
+    }
+    if sig.key_tag != key.key_tag() {
+        return Err(TsigError::Bogus(BogusReason::KeyTagMismatch {
+            rrsig: sig.key_tag,
+            dnskey: key.key_tag(),
+        }));
+    }
+    let now = Timestamp(now as u32);
+    if serial_lt(sig.expiration, now) {
+        return Err(TsigError::Bogus(BogusReason::Expired {
+            expiration: sig.expiration,
+            now,
+        }));
+    }
+    if serial_lt(now, sig.inception) {
+        return Err(TsigError::Bogus(BogusReason::NotYetValid {
+            inception: sig.inception,
+            now,
+        }));
+    }
+
+    // The signature covers its own data without the signature, the request, and the
+    // message without the SIG record (RFC 2931 §3.1).
+    let mut data = Vec::new();
+    Rrsig {
+        signature: Vec::new(),
+        ..sig.clone()
+    }
+    .to_wire(&mut data);
+    data.extend_from_slice(request.unwrap_or_default());
+    let offset = data.len();
+    data.extend_from_slice(&message[..start]);
+    add_to_arcount(&mut data[offset..], -1);
+    crypto::verify(sig.algorithm, &key.public_key, &data, &sig.signature)
+        .map_err(TsigError::Bogus)?;
+    Ok(sig)
+}
@@ -1,4 +1,17 @@
-use std::fmt;
+use core::fmt;
+#[cfg(feature = "alloc")]
+use core::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+#[cfg(feature = "alloc")]
+use thiserror::Error;
+
+#[cfg(feature = "alloc")]
+use crate::prelude::*;
 
 #[allow(non_snake_case)]
 #[allow(non_upper_case_globals)]
@@ -9,6 +22,10 @@ pub mod DnsClasses {
     pub const CS: DnsClass = DnsClass(2);
     pub const CH: DnsClass = DnsClass(3);
     pub const HS: DnsClass = DnsClass(4);
+    /// The class of prerequisites and deletions in dynamic updates (RFC 2136).
+    pub const NONE: DnsClass = DnsClass(254);
+    /// Any class in queries; also the class of TSIG and TKEY records.
+    pub const ANY: DnsClass = DnsClass(255);
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -18,20 +35,130 @@ impl DnsClass {
     pub fn new(value: u16) -> Self {
         Self(value)
     }
+
+    /// Returns the mnemonic of the class (e.g. `"IN"`), or `None` if the class is unknown.
+    pub fn mnemonic(&self) -> Option<&'static str> {
+        Some(match *self {
+            DnsClasses::IN => "IN",     // 1
+            DnsClasses::CS => "CS",     // 2
+            DnsClasses::CH => "CH",     // 3
+            DnsClasses::HS => "HS",     // 4
+            DnsClasses::NONE => "NONE", // 254
+            DnsClasses::ANY => "ANY",   // 255
+            _ => return None,
+        })
+    }
+
+    /// Splits off the top bit that mDNS (RFC 6762) borrows from the class: the
+    /// unicast-response (QU) bit in questions, the cache-flush bit in records.
+    pub fn split_mdns_flag(self) -> (DnsClass, bool) {
+        (DnsClass(self.0 & 0x7FFF), self.0 & 0x8000 != 0)
+    }
 }
 
 impl fmt::Display for DnsClass {
+    /// Formats the mnemonic, or the generic `CLASS<n>` form of RFC 3597 for unknown classes.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match *self {
-                DnsClasses::IN => "IN", // 1
-                DnsClasses::CS => "CS", // 2
-                DnsClasses::CH => "CH", // 3
-                DnsClasses::HS => "HS", // 4
-                _ => "unknown",
+        match self.mnemonic() {
+            Some(mnemonic) => write!(f, "{}", mnemonic),
+            None => write!(f, "CLASS{}", self.0),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FromStr for DnsClass {
+    type Err = DnsClassParseError;
+
+    /// Parses a class mnemonic (`"IN"`) or its generic `CLASS<n>` form (`"CLASS1"`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(value) = s.strip_prefix("CLASS") {
+            if let Ok(value) = value.parse::<u16>() {
+                return Ok(DnsClass(value));
             }
-        )
+        }
+        [
+            DnsClasses::IN,
+            DnsClasses::CS,
+            DnsClasses::CH,
+            DnsClasses::HS,
+            DnsClasses::NONE,
+            DnsClasses::ANY,
+        ]
+        .into_iter()
+        .find(|dns_class| dns_class.mnemonic() == Some(s))
+        .ok_or_else(|| DnsClassParseError(s.to_string()))
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Debug, Error, PartialEq)]
+#[error("Unknown DNS class mnemonic: {0}")]
+pub struct DnsClassParseError(String);
+
+#[cfg(feature = "serde")]
+impl Serialize for DnsClass {
+    /// Serializes the class as its mnemonic, falling back to `CLASS<n>` for unknown classes.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for DnsClass {
+    /// Accepts a mnemonic, a `CLASS<n>` string or a bare integer.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DnsClassVisitor;
+
+        impl Visitor<'_> for DnsClassVisitor {
+            type Value = DnsClass;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a DNS class mnemonic or a 16-bit integer")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<DnsClass, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<DnsClass, E> {
+                u16::try_from(v).map(DnsClass).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(DnsClassVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_mdns_flag() {
+        assert_eq!(DnsClass(0x8001).split_mdns_flag(), (DnsClasses::IN, true));
+        assert_eq!(DnsClasses::IN.split_mdns_flag(), (DnsClasses::IN, false));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_dns_class_from_str() {
+        assert_eq!("IN".parse(), Ok(DnsClasses::IN));
+        assert_eq!("CLASS1232".parse(), Ok(DnsClass(1232)));
+        assert!("XX".parse::<DnsClass>().is_err());
+        assert_eq!(DnsClass(99).to_string(), "CLASS99");
+        assert_eq!(DnsClass(99).to_string().parse(), Ok(DnsClass(99)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_dns_class_serde() {
+        assert_eq!(serde_json::to_string(&DnsClasses::CH).unwrap(), "\"CH\"");
+        assert_eq!(
+            serde_json::to_string(&DnsClass(1232)).unwrap(),
+            "\"CLASS1232\""
+        );
+        let dns_class: DnsClass = serde_json::from_str("\"IN\"").unwrap();
+        assert_eq!(dns_class, DnsClasses::IN);
     }
 }
@@ -1,4 +1,17 @@
-use std::fmt;
+use core::fmt;
+#[cfg(feature = "alloc")]
+use core::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+#[cfg(feature = "alloc")]
+use thiserror::Error;
+
+#[cfg(feature = "alloc")]
+use crate::prelude::*;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DnsType(pub u16);
@@ -105,103 +118,207 @@ pub mod DnsTypes {
     pub const DLV: DnsType = DnsType(32769);
 }
 
+impl DnsType {
+    /// Returns the IANA mnemonic of the type (e.g. `"AAAA"`), or `None` if the type is unknown.
+    pub fn mnemonic(&self) -> Option<&'static str> {
+        Some(match *self {
+            DnsTypes::A => "A",                   // 1
+            DnsTypes::NS => "NS",                 // 2
+            DnsTypes::MD => "MD",                 // 3
+            DnsTypes::MF => "MF",                 // 4
+            DnsTypes::CNAME => "CNAME",           // 5
+            DnsTypes::SOA => "SOA",               // 6
+            DnsTypes::MB => "MB",                 // 7
+            DnsTypes::MG => "MG",                 // 8
+            DnsTypes::MR => "MR",                 // 9
+            DnsTypes::NULL => "NULL",             // 10
+            DnsTypes::WKS => "WKS",               // 11
+            DnsTypes::PTR => "PTR",               // 12
+            DnsTypes::HINFO => "HINFO",           // 13
+            DnsTypes::MINFO => "MINFO",           // 14
+            DnsTypes::MX => "MX",                 // 15
+            DnsTypes::TXT => "TXT",               // 16
+            DnsTypes::RP => "RP",                 // 17
+            DnsTypes::AFSDB => "AFSDB",           // 18
+            DnsTypes::X25 => "X25",               // 19
+            DnsTypes::ISDN => "ISDN",             // 20
+            DnsTypes::RT => "RT",                 // 21
+            DnsTypes::NSAP => "NSAP",             // 22
+            DnsTypes::NSAP_PTR => "NSAP_PTR",     // 23
+            DnsTypes::SIG => "SIG",               // 24
+            DnsTypes::KEY => "KEY",               // 25
+            DnsTypes::PX => "PX",                 // 26
+            DnsTypes::GPOS => "GPOS",             // 27
+            DnsTypes::AAAA => "AAAA",             // 28
+            DnsTypes::LOC => "LOC",               // 29
+            DnsTypes::NXT => "NXT",               // 30
+            DnsTypes::EID => "EID",               // 31
+            DnsTypes::NIMLOC => "NIMLOC",         // 32
+            DnsTypes::SRV => "SRV",               // 33
+            DnsTypes::ATMA => "ATMA",             // 34
+            DnsTypes::NAPTR => "NAPTR",           // 35
+            DnsTypes::KX => "KX",                 // 36
+            DnsTypes::CERT => "CERT",             // 37
+            DnsTypes::A6 => "A6",                 // 38
+            DnsTypes::DNAME => "DNAME",           // 39
+            DnsTypes::SINK => "SINK",             // 40
+            DnsTypes::OPT => "OPT",               // 41
+            DnsTypes::APL => "APL",               // 42
+            DnsTypes::DS => "DS",                 // 43
+            DnsTypes::SSHFP => "SSHFP",           // 44
+            DnsTypes::IPSECKEY => "IPSECKEY",     // 45
+            DnsTypes::RRSIG => "RRSIG",           // 46
+            DnsTypes::NSEC => "NSEC",             // 47
+            DnsTypes::DNSKEY => "DNSKEY",         // 48
+            DnsTypes::DHCID => "DHCID",           // 49
+            DnsTypes::NSEC3 => "NSEC3",           // 50
+            DnsTypes::NSEC3PARAM => "NSEC3PARAM", // 51
+            DnsTypes::TLSA => "TLSA",             // 52
+            DnsTypes::SMIMEA => "SMIMEA",         // 53
+            DnsTypes::HIP => "HIP",               // 55
+            DnsTypes::NINFO => "NINFO",           // 56
+            DnsTypes::RKEY => "RKEY",             // 57
+            DnsTypes::TALINK => "TALINK",         // 58
+            DnsTypes::CDS => "CDS",               // 59
+            DnsTypes::CDNSKEY => "CDNSKEY",       // 60
+            DnsTypes::OPENPGPKEY => "OPENPGPKEY", // 61
+            DnsTypes::CSYNC => "CSYNC",           // 62
+            DnsTypes::ZONEMD => "ZONEMD",         // 63
+            DnsTypes::SVCB => "SVCB",             // 64
+            DnsTypes::HTTPS => "HTTPS",           // 65
+            DnsTypes::SPF => "SPF",               // 99
+            DnsTypes::UINFO => "UINFO",           // 100
+            DnsTypes::UID => "UID",               // 101
+            DnsTypes::GID => "GID",               // 102
+            DnsTypes::UNSPEC => "UNSPEC",         // 103
+            DnsTypes::NID => "NID",               // 104
+            DnsTypes::L32 => "L32",               // 105
+            DnsTypes::L64 => "L64",               // 106
+            DnsTypes::LP => "LP",                 // 107
+            DnsTypes::EUI48 => "EUI48",           // 108
+            DnsTypes::EUI64 => "EUI64",           // 109
+            DnsTypes::TKEY => "TKEY",             // 249
+            DnsTypes::TSIG => "TSIG",             // 250
+            DnsTypes::IXFR => "IXFR",             // 251
+            DnsTypes::AXFR => "AXFR",             // 252
+            DnsTypes::MAILB => "MAILB",           // 253
+            DnsTypes::MAILA => "MAILA",           // 254
+            DnsTypes::ANY => "ANY",               // 255
+            DnsTypes::URI => "URI",               // 256
+            DnsTypes::CAA => "CAA",               // 257
+            DnsTypes::AVC => "AVC",               // 258
+            DnsTypes::DOA => "DOA",               // 259
+            DnsTypes::AMTRELAY => "AMTRELAY",     // 260
+            DnsTypes::TA => "TA",                 // 32768
+            DnsTypes::DLV => "DLV",               // 32769
+            _ => return None,
+        })
+    }
+}
+
 impl fmt::Display for DnsType {
+    /// Formats the mnemonic, or the generic `TYPE<n>` form of RFC 3597 for unknown types.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match *self {
-                DnsTypes::A => "A",                   // 1
-                DnsTypes::NS => "NS",                 // 2
-                DnsTypes::MD => "MD",                 // 3
-                DnsTypes::MF => "MF",                 // 4
-                DnsTypes::CNAME => "CNAME",           // 5
-                DnsTypes::SOA => "SOA",               // 6
-                DnsTypes::MB => "MB",                 // 7
-                DnsTypes::MG => "MG",                 // 8
-                DnsTypes::MR => "MR",                 // 9
-                DnsTypes::NULL => "NULL",             // 10
-                DnsTypes::WKS => "WKS",               // 11
-                DnsTypes::PTR => "PTR",               // 12
-                DnsTypes::HINFO => "HINFO",           // 13
-                DnsTypes::MINFO => "MINFO",           // 14
-                DnsTypes::MX => "MX",                 // 15
-                DnsTypes::TXT => "TXT",               // 16
-                DnsTypes::RP => "RP",                 // 17
-                DnsTypes::AFSDB => "AFSDB",           // 18
-                DnsTypes::X25 => "X25",               // 19
-                DnsTypes::ISDN => "ISDN",             // 20
-                DnsTypes::RT => "RT",                 // 21
-                DnsTypes::NSAP => "NSAP",             // 22
-                DnsTypes::NSAP_PTR => "NSAP_PTR",     // 23
-                DnsTypes::SIG => "SIG",               // 24
-                DnsTypes::KEY => "KEY",               // 25
-                DnsTypes::PX => "PX",                 // 26
-                DnsTypes::GPOS => "GPOS",             // 27
-                DnsTypes::AAAA => "AAAA",             // 28
-                DnsTypes::LOC => "LOC",               // 29
-                DnsTypes::NXT => "NXT",               // 30
-                DnsTypes::EID => "EID",               // 31
-                DnsTypes::NIMLOC => "NIMLOC",         // 32
-                DnsTypes::SRV => "SRV",               // 33
-                DnsTypes::ATMA => "ATMA",             // 34
-                DnsTypes::NAPTR => "NAPTR",           // 35
-                DnsTypes::KX => "KX",                 // 36
-                DnsTypes::CERT => "CERT",             // 37
-                DnsTypes::A6 => "A6",                 // 38
-                DnsTypes::DNAME => "DNAME",           // 39
-                DnsTypes::SINK => "SINK",             // 40
-                DnsTypes::OPT => "OPT",               // 41
-                DnsTypes::APL => "APL",               // 42
-                DnsTypes::DS => "DS",                 // 43
-                DnsTypes::SSHFP => "SSHFP",           // 44
-                DnsTypes::IPSECKEY => "IPSECKEY",     // 45
-                DnsTypes::RRSIG => "RRSIG",           // 46
-                DnsTypes::NSEC => "NSEC",             // 47
-                DnsTypes::DNSKEY => "DNSKEY",         // 48
-                DnsTypes::DHCID => "DHCID",           // 49
-                DnsTypes::NSEC3 => "NSEC3",           // 50
-                DnsTypes::NSEC3PARAM => "NSEC3PARAM", // 51
-                DnsTypes::TLSA => "TLSA",             // 52
-                DnsTypes::SMIMEA => "SMIMEA",         // 53
-                DnsTypes::HIP => "HIP",               // 55
-                DnsTypes::NINFO => "NINFO",           // 56
-                DnsTypes::RKEY => "RKEY",             // 57
-                DnsTypes::TALINK => "TALINK",         // 58
-                DnsTypes::CDS => "CDS",               // 59
-                DnsTypes::CDNSKEY => "CDNSKEY",       // 60
-                DnsTypes::OPENPGPKEY => "OPENPGPKEY", // 61
-                DnsTypes::CSYNC => "CSYNC",           // 62
-                DnsTypes::ZONEMD => "ZONEMD",         // 63
-                DnsTypes::SVCB => "SVCB",             // 64
-                DnsTypes::HTTPS => "HTTPS",           // 65
-                DnsTypes::SPF => "SPF",               // 99
-                DnsTypes::UINFO => "UINFO",           // 100
-                DnsTypes::UID => "UID",               // 101
-                DnsTypes::GID => "GID",               // 102
-                DnsTypes::UNSPEC => "UNSPEC",         // 103
-                DnsTypes::NID => "NID",               // 104
-                DnsTypes::L32 => "L32",               // 105
-                DnsTypes::L64 => "L64",               // 106
-                DnsTypes::LP => "LP",                 // 107
-                DnsTypes::EUI48 => "EUI48",           // 108
-                DnsTypes::EUI64 => "EUI64",           // 109
-                DnsTypes::TKEY => "TKEY",             // 249
-                DnsTypes::TSIG => "TSIG",             // 250
-                DnsTypes::IXFR => "IXFR",             // 251
-                DnsTypes::AXFR => "AXFR",             // 252
-                DnsTypes::MAILB => "MAILB",           // 253
-                DnsTypes::MAILA => "MAILA",           // 254
-                DnsTypes::ANY => "ANY",               // 255
-                DnsTypes::URI => "URI",               // 256
-                DnsTypes::CAA => "CAA",               // 257
-                DnsTypes::AVC => "AVC",               // 258
-                DnsTypes::DOA => "DOA",               // 259
-                DnsTypes::AMTRELAY => "AMTRELAY",     // 260
-                DnsTypes::TA => "TA",                 // 32768
-                DnsTypes::DLV => "DLV",               // 32769
-                _ => "unknown",
+        match self.mnemonic() {
+            Some(mnemonic) => write!(f, "{}", mnemonic),
+            None => write!(f, "TYPE{}", self.0),
+        }
+    }
+}
+
+/// Ranges of type codes that have a mnemonic, used to resolve mnemonics back into types.
+#[cfg(feature = "alloc")]
+const KNOWN_TYPE_RANGES: [(u16, u16); 4] = [(1, 65), (99, 109), (249, 260), (32768, 32769)];
+
+/// Iterates over the types that have a mnemonic.
+#[cfg(feature = "alloc")]
+pub(crate) fn known_types() -> impl Iterator<Item = DnsType> {
+    KNOWN_TYPE_RANGES
+        .iter()
+        .flat_map(|&(start, end)| start..=end)
+        .map(DnsType)
+        .filter(|dns_type| dns_type.mnemonic().is_some())
+}
+
+#[cfg(feature = "alloc")]
+impl FromStr for DnsType {
+    type Err = DnsTypeParseError;
+
+    /// Parses a type mnemonic (`"MX"`) or its generic `TYPE<n>` form (`"TYPE15"`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(value) = s.strip_prefix("TYPE") {
+            if let Ok(value) = value.parse::<u16>() {
+                return Ok(DnsType(value));
+            }
+        }
+        known_types()
+            .find(|dns_type| dns_type.mnemonic() == Some(s))
+            .ok_or_else(|| DnsTypeParseError(s.to_string()))
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Debug, Error, PartialEq)]
+#[error("Unknown DNS type mnemonic: {0}")]
+pub struct DnsTypeParseError(String);
+
+#[cfg(feature = "serde")]
+impl Serialize for DnsType {
+    /// Serializes the type as its mnemonic, falling back to `TYPE<n>` for unknown types.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for DnsType {
+    /// Accepts a mnemonic, a `TYPE<n>` string or a bare integer.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DnsTypeVisitor;
+
+        impl Visitor<'_> for DnsTypeVisitor {
+            type Value = DnsType;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a DNS type mnemonic or a 16-bit integer")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<DnsType, E> {
+                v.parse().map_err(E::custom)
             }
-        )
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<DnsType, E> {
+                u16::try_from(v).map(DnsType).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(DnsTypeVisitor)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dns_type_from_str() {
+        assert_eq!("AAAA".parse(), Ok(DnsTypes::AAAA));
+        assert_eq!("DLV".parse(), Ok(DnsTypes::DLV));
+        assert_eq!("TYPE65280".parse(), Ok(DnsType(65280)));
+        assert!("FOO".parse::<DnsType>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_dns_type_serde() {
+        assert_eq!(serde_json::to_string(&DnsTypes::MX).unwrap(), "\"MX\"");
+        assert_eq!(
+            serde_json::to_string(&DnsType(65280)).unwrap(),
+            "\"TYPE65280\""
+        );
+        let dns_type: DnsType = serde_json::from_str("\"SRV\"").unwrap();
+        assert_eq!(dns_type, DnsTypes::SRV);
+        let dns_type: DnsType = serde_json::from_str("28").unwrap();
+        assert_eq!(dns_type, DnsTypes::AAAA);
     }
 }
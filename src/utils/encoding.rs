@@ -0,0 +1,137 @@
+//! The base64 (RFC 4648 §4) and base32hex (RFC 4648 §7) encodings of the DNSSEC
+//! presentation formats.
+
+use crate::prelude::*;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE32HEX: &[u8; 32] = b"0123456789ABCDEFGHIJKLMNOPQRSTUV";
+
+/// Encodes `bytes` in padded base64.
+pub(crate) fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let mut group = [0; 3];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let bits = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64[(bits >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Decodes padded base64, ignoring whitespace; returns `None` on invalid input.
+pub(crate) fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u8> = s.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if !digits.len().is_multiple_of(4) {
+        return None;
+    }
+    let mut decoded = Vec::with_capacity(digits.len() / 4 * 3);
+    for (index, chunk) in digits.chunks(4).enumerate() {
+        let last = index == digits.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|&&b| b == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return None;
+        }
+        let mut bits = 0u32;
+        for &digit in &chunk[..4 - padding] {
+            let value = BASE64.iter().position(|&b| b == digit)?;
+            bits = bits << 6 | value as u32;
+        }
+        bits <<= 6 * padding;
+        decoded.extend_from_slice(&bits.to_be_bytes()[1..4 - padding]);
+    }
+    Some(decoded)
+}
+
+/// Encodes `bytes` in unpadded base32hex, with uppercase digits.
+pub(crate) fn base32hex_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut bits = 0u16;
+    let mut count = 0;
+    for &byte in bytes {
+        bits = bits << 8 | byte as u16;
+        count += 8;
+        while count >= 5 {
+            count -= 5;
+            encoded.push(BASE32HEX[(bits >> count & 0x1F) as usize] as char);
+        }
+    }
+    if count > 0 {
+        encoded.push(BASE32HEX[(bits << (5 - count) & 0x1F) as usize] as char);
+    }
+    encoded
+}
+
+/// Decodes unpadded base32hex, in either case; returns `None` on invalid input.
+pub(crate) fn base32hex_decode(s: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(s.len() * 5 / 8);
+    let mut bits = 0u16;
+    let mut count = 0;
+    for digit in s.bytes() {
+        let digit = digit.to_ascii_uppercase();
+        let value = BASE32HEX.iter().position(|&b| b == digit)?;
+        bits = bits << 5 | value as u16;
+        count += 5;
+        if count >= 8 {
+            count -= 8;
+            decoded.push((bits >> count) as u8);
+        }
+    }
+    // The leftover bits must be zero padding of less than a digit.
+    if count >= 5 || bits & ((1 << count) - 1) != 0 {
+        return None;
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_round_trip() {
+        // RFC 4648 §10 test vectors.
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(base64_encode(plain.as_bytes()), encoded);
+            assert_eq!(base64_decode(encoded).unwrap(), plain.as_bytes());
+        }
+        assert_eq!(base64_decode("Zm9v YmFy").unwrap(), b"foobar");
+        assert!(base64_decode("Zm9").is_none());
+        assert!(base64_decode("Zg==Zm9v").is_none());
+    }
+
+    #[test]
+    fn test_base32hex_round_trip() {
+        // RFC 4648 §10 test vectors, without padding.
+        let vectors = [
+            ("", ""),
+            ("f", "CO"),
+            ("fo", "CPNG"),
+            ("foo", "CPNMU"),
+            ("foob", "CPNMUOG"),
+            ("fooba", "CPNMUOJ1"),
+            ("foobar", "CPNMUOJ1E8"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(base32hex_encode(plain.as_bytes()), encoded);
+            assert_eq!(base32hex_decode(encoded).unwrap(), plain.as_bytes());
+        }
+        assert_eq!(base32hex_decode("cpnmu").unwrap(), b"foo");
+        assert!(base32hex_decode("CP").is_none());
+        assert!(base32hex_decode("CPNMW").is_none());
+    }
+}
@@ -0,0 +1,70 @@
+use core::fmt::Write;
+
+use crate::prelude::*;
+
+const ROW: usize = 16;
+
+/// Renders `bytes` as a hexdump of 16 bytes per row, with a caret line under the byte at
+/// `mark` followed by `note`. `mark` may be past the end, to point at a missing byte.
+pub(crate) fn hexdump(bytes: &[u8], mark: usize, note: &str) -> String {
+    let mut dump = String::new();
+    let rows = bytes.len().max(mark + 1).div_ceil(ROW);
+    for row in 0..rows {
+        let start = row * ROW;
+        let chunk = bytes.get(start..).unwrap_or_default();
+        let chunk = &chunk[..chunk.len().min(ROW)];
+
+        let _ = write!(dump, "{:04x} ", start);
+        for i in 0..ROW {
+            if i == ROW / 2 {
+                dump.push(' ');
+            }
+            match chunk.get(i) {
+                Some(byte) => {
+                    let _ = write!(dump, " {:02x}", byte);
+                }
+                None => dump.push_str("   "),
+            }
+        }
+        dump.push_str("  |");
+        dump.extend(chunk.iter().map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            }
+        }));
+        dump.push_str("|\n");
+
+        if (start..start + ROW).contains(&mark) {
+            let column = mark - start;
+            let indent = 6 + column * 3 + usize::from(column >= ROW / 2);
+            let _ = writeln!(dump, "{:indent$}^^ {}", "", note, indent = indent);
+        }
+    }
+    dump
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hexdump_marks_byte() {
+        let bytes: Vec<u8> = (0x41..0x41 + 20).collect();
+        let dump = hexdump(&bytes, 9, "here");
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(
+            lines[0],
+            "0000  41 42 43 44 45 46 47 48  49 4a 4b 4c 4d 4e 4f 50  |ABCDEFGHIJKLMNOP|"
+        );
+        assert_eq!(lines[1], format!("{:34}^^ here", ""));
+        assert!(lines[2].starts_with("0010  51 52 53 54   "));
+        assert!(lines[2].ends_with("  |QRST|"));
+        assert_eq!(lines.len(), 3);
+
+        // Past the end: the caret points at the first missing byte.
+        let dump = hexdump(&bytes[..16], 16, "missing");
+        assert_eq!(dump.lines().last().unwrap(), "      ^^ missing");
+    }
+}
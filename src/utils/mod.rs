@@ -1,2 +1,10 @@
 pub mod dns_class;
 pub mod dns_types;
+#[cfg(feature = "alloc")]
+pub(crate) mod encoding;
+#[cfg(feature = "alloc")]
+pub(crate) mod hexdump;
+#[cfg(test)]
+pub(crate) mod pcap;
+#[cfg(feature = "serde")]
+pub(crate) mod serde_hex;
@@ -0,0 +1,69 @@
+//! A minimal reader for the classic pcap captures in `pcap_exemples`, used by the tests.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+/// A UDP datagram extracted from a capture.
+pub(crate) struct UdpDatagram {
+    #[cfg_attr(not(feature = "cdns"), allow(dead_code))]
+    pub time: Duration,
+    pub source: SocketAddr,
+    pub destination: SocketAddr,
+    pub payload: Vec<u8>,
+}
+
+/// Returns the UDP datagrams of a little-endian, microsecond, Ethernet pcap file.
+pub(crate) fn read_udp_datagrams(path: &str) -> Vec<UdpDatagram> {
+    let bytes = std::fs::read(path).unwrap();
+    assert_eq!(
+        &bytes[..4],
+        &[0xd4, 0xc3, 0xb2, 0xa1],
+        "unsupported pcap format"
+    );
+    let u32_at = |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+
+    let mut datagrams = Vec::new();
+    let mut offset = 24;
+    while offset + 16 <= bytes.len() {
+        let time = Duration::new(u32_at(offset) as u64, u32_at(offset + 4) * 1000);
+        let length = u32_at(offset + 8) as usize;
+        let frame = &bytes[offset + 16..offset + 16 + length];
+        offset += 16 + length;
+        if let Some((source, destination, payload)) = parse_frame(frame) {
+            datagrams.push(UdpDatagram {
+                time,
+                source,
+                destination,
+                payload: payload.to_vec(),
+            });
+        }
+    }
+    datagrams
+}
+
+fn parse_frame(frame: &[u8]) -> Option<(SocketAddr, SocketAddr, &[u8])> {
+    let ethertype = u16::from_be_bytes(frame.get(12..14)?.try_into().ok()?);
+    let ip = &frame[14..];
+    let (source, destination, udp) = match ethertype {
+        0x0800 if ip.get(9) == Some(&17) => {
+            let header_length = (ip[0] & 0x0F) as usize * 4;
+            let source = IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(&ip[12..16]).ok()?));
+            let destination = IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(&ip[16..20]).ok()?));
+            (source, destination, ip.get(header_length..)?)
+        }
+        0x86DD if ip.get(6) == Some(&17) => {
+            let source = IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(&ip[8..24]).ok()?));
+            let destination = IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(&ip[24..40]).ok()?));
+            (source, destination, ip.get(40..)?)
+        }
+        _ => return None,
+    };
+    let source_port = u16::from_be_bytes(udp.get(0..2)?.try_into().ok()?);
+    let destination_port = u16::from_be_bytes(udp.get(2..4)?.try_into().ok()?);
+    let length = u16::from_be_bytes(udp.get(4..6)?.try_into().ok()?) as usize;
+    Some((
+        SocketAddr::new(source, source_port),
+        SocketAddr::new(destination, destination_port),
+        udp.get(8..length)?,
+    ))
+}
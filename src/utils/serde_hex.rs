@@ -0,0 +1,14 @@
+//! Serde helpers encoding raw byte fields as lowercase hex strings.
+
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+use crate::prelude::*;
+
+pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hex::encode(bytes))
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    hex::decode(s).map_err(de::Error::custom)
+}
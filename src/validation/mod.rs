@@ -0,0 +1,282 @@
+//! Configurable validation of the rules checked while parsing.
+//!
+//! `DnsPacket::try_from` enforces every rule, some of which are stricter than what real
+//! servers emit (e.g. recursive resolvers answer NXDOMAIN with AA=0). A `ParseOptions`
+//! given to `DnsPacket::parse_with` decides, rule by rule, whether a violation is an error,
+//! a `Warning` attached to the packet, or ignored.
+
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use crate::prelude::*;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A validation rule, named after the anomaly it detects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Rule {
+    /// The reserved Z bit is set.
+    ReservedZBit,
+    /// The AD or CD bit is set, which RFC 1035 reserved as part of a 3-bit Z field
+    /// before RFC 4035 assigned them.
+    AdCdBits,
+    /// The opcode is above 5 (QUERY, IQUERY, STATUS, NOTIFY, UPDATE).
+    UnknownOpcode,
+    /// The rcode is above 5 (NOERROR to REFUSED).
+    UnknownRcode,
+    /// RA is set in a query.
+    RaInQuery,
+    /// AA or TC is set in a STATUS response.
+    AaTcInStatusResponse,
+    /// AA is set in a SERVFAIL response.
+    AaInServerFailure,
+    /// AA is not set in an NXDOMAIN response.
+    AaInNameError,
+    /// AA is set in a REFUSED response.
+    AaInRefused,
+    /// Records are declared without any question.
+    RecordsWithoutQuestion,
+    /// Bytes remain after the last declared record.
+    TrailingData,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 11] = [
+        Rule::ReservedZBit,
+        Rule::AdCdBits,
+        Rule::UnknownOpcode,
+        Rule::UnknownRcode,
+        Rule::RaInQuery,
+        Rule::AaTcInStatusResponse,
+        Rule::AaInServerFailure,
+        Rule::AaInNameError,
+        Rule::AaInRefused,
+        Rule::RecordsWithoutQuestion,
+        Rule::TrailingData,
+    ];
+}
+
+/// A set of rules, e.g. the rules a `DnsPacketRef` downgraded to warnings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RuleSet(u16);
+
+impl RuleSet {
+    pub fn contains(&self, rule: Rule) -> bool {
+        self.0 & (1 << rule as u16) != 0
+    }
+
+    pub fn insert(&mut self, rule: Rule) {
+        self.0 |= 1 << rule as u16;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Rule> + '_ {
+        Rule::ALL.into_iter().filter(|rule| self.contains(*rule))
+    }
+}
+
+/// What to do when a rule is broken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleAction {
+    /// Fail the parse.
+    Error,
+    /// Parse the packet and attach a `Warning` to it.
+    Warn,
+    /// Parse the packet silently.
+    Ignore,
+}
+
+/// Predefined sets of rule actions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ValidationProfile {
+    /// Every rule is an error; the behavior of `DnsPacket::try_from`.
+    #[default]
+    Strict,
+    /// Only what the RFCs require and trailing data are errors; conventions are warnings,
+    /// and the rules real servers routinely break (AD/CD bits, NXDOMAIN without AA) are
+    /// ignored.
+    Rfc,
+    /// Nothing is an error; unusual values are warnings, other rules are ignored.
+    Lenient,
+}
+
+impl ValidationProfile {
+    pub fn action(&self, rule: Rule) -> RuleAction {
+        use RuleAction::*;
+        match (self, rule) {
+            (ValidationProfile::Strict, _) => Error,
+            (ValidationProfile::Rfc, Rule::ReservedZBit | Rule::TrailingData) => Error,
+            (_, Rule::AdCdBits | Rule::AaInNameError) => Ignore,
+            (ValidationProfile::Rfc, _) => Warn,
+            (
+                ValidationProfile::Lenient,
+                Rule::ReservedZBit | Rule::UnknownOpcode | Rule::UnknownRcode | Rule::TrailingData,
+            ) => Warn,
+            (ValidationProfile::Lenient, _) => Ignore,
+        }
+    }
+}
+
+/// Options of `DnsPacket::parse_with`: a profile, with optional per-rule overrides.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ParseOptions {
+    profile: ValidationProfile,
+    overrides: [Option<RuleAction>; Rule::ALL.len()],
+}
+
+impl ParseOptions {
+    pub fn new(profile: ValidationProfile) -> Self {
+        ParseOptions {
+            profile,
+            overrides: Default::default(),
+        }
+    }
+
+    /// Overrides the profile's action for `rule`.
+    pub fn with_rule(mut self, rule: Rule, action: RuleAction) -> Self {
+        self.overrides[rule as usize] = Some(action);
+        self
+    }
+
+    pub fn action(&self, rule: Rule) -> RuleAction {
+        self.overrides[rule as usize].unwrap_or_else(|| self.profile.action(rule))
+    }
+
+    /// Applies the action of `rule` to its violation: fails with `error`, records it in
+    /// `warnings`, or does nothing.
+    pub(crate) fn check<E: fmt::Display>(
+        &self,
+        rule: Rule,
+        error: E,
+        warnings: &mut impl WarningSink,
+    ) -> Result<(), E> {
+        match self.action(rule) {
+            RuleAction::Error => Err(error),
+            RuleAction::Warn => {
+                warnings.warn(rule, &error);
+                Ok(())
+            }
+            RuleAction::Ignore => Ok(()),
+        }
+    }
+}
+
+/// Where `ParseOptions::check` records the violations downgraded to warnings: a list of
+/// `Warning`s, or just the set of rules when nothing may be allocated.
+pub(crate) trait WarningSink {
+    fn warn(&mut self, rule: Rule, error: &dyn fmt::Display);
+}
+
+#[cfg(feature = "alloc")]
+impl WarningSink for Vec<Warning> {
+    fn warn(&mut self, rule: Rule, error: &dyn fmt::Display) {
+        self.push(Warning {
+            rule,
+            message: error.to_string(),
+        });
+    }
+}
+
+impl WarningSink for RuleSet {
+    fn warn(&mut self, rule: Rule, _error: &dyn fmt::Display) {
+        self.insert(rule);
+    }
+}
+
+/// A broken rule that was downgraded to a warning.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Warning {
+    pub rule: Rule,
+    pub message: String,
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.rule, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles() {
+        assert_eq!(
+            ValidationProfile::Strict.action(Rule::AaInNameError),
+            RuleAction::Error
+        );
+        assert_eq!(
+            ValidationProfile::Rfc.action(Rule::AaInNameError),
+            RuleAction::Ignore
+        );
+        assert_eq!(
+            ValidationProfile::Rfc.action(Rule::ReservedZBit),
+            RuleAction::Error
+        );
+        assert_eq!(
+            ValidationProfile::Rfc.action(Rule::RaInQuery),
+            RuleAction::Warn
+        );
+        assert_eq!(
+            ValidationProfile::Lenient.action(Rule::UnknownRcode),
+            RuleAction::Warn
+        );
+        assert_eq!(
+            ValidationProfile::Lenient.action(Rule::RecordsWithoutQuestion),
+            RuleAction::Ignore
+        );
+    }
+
+    #[test]
+    fn test_rule_set() {
+        let mut rules = RuleSet::default();
+        assert!(rules.is_empty());
+        rules.insert(Rule::TrailingData);
+        rules.insert(Rule::AdCdBits);
+        assert!(rules.contains(Rule::AdCdBits));
+        assert!(!rules.contains(Rule::ReservedZBit));
+        assert_eq!(
+            rules.iter().collect::<Vec<_>>(),
+            [Rule::AdCdBits, Rule::TrailingData]
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_overrides() {
+        let options =
+            ParseOptions::new(ValidationProfile::Rfc).with_rule(Rule::RaInQuery, RuleAction::Error);
+        assert_eq!(options.action(Rule::RaInQuery), RuleAction::Error);
+        assert_eq!(options.action(Rule::AaInRefused), RuleAction::Warn);
+
+        let mut warnings = Vec::new();
+        assert_eq!(
+            options.check(Rule::RaInQuery, "RA set", &mut warnings),
+            Err("RA set")
+        );
+        assert_eq!(
+            options.check(Rule::AaInRefused, "AA set", &mut warnings),
+            Ok(())
+        );
+        assert_eq!(
+            options.check(Rule::AdCdBits, "AD set", &mut warnings),
+            Ok(())
+        );
+        assert_eq!(
+            warnings,
+            [Warning {
+                rule: Rule::AaInRefused,
+                message: "AA set".to_string()
+            }]
+        );
+    }
+}
@@ -0,0 +1,54 @@
+//! Checks that `DnsPacketRef` parses without allocating. The counting allocator replaces
+//! the global one for this test binary only.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use detect_dns_packet::validation::{ParseOptions, Rule, ValidationProfile};
+use detect_dns_packet::DnsPacketRef;
+
+/// Counts the allocations of the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// Response for example.com A with two answers, the owner names compressed.
+const RESPONSE: &str = "abcd81800001000200000000076578616d706c6503636f6d0000010001\
+                        c00c000100010000012c00045db8d822\
+                        03777777c00c000100010000012c00045db8d823";
+
+#[test]
+fn test_packet_ref_does_not_allocate() {
+    let mut bytes = hex::decode(RESPONSE).unwrap();
+    bytes.push(0);
+    let options = ParseOptions::new(ValidationProfile::Lenient);
+
+    let before = ALLOCATIONS.with(Cell::get);
+    let packet = DnsPacketRef::parse_with(&bytes, &options).unwrap();
+    let question = packet.questions().next().unwrap();
+    assert!(question.name.eq_ignore_ascii_case("example.com"));
+    let addresses = packet
+        .answers()
+        .filter(|answer| answer.name.labels().count() == 3)
+        .map(|answer| answer.rdata[3])
+        .fold(0, |sum, byte| sum + byte as usize);
+    assert_eq!(addresses, 0x23);
+    assert!(packet.warnings.contains(Rule::TrailingData));
+    assert_eq!(ALLOCATIONS.with(Cell::get), before);
+}